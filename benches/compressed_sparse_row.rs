@@ -0,0 +1,81 @@
+/*
+Compares the algorithms of `CompressedSparseRowGraph` with the same algorithms
+on a `BTreeMap` of neighbours (or a `Vec<Vec<usize>>`), on a random graph with
+200000 vertices and 2000000 edges.
+
+Run with `cargo bench --bench compressed_sparse_row`.
+*/
+
+use std::time::Instant;
+
+use the_algorithms_rust::graph::{
+    breadth_first_search, dijkstra, AdjacencyMap, CompressedSparseRowGraph, Graph,
+    StronglyConnectedComponents,
+};
+use the_algorithms_rust::math::PCG32;
+
+fn random_graph(num_vertices: usize, num_edges: usize, seed: u64) -> AdjacencyMap<usize, u64> {
+    let mut rng = PCG32::new_default(seed);
+    let mut graph: AdjacencyMap<usize, u64> =
+        (0..num_vertices).map(|v| (v, Default::default())).collect();
+    for _ in 0..num_edges {
+        let u = rng.get_u32() as usize % num_vertices;
+        let v = rng.get_u32() as usize % num_vertices;
+        let w = (rng.get_u32() % 1000) as u64;
+        graph.get_mut(&u).unwrap().insert(v, w);
+    }
+    graph
+}
+
+fn main() {
+    let num_vertices = 2e5 as usize;
+    let map = random_graph(num_vertices, 2e6 as usize, 2718);
+    let (graph, _) = CompressedSparseRowGraph::from_graph(&map);
+
+    let now = Instant::now();
+    let from_map = dijkstra(&map, &0);
+    let map_time = now.elapsed();
+    let now = Instant::now();
+    let from_csr = graph.dijkstra(0);
+    let csr_time = now.elapsed();
+    assert_eq!(from_map.len(), from_csr.iter().flatten().count());
+    println!("dijkstra: map {map_time:?}, compressed sparse row {csr_time:?}");
+
+    let now = Instant::now();
+    let from_map = breadth_first_search(&map, 0, num_vertices);
+    let map_time = now.elapsed();
+    let now = Instant::now();
+    let from_csr = graph.breadth_first_search(0, num_vertices);
+    let csr_time = now.elapsed();
+    assert_eq!(from_map, from_csr);
+    println!("breadth first search: map {map_time:?}, compressed sparse row {csr_time:?}");
+
+    let adj: Vec<Vec<usize>> = (0..=num_vertices)
+        .map(|v| match v {
+            0 => vec![],
+            v => map[&(v - 1)].keys().map(|u| u + 1).collect(),
+        })
+        .collect();
+    let shifted: Vec<(usize, usize, u64)> =
+        map.edges().map(|(u, v, w)| (u + 1, v + 1, w)).collect();
+    let shifted = CompressedSparseRowGraph::from_edges(num_vertices + 1, &shifted);
+    // Tarjan's algorithm is recursive, give it enough stack
+    let handler = std::thread::Builder::new()
+        .stack_size(512 * 1024 * 1024)
+        .spawn(move || {
+            let now = Instant::now();
+            let mut from_adj = StronglyConnectedComponents::new(num_vertices);
+            from_adj.find_components(&adj);
+            let adj_time = now.elapsed();
+            let now = Instant::now();
+            let mut from_csr = StronglyConnectedComponents::new(num_vertices);
+            from_csr.find_components_compressed(&shifted);
+            let csr_time = now.elapsed();
+            assert_eq!(from_adj.component, from_csr.component);
+            println!(
+                "strongly connected components: Vec<Vec<usize>> {adj_time:?}, compressed sparse row {csr_time:?}"
+            );
+        })
+        .unwrap();
+    handler.join().unwrap();
+}
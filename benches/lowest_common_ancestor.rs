@@ -0,0 +1,71 @@
+/*
+Compares the Euler tour lowest common ancestor (`LowestCommonAncestorEulerTour`)
+with binary lifting (`LowestCommonAncestorOnline`) on a random tree with
+1000000 vertices, both to build them and to answer 1000000 queries.
+
+Run with `cargo bench --bench lowest_common_ancestor`.
+*/
+
+use std::time::Instant;
+
+use the_algorithms_rust::graph::{
+    random_tree, LowestCommonAncestorEulerTour, LowestCommonAncestorOnline,
+};
+use the_algorithms_rust::math::PCG32;
+
+fn random_edges(n: usize, rng: &mut PCG32) -> Vec<(usize, usize)> {
+    random_tree(n, rng)
+        .into_iter()
+        .flat_map(|(u, neighbors)| neighbors.into_iter().map(move |v| (u, v)))
+        .filter(|&(u, v)| u < v)
+        .collect()
+}
+
+fn main() {
+    let n = 1e6 as usize;
+    let mut rng = PCG32::new_default(27182);
+    let edges = random_edges(n, &mut rng);
+    let queries: Vec<(usize, usize)> = (0..n)
+        .map(|_| {
+            (
+                1 + rng.get_u32() as usize % n,
+                1 + rng.get_u32() as usize % n,
+            )
+        })
+        .collect();
+    // `fill_sparse_table` is recursive, give it enough stack
+    let handler = std::thread::Builder::new()
+        .stack_size(512 * 1024 * 1024)
+        .spawn(move || {
+            let now = Instant::now();
+            let mut tree = vec![vec![]; n + 1];
+            for &(u, v) in &edges {
+                tree[u].push(v);
+                tree[v].push(u);
+            }
+            let mut online = LowestCommonAncestorOnline::new(n);
+            online.fill_sparse_table(1, 0, 0, &tree);
+            let online_build = now.elapsed();
+            let now = Instant::now();
+            let euler = LowestCommonAncestorEulerTour::from_edges(n, 1, &edges);
+            let euler_build = now.elapsed();
+
+            let now = Instant::now();
+            let from_online: Vec<usize> = queries
+                .iter()
+                .map(|&(u, v)| online.get_ancestor(u, v))
+                .collect();
+            let online_queries = now.elapsed();
+            let now = Instant::now();
+            let from_euler: Vec<usize> = queries
+                .iter()
+                .map(|&(u, v)| euler.get_ancestor(u, v))
+                .collect();
+            let euler_queries = now.elapsed();
+            assert_eq!(from_online, from_euler);
+            println!("build: binary lifting {online_build:?}, euler tour {euler_build:?}");
+            println!("queries: binary lifting {online_queries:?}, euler tour {euler_queries:?}");
+        })
+        .unwrap();
+    handler.join().unwrap();
+}
@@ -0,0 +1,52 @@
+/*
+Compares `PushRelabelMaxFlow` with `DinicMaxFlow` on a dense random network
+with 1000 vertices and 250000 edges.
+
+Run with `cargo bench --bench push_relabel`.
+*/
+
+use std::time::Instant;
+
+use the_algorithms_rust::graph::{DinicMaxFlow, MaxFlow, PushRelabelMaxFlow};
+use the_algorithms_rust::math::PCG32;
+
+fn random_network<M: MaxFlow<i64>>(
+    rng: &mut PCG32,
+    num_vertices: usize,
+    num_edges: usize,
+    max_capacity: u32,
+) -> M {
+    let mut flow = M::new(1, num_vertices, num_vertices);
+    for _ in 0..num_edges {
+        let u = (rng.get_u32() as usize % num_vertices) + 1;
+        let v = (rng.get_u32() as usize % num_vertices) + 1;
+        flow.add_edge(u, v, (rng.get_u32() % max_capacity) as i64);
+    }
+    flow
+}
+
+fn main() {
+    let num_vertices = 1000;
+    let num_edges = 250_000;
+    let mut dinic: DinicMaxFlow<i64> = random_network(
+        &mut PCG32::new_default(99),
+        num_vertices,
+        num_edges,
+        1_000_000,
+    );
+    let mut push_relabel: PushRelabelMaxFlow<i64> = random_network(
+        &mut PCG32::new_default(99),
+        num_vertices,
+        num_edges,
+        1_000_000,
+    );
+
+    let now = Instant::now();
+    let expected = MaxFlow::find_maxflow(&mut dinic, i64::MAX);
+    let dinic_time = now.elapsed();
+    let now = Instant::now();
+    let found = MaxFlow::find_maxflow(&mut push_relabel, i64::MAX);
+    let push_relabel_time = now.elapsed();
+    assert_eq!(found, expected);
+    println!("dense max flow: dinic {dinic_time:?}, push-relabel {push_relabel_time:?}");
+}
@@ -61,6 +61,70 @@ impl Graph for UndirectedGraph {
     }
 }
 
+// Both graphs can be given to the algorithms of `crate::graph` by reference,
+// with borrowed `&str` vertices: `dijkstra(&&graph, &"a")`. The vertices are
+// listed in sorted order, so the results don't depend on the hash map.
+type AdjacencyTable = HashMap<String, Vec<(String, i32)>>;
+
+fn sorted_nodes(table: &AdjacencyTable) -> impl Iterator<Item = &str> {
+    let mut nodes: Vec<&str> = table.keys().map(String::as_str).collect();
+    nodes.sort_unstable();
+    nodes.into_iter()
+}
+
+fn neighbours_of<'a>(
+    table: &'a AdjacencyTable,
+    node: &str,
+) -> impl Iterator<Item = (&'a str, i32)> {
+    table
+        .get(node)
+        .into_iter()
+        .flatten()
+        .map(|(to_node, weight)| (to_node.as_str(), *weight))
+}
+
+impl<'a> crate::graph::Graph for &'a DirectedGraph {
+    type Vertex = &'a str;
+    type Weight = i32;
+
+    fn num_vertices(&self) -> usize {
+        self.adjacency_table.len()
+    }
+
+    fn vertices(&self) -> impl Iterator<Item = &'a str> + '_ {
+        sorted_nodes(&self.adjacency_table)
+    }
+
+    fn neighbors(&self, vertex: &'a str) -> impl Iterator<Item = (&'a str, i32)> + '_ {
+        neighbours_of(&self.adjacency_table, vertex)
+    }
+
+    fn contains_vertex(&self, vertex: &'a str) -> bool {
+        self.adjacency_table.contains_key(vertex)
+    }
+}
+
+impl<'a> crate::graph::Graph for &'a UndirectedGraph {
+    type Vertex = &'a str;
+    type Weight = i32;
+
+    fn num_vertices(&self) -> usize {
+        self.adjacency_table.len()
+    }
+
+    fn vertices(&self) -> impl Iterator<Item = &'a str> + '_ {
+        sorted_nodes(&self.adjacency_table)
+    }
+
+    fn neighbors(&self, vertex: &'a str) -> impl Iterator<Item = (&'a str, i32)> + '_ {
+        neighbours_of(&self.adjacency_table, vertex)
+    }
+
+    fn contains_vertex(&self, vertex: &'a str) -> bool {
+        self.adjacency_table.contains_key(vertex)
+    }
+}
+
 pub trait Graph {
     fn new() -> Self;
     fn adjacency_table_mutable(&mut self) -> &mut HashMap<String, Vec<(String, i32)>>;
@@ -218,3 +282,45 @@ mod test_directed_graph {
         assert_eq!(graph.contains("d"), false);
     }
 }
+
+#[cfg(test)]
+mod test_generic_graph {
+    use super::{DirectedGraph, Graph, UndirectedGraph};
+    use crate::graph::{breadth_first_search, dijkstra, prim, topological_sort, Graph as _};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn graph_algorithms() {
+        let mut graph = UndirectedGraph::new();
+        graph.add_edge(("a", "b", 5));
+        graph.add_edge(("b", "c", 10));
+        graph.add_edge(("c", "a", 7));
+        graph.add_node("d");
+
+        let view = &graph;
+        assert_eq!(view.num_vertices(), 4);
+        assert_eq!(
+            view.vertices().collect::<Vec<_>>(),
+            vec!["a", "b", "c", "d"]
+        );
+        assert_eq!(view.num_edges(), 6);
+        assert_eq!(
+            dijkstra(&view, &"b"),
+            BTreeMap::from([("a", Some(("b", 5))), ("b", None), ("c", Some(("b", 10)))])
+        );
+        assert_eq!(breadth_first_search(&view, "a", "d"), None);
+        let tree = prim(&view);
+        assert_eq!(tree["a"], BTreeMap::from([("b", 5), ("c", 7)]));
+
+        let mut dag = DirectedGraph::new();
+        dag.add_edge(("shirt", "tie", 1));
+        dag.add_edge(("tie", "jacket", 1));
+        dag.add_edge(("trousers", "shoes", 1));
+        dag.add_edge(("trousers", "jacket", 1));
+        let order = topological_sort(&&dag).unwrap();
+        let position = |node: &str| order.iter().position(|&n| n == node).unwrap();
+        assert!(position("shirt") < position("tie"));
+        assert!(position("tie") < position("jacket"));
+        assert!(position("trousers") < position("shoes"));
+    }
+}
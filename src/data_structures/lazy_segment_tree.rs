@@ -0,0 +1,345 @@
+use std::marker::PhantomData;
+use std::ops::Add;
+
+// What a `LazySegmentTree` stores and how: values are aggregated with an
+// associative `combine`, and updates are applied to whole ranges at once.
+pub trait LazyMonoid {
+    type Value: Copy;
+    type Update: Copy;
+
+    // The aggregate of an empty range: `combine(identity(), x) == x`
+    fn identity() -> Self::Value;
+    fn combine(left: Self::Value, right: Self::Value) -> Self::Value;
+    // The aggregate of the same values in the opposite order. The default,
+    // the value unchanged, is right when `combine` is commutative.
+    fn reverse(value: Self::Value) -> Self::Value {
+        value
+    }
+
+    // The update that changes nothing
+    fn no_update() -> Self::Update;
+    // The update doing `older` first and then `newer`
+    fn compose(newer: Self::Update, older: Self::Update) -> Self::Update;
+    // The aggregate of `len` values, once `update` is applied to each of them
+    fn apply(value: Self::Value, update: Self::Update, len: usize) -> Self::Value;
+}
+
+// `x + x + ... + x` (n times) in O(log n) additions
+fn repeat_add<T: Add<Output = T> + Copy + Default>(mut x: T, mut n: usize) -> T {
+    let mut sum = T::default();
+    while n > 0 {
+        if n % 2 == 1 {
+            sum = sum + x;
+        }
+        n /= 2;
+        // doubling x once more than needed could overflow
+        if n > 0 {
+            x = x + x;
+        }
+    }
+    sum
+}
+
+// Range sums, and adding a value to every element of a range
+pub struct RangeSum<T>(PhantomData<T>);
+
+impl<T: Add<Output = T> + Copy + Default> LazyMonoid for RangeSum<T> {
+    type Value = T;
+    type Update = T;
+
+    fn identity() -> T {
+        T::default()
+    }
+    fn combine(left: T, right: T) -> T {
+        left + right
+    }
+    fn no_update() -> T {
+        T::default()
+    }
+    fn compose(newer: T, older: T) -> T {
+        newer + older
+    }
+    fn apply(value: T, update: T, len: usize) -> T {
+        value + repeat_add(update, len)
+    }
+}
+
+// Range minimums (None for an empty range), and adding a value to every
+// element of a range
+pub struct RangeMin<T>(PhantomData<T>);
+
+impl<T: Add<Output = T> + Ord + Copy + Default> LazyMonoid for RangeMin<T> {
+    type Value = Option<T>;
+    type Update = T;
+
+    fn identity() -> Option<T> {
+        None
+    }
+    fn combine(left: Option<T>, right: Option<T>) -> Option<T> {
+        match (left, right) {
+            (Some(l), Some(r)) => Some(l.min(r)),
+            (l, r) => l.or(r),
+        }
+    }
+    fn no_update() -> T {
+        T::default()
+    }
+    fn compose(newer: T, older: T) -> T {
+        newer + older
+    }
+    fn apply(value: Option<T>, update: T, _len: usize) -> Option<T> {
+        value.map(|v| v + update)
+    }
+}
+
+// Range maximums (None for an empty range), and adding a value to every
+// element of a range
+pub struct RangeMax<T>(PhantomData<T>);
+
+impl<T: Add<Output = T> + Ord + Copy + Default> LazyMonoid for RangeMax<T> {
+    type Value = Option<T>;
+    type Update = T;
+
+    fn identity() -> Option<T> {
+        None
+    }
+    fn combine(left: Option<T>, right: Option<T>) -> Option<T> {
+        match (left, right) {
+            (Some(l), Some(r)) => Some(l.max(r)),
+            (l, r) => l.or(r),
+        }
+    }
+    fn no_update() -> T {
+        T::default()
+    }
+    fn compose(newer: T, older: T) -> T {
+        newer + older
+    }
+    fn apply(value: Option<T>, update: T, _len: usize) -> Option<T> {
+        value.map(|v| v + update)
+    }
+}
+
+// A segment tree with lazy propagation: both queries and updates on a range
+// [l, r] take O(log n). What is stored, and what an update does, is defined
+// by `M`.
+pub struct LazySegmentTree<M: LazyMonoid> {
+    len: usize,
+    // The aggregate of the range of each node, with its own pending update
+    // already applied
+    values: Vec<M::Value>,
+    // The update that still has to be applied to the children of each node
+    pending: Vec<M::Update>,
+}
+
+impl<M: LazyMonoid> LazySegmentTree<M> {
+    // Builds the tree over the values of `arr`
+    pub fn from_vec(arr: &[M::Value]) -> Self {
+        let len = arr.len();
+        let mut tree = LazySegmentTree {
+            len,
+            values: vec![M::identity(); 4 * len.max(1)],
+            pending: vec![M::no_update(); 4 * len.max(1)],
+        };
+        if len > 0 {
+            tree.build(1, 0, len - 1, arr);
+        }
+        tree
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn build(&mut self, node: usize, start: usize, end: usize, arr: &[M::Value]) {
+        if start == end {
+            self.values[node] = arr[start];
+            return;
+        }
+        let mid = (start + end) / 2;
+        self.build(2 * node, start, mid, arr);
+        self.build(2 * node + 1, mid + 1, end, arr);
+        self.values[node] = M::combine(self.values[2 * node], self.values[2 * node + 1]);
+    }
+
+    fn apply_to_node(&mut self, node: usize, update: M::Update, len: usize) {
+        self.values[node] = M::apply(self.values[node], update, len);
+        self.pending[node] = M::compose(update, self.pending[node]);
+    }
+
+    fn push_down(&mut self, node: usize, start: usize, end: usize) {
+        let mid = (start + end) / 2;
+        let update = self.pending[node];
+        self.apply_to_node(2 * node, update, mid - start + 1);
+        self.apply_to_node(2 * node + 1, update, end - mid);
+        self.pending[node] = M::no_update();
+    }
+
+    fn query_node(
+        &mut self,
+        node: usize,
+        start: usize,
+        end: usize,
+        l: usize,
+        r: usize,
+    ) -> M::Value {
+        if r < start || end < l {
+            return M::identity();
+        }
+        if l <= start && end <= r {
+            return self.values[node];
+        }
+        self.push_down(node, start, end);
+        let mid = (start + end) / 2;
+        M::combine(
+            self.query_node(2 * node, start, mid, l, r),
+            self.query_node(2 * node + 1, mid + 1, end, l, r),
+        )
+    }
+
+    fn update_node(
+        &mut self,
+        node: usize,
+        start: usize,
+        end: usize,
+        l: usize,
+        r: usize,
+        update: M::Update,
+    ) {
+        if r < start || end < l {
+            return;
+        }
+        if l <= start && end <= r {
+            self.apply_to_node(node, update, end - start + 1);
+            return;
+        }
+        self.push_down(node, start, end);
+        let mid = (start + end) / 2;
+        self.update_node(2 * node, start, mid, l, r, update);
+        self.update_node(2 * node + 1, mid + 1, end, l, r, update);
+        self.values[node] = M::combine(self.values[2 * node], self.values[2 * node + 1]);
+    }
+
+    // The aggregate of the interval [l, r] (the identity if it is empty,
+    // i.e. l > r). It takes `&mut self` because pending updates are pushed
+    // down on the way.
+    pub fn query(&mut self, l: usize, r: usize) -> M::Value {
+        if l > r || self.len == 0 {
+            return M::identity();
+        }
+        self.query_node(1, 0, self.len - 1, l, r)
+    }
+
+    // Applies `update` to every element of the interval [l, r]
+    pub fn update(&mut self, l: usize, r: usize, update: M::Update) {
+        if l > r || self.len == 0 {
+            return;
+        }
+        self.update_node(1, 0, self.len - 1, l, r, update);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::PCG32;
+
+    #[test]
+    fn sum_and_add() {
+        let vec = vec![1, 2, -4, 7, 3, -5, 6, 11, -20, 9];
+        let mut tree: LazySegmentTree<RangeSum<i64>> = LazySegmentTree::from_vec(&vec);
+        assert_eq!(tree.len(), 10);
+        assert_eq!(tree.query(0, 9), 10);
+        assert_eq!(tree.query(2, 4), 6);
+        tree.update(1, 5, 10);
+        assert_eq!(tree.query(0, 9), 60);
+        assert_eq!(tree.query(5, 6), 11);
+        assert_eq!(tree.query(4, 3), 0);
+    }
+
+    #[test]
+    fn add_close_to_overflow() {
+        let mut tree: LazySegmentTree<RangeSum<i64>> = LazySegmentTree::from_vec(&[0]);
+        tree.update(0, 0, 1 << 62);
+        assert_eq!(tree.query(0, 0), 1 << 62);
+        let mut tree: LazySegmentTree<RangeSum<i64>> = LazySegmentTree::from_vec(&[0; 3]);
+        tree.update(0, 2, 1 << 61);
+        assert_eq!(tree.query(0, 2), 3 << 61);
+    }
+
+    #[test]
+    fn min_max_and_add() {
+        let vec: Vec<Option<i32>> = [5, 3, 8, 1, 9, 2].iter().map(|&x| Some(x)).collect();
+        let mut min_tree: LazySegmentTree<RangeMin<i32>> = LazySegmentTree::from_vec(&vec);
+        let mut max_tree: LazySegmentTree<RangeMax<i32>> = LazySegmentTree::from_vec(&vec);
+        assert_eq!(min_tree.query(0, 2), Some(3));
+        assert_eq!(max_tree.query(3, 5), Some(9));
+        min_tree.update(3, 3, 10);
+        max_tree.update(0, 2, 5);
+        assert_eq!(min_tree.query(0, 5), Some(2));
+        assert_eq!(min_tree.query(3, 3), Some(11));
+        assert_eq!(max_tree.query(0, 5), Some(13));
+        assert_eq!(max_tree.query(5, 4), None);
+    }
+
+    // Keeps the value of the first element of a range, and replaces every
+    // element with a constant: neither combine nor compose is commutative
+    struct FirstAssign;
+
+    impl LazyMonoid for FirstAssign {
+        type Value = Option<char>;
+        type Update = Option<char>;
+
+        fn identity() -> Option<char> {
+            None
+        }
+        fn combine(left: Option<char>, right: Option<char>) -> Option<char> {
+            left.or(right)
+        }
+        fn no_update() -> Option<char> {
+            None
+        }
+        fn compose(newer: Option<char>, older: Option<char>) -> Option<char> {
+            newer.or(older)
+        }
+        fn apply(value: Option<char>, update: Option<char>, _len: usize) -> Option<char> {
+            update.or(value)
+        }
+    }
+
+    #[test]
+    fn custom_monoid() {
+        let vec: Vec<Option<char>> = "abcdefgh".chars().map(Some).collect();
+        let mut tree: LazySegmentTree<FirstAssign> = LazySegmentTree::from_vec(&vec);
+        assert_eq!(tree.query(3, 7), Some('d'));
+        tree.update(2, 5, Some('x'));
+        tree.update(4, 7, Some('y'));
+        let values: String = (0..8).map(|i| tree.query(i, i).unwrap()).collect();
+        assert_eq!(values, "abxxyyyy");
+        assert_eq!(tree.query(3, 7), Some('x'));
+    }
+
+    #[test]
+    fn same_as_naive() {
+        let mut rng = PCG32::new_default(27);
+        let n = 50;
+        let mut naive: Vec<i64> = (0..n).map(|_| (rng.get_u32() % 100) as i64).collect();
+        let mut tree: LazySegmentTree<RangeSum<i64>> = LazySegmentTree::from_vec(&naive);
+        for _ in 0..1000 {
+            let a = rng.get_u32() as usize % n;
+            let b = rng.get_u32() as usize % n;
+            let (l, r) = (a.min(b), a.max(b));
+            if rng.get_u32().is_multiple_of(2) {
+                let x = (rng.get_u32() % 21) as i64 - 10;
+                tree.update(l, r, x);
+                naive[l..=r].iter_mut().for_each(|v| *v += x);
+            } else {
+                assert_eq!(tree.query(l, r), naive[l..=r].iter().sum::<i64>());
+            }
+        }
+    }
+}
@@ -4,6 +4,7 @@ mod binary_search_tree;
 mod fenwick_tree;
 mod graph;
 mod heap;
+mod lazy_segment_tree;
 mod linked_list;
 mod queue;
 mod rb_tree;
@@ -20,6 +21,7 @@ pub use self::fenwick_tree::FenwickTree;
 pub use self::graph::DirectedGraph;
 pub use self::graph::UndirectedGraph;
 pub use self::heap::Heap;
+pub use self::lazy_segment_tree::{LazyMonoid, LazySegmentTree, RangeMax, RangeMin, RangeSum};
 pub use self::linked_list::LinkedList;
 pub use self::queue::Queue;
 pub use self::rb_tree::RBTree;
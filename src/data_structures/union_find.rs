@@ -1,58 +1,38 @@
-/// UnionFind data structure
+use crate::graph::DisjointSetUnion;
+
+/// UnionFind data structure, a thin wrapper over `graph::DisjointSetUnion`
+/// with the elements 0 to n - 1
 pub struct UnionFind {
-    id: Vec<usize>,
-    size: Vec<usize>,
-    count: usize,
+    // n + 1 sets, the last one (n) is never used
+    sets: DisjointSetUnion,
 }
 
 impl UnionFind {
     /// Creates a new UnionFind data structure with n elements
     pub fn new(n: usize) -> Self {
-        let mut id = vec![0; n];
-        let mut size = vec![0; n];
-        for i in 0..n {
-            id[i] = i;
-            size[i] = 1;
+        Self {
+            sets: DisjointSetUnion::new(n),
         }
-        Self { id, size, count: n }
     }
 
-    /// Returns the parent of the element
+    /// Returns the representative of the set of the element
     pub fn find(&mut self, x: usize) -> usize {
-        let mut x = x;
-        while x != self.id[x] {
-            x = self.id[x];
-            // self.id[x] = self.id[self.id[x]]; // path compression
-        }
-        x
+        self.sets.find_set(x)
     }
 
     /// Unions the sets containing x and y
     pub fn union(&mut self, x: usize, y: usize) -> bool {
-        let x = self.find(x);
-        let y = self.find(y);
-        if x == y {
-            return false;
-        }
-        if self.size[x] < self.size[y] {
-            self.id[x] = y;
-            self.size[y] += self.size[x];
-        } else {
-            self.id[y] = x;
-            self.size[x] += self.size[y];
-        }
-        self.count -= 1;
-        true
+        self.sets.merge(x, y) != usize::MAX
     }
 
     /// Checks if x and y are in the same set
     pub fn is_same_set(&mut self, x: usize, y: usize) -> bool {
-        self.find(x) == self.find(y)
+        self.sets.same_set(x, y)
     }
 
     /// Returns the number of disjoint sets
     pub fn count(&self) -> usize {
-        self.count
+        self.sets.num_sets() - 1
     }
 }
 
@@ -0,0 +1,159 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap};
+use std::ops::Add;
+
+use super::{reconstruct_path, Graph};
+
+// performs the A* search on the given graph, from start to target
+// the graph is a positively-weighted directed graph
+//
+// `heuristic(v)` estimates the distance from v to target. It should be
+// admissible, i.e. never greater than the real distance, otherwise the path
+// may not be a shortest one. The closer it is to the real distance, the fewer
+// vertices are explored; with `heuristic(v) == 0` it is the same as dijkstra.
+//
+// returns the vertices of the path (start and target included) and its total
+// weight, or None if target is unreachable. E::default() should be zero.
+pub fn a_star<G: Graph, H: Fn(G::Vertex) -> G::Weight>(
+    graph: &G,
+    start: &G::Vertex,
+    target: &G::Vertex,
+    heuristic: H,
+) -> Option<(Vec<G::Vertex>, G::Weight)>
+where
+    G::Weight: Ord + Add<Output = G::Weight> + Default,
+{
+    // same as in dijkstra: the predecessor and the distance of each vertex
+    let mut ans = BTreeMap::new();
+    // the elements are the estimated length of a path through the vertex,
+    // its distance from start and the vertex itself
+    let mut prio = BinaryHeap::new();
+
+    ans.insert(*start, None);
+    prio.push(Reverse((heuristic(*start), G::Weight::default(), *start)));
+
+    while let Some(Reverse((_, dist, current))) = prio.pop() {
+        if current == *target {
+            return reconstruct_path(&ans, target);
+        }
+        let best = match ans[&current] {
+            Some((_, d)) => d,
+            None => G::Weight::default(),
+        };
+        // a shorter path to current was found after this one was pushed
+        if dist > best {
+            continue;
+        }
+
+        for (next, weight) in graph.neighbors(current) {
+            let dist_next = dist + weight;
+            match ans.get(&next) {
+                Some(Some((_, d))) if *d <= dist_next => {}
+                // next is start
+                Some(None) => {}
+                // the vertex is pushed again even if it was already popped,
+                // because a heuristic that is only admissible can make us
+                // find a shorter path to it later
+                _ => {
+                    ans.insert(next, Some((current, dist_next)));
+                    prio.push(Reverse((dist_next + heuristic(next), dist_next, next)));
+                }
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::a_star;
+    use crate::graph::{shortest_path, AdjacencyMap};
+    use crate::math::PCG32;
+
+    type Cell = (i32, i32);
+
+    fn grid(width: i32, height: i32, walls: &[Cell]) -> AdjacencyMap<Cell, i32> {
+        let mut graph = AdjacencyMap::new();
+        for x in 0..width {
+            for y in 0..height {
+                let edges = graph.entry((x, y)).or_insert_with(Default::default);
+                if walls.contains(&(x, y)) {
+                    continue;
+                }
+                for (nx, ny) in [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)] {
+                    if (0..width).contains(&nx)
+                        && (0..height).contains(&ny)
+                        && !walls.contains(&(nx, ny))
+                    {
+                        edges.insert((nx, ny), 1);
+                    }
+                }
+            }
+        }
+        graph
+    }
+
+    fn manhattan(target: Cell) -> impl Fn(Cell) -> i32 {
+        move |(x, y)| (x - target.0).abs() + (y - target.1).abs()
+    }
+
+    #[test]
+    fn open_grid() {
+        let graph = grid(10, 10, &[]);
+        let (path, cost) = a_star(&graph, &(0, 0), &(9, 9), manhattan((9, 9))).unwrap();
+        assert_eq!(cost, 18);
+        assert_eq!(path.len(), 19);
+        assert_eq!(path[0], (0, 0));
+        assert_eq!(path[18], (9, 9));
+        for step in path.windows(2) {
+            assert_eq!(manhattan(step[0])(step[1]), 1);
+        }
+    }
+
+    #[test]
+    fn wall_around_target() {
+        let walls = [(4, 5), (6, 5), (5, 4), (5, 6)];
+        let graph = grid(10, 10, &walls);
+        assert_eq!(a_star(&graph, &(0, 0), &(5, 5), manhattan((5, 5))), None);
+        assert_eq!(
+            a_star(&graph, &(5, 5), &(5, 5), manhattan((5, 5))),
+            Some((vec![(5, 5)], 0))
+        );
+    }
+
+    #[test]
+    fn same_cost_as_dijkstra() {
+        let mut rng = PCG32::new_default(314159);
+        let mut walls = vec![];
+        for _ in 0..120 {
+            walls.push(((rng.get_u32() % 20) as i32, (rng.get_u32() % 20) as i32));
+        }
+        walls.retain(|&cell| cell != (0, 0));
+        let graph = grid(20, 20, &walls);
+        for target in [(19, 19), (0, 19), (10, 3), (7, 7)] {
+            let expected = shortest_path(&graph, &(0, 0), &target).map(|(_, cost)| cost);
+            let found = a_star(&graph, &(0, 0), &target, manhattan(target));
+            assert_eq!(found.map(|(_, cost)| cost), expected);
+        }
+    }
+
+    #[test]
+    fn inconsistent_heuristic() {
+        // the heuristic is admissible but not consistent: 'b' looks much
+        // better than 'a' at first, yet the shortest path goes through 'a'
+        let mut graph = AdjacencyMap::new();
+        graph.insert('s', [('a', 1), ('b', 4)].into_iter().collect());
+        graph.insert('a', [('b', 1)].into_iter().collect());
+        graph.insert('b', [('t', 5)].into_iter().collect());
+        graph.insert('t', Default::default());
+        let heuristic = |v| match v {
+            'a' => 6,
+            _ => 0,
+        };
+        assert_eq!(
+            a_star(&graph, &'s', &'t', heuristic),
+            Some((vec!['s', 'a', 'b', 't'], 7))
+        );
+    }
+}
@@ -3,49 +3,55 @@ use std::ops::Add;
 
 use std::ops::Neg;
 
-type Graph<V, E> = BTreeMap<V, BTreeMap<V, E>>;
+use super::{all_vertices, Graph, PredecessorMap};
+
+// the distance of every vertex, or a negative cycle
+type Distances<V, E> = Result<BTreeMap<V, E>, Vec<V>>;
 
 // performs the Bellman-Ford algorithm on the given graph from the given start
 // the graph is an undirected graph
 //
-// if there is a negative weighted loop it returns None
+// if there is a negative weighted loop it returns None (`negative_cycle_from`
+// tells which one)
 // else it returns a map that for each reachable vertex associates the distance and the predecessor
 // since the start has no predecessor but is reachable, map[start] will be None
-pub fn bellman_ford<
-    V: Ord + Copy,
-    E: Ord + Copy + Add<Output = E> + Neg<Output = E> + std::ops::Sub<Output = E>,
->(
-    graph: &Graph<V, E>,
-    start: &V,
-) -> Option<BTreeMap<V, Option<(V, E)>>> {
-    let mut ans: BTreeMap<V, Option<(V, E)>> = BTreeMap::new();
+pub fn bellman_ford<G: Graph>(
+    graph: &G,
+    start: &G::Vertex,
+) -> Option<PredecessorMap<G::Vertex, G::Weight>>
+where
+    G::Weight:
+        Ord + Add<Output = G::Weight> + Neg<Output = G::Weight> + std::ops::Sub<Output = G::Weight>,
+{
+    let mut ans: PredecessorMap<G::Vertex, G::Weight> = BTreeMap::new();
 
     ans.insert(*start, None);
 
-    for _ in 1..(graph.len()) {
-        for (u, edges) in graph {
-            let dist_u = match ans.get(u) {
+    let num_vertices = all_vertices(graph).len();
+    for _ in 1..num_vertices {
+        for u in graph.vertices() {
+            let dist_u = match ans.get(&u) {
                 Some(Some((_, d))) => Some(*d),
                 Some(None) => None,
                 None => continue,
             };
 
-            for (v, d) in edges {
-                match ans.get(v) {
+            for (v, d) in graph.neighbors(u) {
+                match ans.get(&v) {
                     Some(Some((_, dist)))
                         // if this is a longer path, do nothing
                         if match dist_u {
-                            Some(dist_u) => dist_u + *d >= *dist,
-                            None => d >= dist,
+                            Some(dist_u) => dist_u + d >= *dist,
+                            None => d >= *dist,
                         } => {}
                     Some(None) => {
                         match dist_u {
                             // if dist_u + d < 0 there is a negative loop going by start
                             // else it's just a longer path
-                            Some(dist_u) if dist_u >= -*d => {}
+                            Some(dist_u) if dist_u >= -d => {}
                             // negative self edge or negative loop
                             _ => {
-                                if *d > *d + *d {
+                                if d > d + d {
                                     return None;
                                 }
                             }
@@ -54,12 +60,12 @@ pub fn bellman_ford<
                     // it's a shorter path: either dist_v was infinite or it was longer than dist_u + d
                     _ => {
                         ans.insert(
-                            *v,
+                            v,
                             Some((
-                                *u,
+                                u,
                                 match dist_u {
-                                    Some(dist) => dist + *d,
-                                    None => *d,
+                                    Some(dist) => dist + d,
+                                    None => d,
                                 },
                             )),
                         );
@@ -69,34 +75,138 @@ pub fn bellman_ford<
         }
     }
 
-    for (u, edges) in graph {
-        for (v, d) in edges {
-            match (ans.get(u), ans.get(v)) {
-                (Some(None), Some(None)) if *d > *d + *d => return None,
-                (Some(None), Some(Some((_, dv)))) if d < dv => return None,
-                (Some(Some((_, du))), Some(None)) if *du < -*d => return None,
-                (Some(Some((_, du))), Some(Some((_, dv)))) if *du + *d < *dv => return None,
-                (_, _) => {}
-            }
+    for (u, v, d) in graph.edges() {
+        match (ans.get(&u), ans.get(&v)) {
+            (Some(None), Some(None)) if d > d + d => return None,
+            (Some(None), Some(Some((_, dv)))) if d < *dv => return None,
+            (Some(Some((_, du))), Some(None)) if *du < -d => return None,
+            (Some(Some((_, du))), Some(Some((_, dv)))) if *du + d < *dv => return None,
+            (_, _) => {}
         }
     }
 
     Some(ans)
 }
 
+// Bellman-Ford from all of the sources at once (each at distance zero),
+// keeping track of the predecessors to find a negative cycle if there is one
+//
+// returns the distance of every reachable vertex, or the vertices of a
+// negative cycle reachable from the sources, in order (the edge from the last
+// one back to the first closes it)
+fn relax_from<G: Graph>(graph: &G, sources: &[G::Vertex]) -> Distances<G::Vertex, G::Weight>
+where
+    G::Weight: Ord + Add<Output = G::Weight> + Default,
+{
+    let mut vertices = all_vertices(graph);
+    vertices.extend(sources);
+    let vertices: Vec<G::Vertex> = vertices.into_iter().collect();
+    let index: BTreeMap<G::Vertex, usize> =
+        vertices.iter().enumerate().map(|(i, &v)| (v, i)).collect();
+    let edges: Vec<(usize, usize, G::Weight)> = graph
+        .edges()
+        .map(|(u, v, w)| (index[&u], index[&v], w))
+        .collect();
+    let n = vertices.len();
+
+    let mut dist: Vec<Option<G::Weight>> = vec![None; n];
+    let mut pred: Vec<Option<usize>> = vec![None; n];
+    for source in sources {
+        dist[index[source]] = Some(G::Weight::default());
+    }
+
+    // with the sources merged into a single one, the shortest paths have at
+    // most n edges, so a change in round n + 1 means there is a negative cycle
+    let mut last_changed = None;
+    for _ in 0..=n {
+        last_changed = None;
+        for &(u, v, w) in &edges {
+            if let Some(dist_u) = dist[u] {
+                if dist[v].is_none_or(|dist_v| dist_u + w < dist_v) {
+                    dist[v] = Some(dist_u + w);
+                    pred[v] = Some(u);
+                    last_changed = Some(v);
+                }
+            }
+        }
+        if last_changed.is_none() {
+            break;
+        }
+    }
+
+    match last_changed {
+        None => Ok(vertices
+            .iter()
+            .zip(dist)
+            .filter_map(|(&v, d)| Some((v, d?)))
+            .collect()),
+        Some(mut v) => {
+            // going back n times from the last changed vertex ends on the cycle
+            for _ in 0..n {
+                v = pred[v].unwrap();
+            }
+            let mut cycle = vec![vertices[v]];
+            let mut u = pred[v].unwrap();
+            while u != v {
+                cycle.push(vertices[u]);
+                u = pred[u].unwrap();
+            }
+            cycle.reverse();
+            Err(cycle)
+        }
+    }
+}
+
+// finds a cycle of negative total weight anywhere in the graph
+//
+// returns its vertices in order (the edge from the last one back to the first
+// closes it), or None if there is no negative cycle
+pub fn find_negative_cycle<G: Graph>(graph: &G) -> Option<Vec<G::Vertex>>
+where
+    G::Weight: Ord + Add<Output = G::Weight> + Default,
+{
+    let vertices: Vec<G::Vertex> = all_vertices(graph).into_iter().collect();
+    relax_from(graph, &vertices).err()
+}
+
+// same as `find_negative_cycle`, but only looks at the cycles that can be
+// reached from start, which are the ones that make `bellman_ford` fail
+pub fn negative_cycle_from<G: Graph>(graph: &G, start: &G::Vertex) -> Option<Vec<G::Vertex>>
+where
+    G::Weight: Ord + Add<Output = G::Weight> + Default,
+{
+    relax_from(graph, &[*start]).err()
+}
+
+// a potential h for every vertex such that h(u) + w >= h(v) for every edge
+// (u, v, w), which can be used to make all the weights non-negative by
+// replacing w with w + h(u) - h(v) (see `johnson`)
+//
+// h(v) is the shortest distance to v from any vertex, so it is never positive.
+// If there is a negative cycle, there are no such potentials and the cycle is
+// returned instead.
+pub fn shortest_path_potentials<G: Graph>(graph: &G) -> Distances<G::Vertex, G::Weight>
+where
+    G::Weight: Ord + Add<Output = G::Weight> + Default,
+{
+    let vertices: Vec<G::Vertex> = all_vertices(graph).into_iter().collect();
+    relax_from(graph, &vertices)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{bellman_ford, Graph};
+    use super::{bellman_ford, find_negative_cycle, negative_cycle_from, shortest_path_potentials};
+    use crate::graph::AdjacencyMap;
     use std::collections::BTreeMap;
 
-    fn add_edge<V: Ord + Copy, E: Ord>(graph: &mut Graph<V, E>, v1: V, v2: V, c: E) {
-        graph.entry(v1).or_insert_with(BTreeMap::new).insert(v2, c);
-        graph.entry(v2).or_insert_with(BTreeMap::new);
+    fn add_edge<V: Ord + Copy, E: Ord>(graph: &mut AdjacencyMap<V, E>, v1: V, v2: V, c: E) {
+        graph.entry(v1).or_default().insert(v2, c);
+        graph.entry(v2).or_default();
     }
 
     #[test]
     fn single_vertex() {
-        let mut graph: Graph<isize, isize> = BTreeMap::new();
+        let mut graph: AdjacencyMap<isize, isize> = BTreeMap::new();
         graph.insert(0, BTreeMap::new());
 
         let mut dists = BTreeMap::new();
@@ -265,4 +375,96 @@ mod tests {
         assert_eq!(bellman_ford(&graph, &3), None);
         assert_eq!(bellman_ford(&graph, &4), None);
     }
+
+    // checks that cycle is a cycle of graph with a negative total weight
+    fn assert_negative_cycle(graph: &AdjacencyMap<i32, i32>, cycle: &[i32]) {
+        assert!(!cycle.is_empty());
+        let total: i32 = (0..cycle.len())
+            .map(|i| graph[&cycle[i]][&cycle[(i + 1) % cycle.len()]])
+            .sum();
+        assert!(total < 0);
+    }
+
+    #[test]
+    fn negative_cycles() {
+        let mut graph = BTreeMap::new();
+        add_edge(&mut graph, 0, 1, 6);
+        add_edge(&mut graph, 0, 3, 7);
+        add_edge(&mut graph, 1, 2, 5);
+        add_edge(&mut graph, 1, 3, 8);
+        add_edge(&mut graph, 1, 4, -4);
+        add_edge(&mut graph, 2, 1, -4);
+        add_edge(&mut graph, 3, 2, -3);
+        add_edge(&mut graph, 3, 4, 9);
+        add_edge(&mut graph, 4, 0, 3);
+        add_edge(&mut graph, 4, 2, 7);
+
+        let cycle = find_negative_cycle(&graph).unwrap();
+        assert_negative_cycle(&graph, &cycle);
+        assert_negative_cycle(&graph, &negative_cycle_from(&graph, &4).unwrap());
+        assert!(shortest_path_potentials(&graph).is_err());
+
+        // a negative loop on a single vertex, that 5 can't reach
+        add_edge(&mut graph, 5, 6, 1);
+        add_edge(&mut graph, 7, 7, -1);
+        assert_eq!(negative_cycle_from(&graph, &7), Some(vec![7]));
+        assert_eq!(negative_cycle_from(&graph, &5), None);
+    }
+
+    #[test]
+    fn potentials() {
+        let mut graph = BTreeMap::new();
+        add_edge(&mut graph, 0, 1, 6);
+        add_edge(&mut graph, 0, 3, 7);
+        add_edge(&mut graph, 1, 2, 5);
+        add_edge(&mut graph, 1, 3, 8);
+        add_edge(&mut graph, 1, 4, -4);
+        add_edge(&mut graph, 2, 1, -2);
+        add_edge(&mut graph, 3, 2, -3);
+        add_edge(&mut graph, 3, 4, 9);
+        add_edge(&mut graph, 4, 0, 3);
+        add_edge(&mut graph, 4, 2, 7);
+
+        assert_eq!(find_negative_cycle(&graph), None);
+        let h = shortest_path_potentials(&graph).unwrap();
+        assert_eq!(
+            h,
+            BTreeMap::from([(0, -6), (1, -5), (2, -3), (3, 0), (4, -9)])
+        );
+        for (u, neighbors) in &graph {
+            for (v, w) in neighbors {
+                assert!(h[u] + w >= h[v]);
+            }
+        }
+    }
+
+    #[test]
+    fn vertex_without_entry() {
+        // 2 is only the destination of an edge
+        let graph: AdjacencyMap<i32, i32> = BTreeMap::from([(1, BTreeMap::from([(2, 3)]))]);
+        assert_eq!(find_negative_cycle(&graph), None);
+        assert_eq!(negative_cycle_from(&graph, &1), None);
+        assert_eq!(negative_cycle_from(&graph, &2), None);
+        assert_eq!(
+            shortest_path_potentials(&graph),
+            Ok(BTreeMap::from([(1, 0), (2, 0)]))
+        );
+
+        let mut graph = graph;
+        graph.insert(3, BTreeMap::from([(1, -1), (4, -2)]));
+        graph.get_mut(&1).unwrap().insert(3, -1);
+        assert_negative_cycle(&graph, &find_negative_cycle(&graph).unwrap());
+        assert_negative_cycle(&graph, &negative_cycle_from(&graph, &1).unwrap());
+
+        // 1 needs as many rounds as there are vertices, counting itself
+        let graph: AdjacencyMap<i32, i32> = BTreeMap::from([
+            (5, BTreeMap::from([(4, 1)])),
+            (4, BTreeMap::from([(3, 1)])),
+            (3, BTreeMap::from([(2, 1)])),
+            (2, BTreeMap::from([(1, 1)])),
+        ]);
+        let dists = bellman_ford(&graph, &5).unwrap();
+        assert_eq!(dists[&1], Some((2, 4)));
+        assert_eq!(dists.len(), 5);
+    }
 }
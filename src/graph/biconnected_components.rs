@@ -0,0 +1,350 @@
+/*
+Tarjan's bridge and articulation point algorithm for undirected graphs:
+In one DFS, computing for every vertex the earliest discovered vertex it can
+reach with at most one back edge (`low`), it finds
+
+- the bridges, edges whose removal disconnects the graph, and the
+  2-edge-connected components that remain once they are removed;
+- the articulation points, vertices whose removal disconnects the graph, and
+  the blocks (2-vertex-connected components), which split the edges so that
+  two edges are in the same block iff they lie on a common simple cycle.
+
+It runs in O(n + m). The graph is given as a list of edges (parallel edges
+are allowed, so an edge doubled is never a bridge), and its vertices are
+numbered from 1 to n.
+*/
+
+pub struct BiconnectedComponents {
+    pub num_vertices: usize,
+
+    // The block of each edge, starting from 1. Self-loops are in no block (0)
+    pub edge_block: Vec<usize>,
+    pub num_blocks: usize,
+
+    // The 2-edge-connected component of each vertex, starting from 1
+    pub component: Vec<usize>,
+    pub num_components: usize,
+
+    // The indices of the edges that are bridges, in the order they were found
+    pub bridges: Vec<usize>,
+    pub is_articulation_point: Vec<bool>,
+
+    edges: Vec<(usize, usize)>,
+    // The neighbours of each vertex, with the index of the edge to them
+    adj: Vec<Vec<(usize, usize)>>,
+    // Discover time of each vertex (0 if not discovered yet)
+    discover_time: Vec<usize>,
+    low: Vec<usize>,
+    current_time: usize,
+    // The edges of the blocks still being explored
+    edge_stack: Vec<usize>,
+    // The vertices of the 2-edge-connected components still being explored
+    vertex_stack: Vec<usize>,
+}
+
+impl BiconnectedComponents {
+    pub fn new(num_vertices: usize) -> Self {
+        BiconnectedComponents {
+            num_vertices,
+            edge_block: vec![],
+            num_blocks: 0,
+            component: vec![0; num_vertices + 1],
+            num_components: 0,
+            bridges: vec![],
+            is_articulation_point: vec![false; num_vertices + 1],
+            edges: vec![],
+            adj: vec![vec![]; num_vertices + 1],
+            discover_time: vec![0; num_vertices + 1],
+            low: vec![0; num_vertices + 1],
+            current_time: 1,
+            edge_stack: vec![],
+            vertex_stack: vec![],
+        }
+    }
+
+    fn dfs(&mut self, v: usize, parent_edge: Option<usize>) {
+        self.discover_time[v] = self.current_time;
+        self.low[v] = self.current_time;
+        self.current_time += 1;
+        self.vertex_stack.push(v);
+        let mut children = 0;
+
+        for i in 0..self.adj[v].len() {
+            let (u, id) = self.adj[v][i];
+            // Going back through the same edge is not a cycle, but going
+            // back through a parallel one is
+            if Some(id) == parent_edge {
+                continue;
+            }
+            if self.discover_time[u] != 0 {
+                self.low[v] = self.low[v].min(self.discover_time[u]);
+                if self.discover_time[u] < self.discover_time[v] {
+                    self.edge_stack.push(id);
+                }
+                continue;
+            }
+
+            children += 1;
+            self.edge_stack.push(id);
+            self.dfs(u, Some(id));
+            self.low[v] = self.low[v].min(self.low[u]);
+
+            if self.low[u] > self.discover_time[v] {
+                self.bridges.push(id);
+            }
+            // Nothing under u goes above v: v separates them from the rest
+            if self.low[u] >= self.discover_time[v] {
+                if parent_edge.is_some() || children > 1 {
+                    self.is_articulation_point[v] = true;
+                }
+                self.num_blocks += 1;
+                loop {
+                    let e = self.edge_stack.pop().unwrap();
+                    self.edge_block[e] = self.num_blocks;
+                    if e == id {
+                        break;
+                    }
+                }
+            }
+        }
+        // The root of a DFS tree is an articulation point iff it has more
+        // than one child, which is only known at the end
+        if parent_edge.is_none() && children > 1 {
+            self.is_articulation_point[v] = true;
+        }
+
+        // No edge goes from the subtree of v to above it (or only the bridge
+        // to its parent): v and what is left of its subtree are a component
+        if self.low[v] == self.discover_time[v] {
+            self.num_components += 1;
+            loop {
+                let u = self.vertex_stack.pop().unwrap();
+                self.component[u] = self.num_components;
+                if u == v {
+                    break;
+                }
+            }
+        }
+    }
+
+    pub fn find_components(&mut self, edges: &[(usize, usize)]) {
+        self.edges = edges.to_vec();
+        self.edge_block = vec![0; edges.len()];
+        for (id, &(u, v)) in edges.iter().enumerate() {
+            self.adj[u].push((v, id));
+            if u != v {
+                self.adj[v].push((u, id));
+            }
+        }
+        for v in 1..=self.num_vertices {
+            if self.discover_time[v] == 0 {
+                self.dfs(v, None);
+            }
+        }
+    }
+
+    pub fn articulation_points(&self) -> Vec<usize> {
+        (1..=self.num_vertices)
+            .filter(|&v| self.is_articulation_point[v])
+            .collect()
+    }
+
+    // The vertices of each block, sorted (blocks[0] is empty since blocks
+    // are numbered from 1)
+    pub fn blocks(&self) -> Vec<Vec<usize>> {
+        let mut blocks = vec![vec![]; self.num_blocks + 1];
+        for (&(u, v), &b) in self.edges.iter().zip(self.edge_block.iter()) {
+            if b != 0 {
+                blocks[b].push(u);
+                blocks[b].push(v);
+            }
+        }
+        for block in blocks.iter_mut() {
+            block.sort_unstable();
+            block.dedup();
+        }
+        blocks
+    }
+
+    // The block-cut tree (a forest if the graph is not connected), as an
+    // adjacency list. Vertices 1 to n of the tree are the vertices of the
+    // graph, and n + b is the b-th block; every vertex is linked to the blocks
+    // it is in. Articulation points are the vertices in several blocks, the
+    // others are leaves (or isolated, for isolated vertices).
+    pub fn block_cut_tree(&self) -> Vec<Vec<usize>> {
+        let n = self.num_vertices;
+        let mut tree = vec![vec![]; n + self.num_blocks + 1];
+        for (b, block) in self.blocks().iter().enumerate().skip(1) {
+            for &v in block {
+                tree[v].push(n + b);
+                tree[n + b].push(v);
+            }
+        }
+        tree
+    }
+
+    // The bridge tree (a forest if the graph is not connected), as an
+    // adjacency list: its vertices are the 2-edge-connected components, and
+    // its edges are the bridges
+    pub fn bridge_tree(&self) -> Vec<Vec<usize>> {
+        let mut tree = vec![vec![]; self.num_components + 1];
+        for &id in self.bridges.iter() {
+            let (u, v) = self.edges[id];
+            let (cu, cv) = (self.component[u], self.component[v]);
+            tree[cu].push(cv);
+            tree[cv].push(cu);
+        }
+        tree
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::PCG32;
+
+    // The number of connected components, without the edge `skip_edge`
+    // and the vertex `skip_vertex`
+    fn count_components(
+        num_vertices: usize,
+        edges: &[(usize, usize)],
+        skip_edge: Option<usize>,
+        skip_vertex: Option<usize>,
+    ) -> usize {
+        let mut parent: Vec<usize> = (0..=num_vertices).collect();
+        fn find(parent: &mut [usize], v: usize) -> usize {
+            if parent[v] != v {
+                parent[v] = find(parent, parent[v]);
+            }
+            parent[v]
+        }
+        for (id, &(u, v)) in edges.iter().enumerate() {
+            if Some(id) == skip_edge || Some(u) == skip_vertex || Some(v) == skip_vertex {
+                continue;
+            }
+            let (ru, rv) = (find(&mut parent, u), find(&mut parent, v));
+            parent[ru] = rv;
+        }
+        (1..=num_vertices)
+            .filter(|&v| Some(v) != skip_vertex && find(&mut parent, v) == v)
+            .count()
+    }
+
+    #[test]
+    fn two_triangles_and_a_bridge() {
+        // 1-2-3 and 4-5-6 are triangles, joined by the bridge 3-4, and 7 is
+        // hanging from 6
+        let edges = vec![
+            (1, 2),
+            (2, 3),
+            (3, 1),
+            (3, 4),
+            (4, 5),
+            (5, 6),
+            (6, 4),
+            (6, 7),
+        ];
+        let mut bcc = BiconnectedComponents::new(8);
+        bcc.find_components(&edges);
+
+        let mut bridges = bcc.bridges.clone();
+        bridges.sort_unstable();
+        assert_eq!(bridges, vec![3, 7]);
+        assert_eq!(bcc.articulation_points(), vec![3, 4, 6]);
+
+        assert_eq!(bcc.num_blocks, 4);
+        let mut blocks = bcc.blocks();
+        blocks.sort();
+        assert_eq!(
+            blocks,
+            vec![vec![], vec![1, 2, 3], vec![3, 4], vec![4, 5, 6], vec![6, 7]]
+        );
+
+        // {1, 2, 3}, {4, 5, 6}, {7} and the isolated vertex 8
+        assert_eq!(bcc.num_components, 4);
+        assert_eq!(bcc.component[1], bcc.component[2]);
+        assert_eq!(bcc.component[1], bcc.component[3]);
+        assert_eq!(bcc.component[4], bcc.component[6]);
+        assert_ne!(bcc.component[3], bcc.component[4]);
+        assert_ne!(bcc.component[6], bcc.component[7]);
+
+        let bridge_tree = bcc.bridge_tree();
+        let middle = bcc.component[4];
+        let mut neighbours = bridge_tree[middle].clone();
+        neighbours.sort_unstable();
+        let mut expected = vec![bcc.component[1], bcc.component[7]];
+        expected.sort_unstable();
+        assert_eq!(neighbours, expected);
+        assert!(bridge_tree[bcc.component[8]].is_empty());
+
+        let tree = bcc.block_cut_tree();
+        assert_eq!(tree.len(), 8 + 4 + 1);
+        assert_eq!(tree[3].len(), 2);
+        assert_eq!(tree[4].len(), 2);
+        assert_eq!(tree[1].len(), 1);
+        assert!(tree[8].is_empty());
+        // a forest: one edge less than the number of vertices in each tree
+        let num_edges: usize = tree.iter().map(|t| t.len()).sum::<usize>() / 2;
+        assert_eq!(num_edges, 7 + 4 - 1);
+    }
+
+    #[test]
+    fn parallel_edges_and_loops() {
+        let edges = vec![(1, 2), (2, 1), (2, 3), (3, 3)];
+        let mut bcc = BiconnectedComponents::new(3);
+        bcc.find_components(&edges);
+        assert_eq!(bcc.bridges, vec![2]);
+        assert_eq!(bcc.articulation_points(), vec![2]);
+        assert_eq!(bcc.edge_block[0], bcc.edge_block[1]);
+        assert_eq!(bcc.edge_block[3], 0);
+        assert_eq!(bcc.num_blocks, 2);
+        assert_eq!(bcc.num_components, 2);
+    }
+
+    #[test]
+    fn same_as_removing_edges_and_vertices() {
+        let mut rng = PCG32::new_default(3141);
+        for round in 0..100 {
+            let n = 2 + round % 12;
+            let m = rng.get_u32() as usize % (2 * n);
+            let edges: Vec<(usize, usize)> = (0..m)
+                .map(|_| {
+                    (
+                        rng.get_u32() as usize % n + 1,
+                        rng.get_u32() as usize % n + 1,
+                    )
+                })
+                .collect();
+            let mut bcc = BiconnectedComponents::new(n);
+            bcc.find_components(&edges);
+
+            let before = count_components(n, &edges, None, None);
+            let mut bridges = bcc.bridges.clone();
+            bridges.sort_unstable();
+            let expected: Vec<usize> = (0..m)
+                .filter(|&id| count_components(n, &edges, Some(id), None) > before)
+                .collect();
+            assert_eq!(bridges, expected);
+
+            // removing a vertex also removes its own component if it was
+            // isolated
+            let expected: Vec<usize> = (1..=n)
+                .filter(|&v| {
+                    let isolated = edges.iter().all(|&(a, b)| a != v && b != v || a == b);
+                    count_components(n, &edges, None, Some(v)) + usize::from(isolated) > before
+                })
+                .collect();
+            assert_eq!(bcc.articulation_points(), expected);
+
+            // every edge that isn't a bridge is in the same 2-edge-connected
+            // component on both ends, bridges are not
+            for (id, &(u, v)) in edges.iter().enumerate() {
+                let same = bcc.component[u] == bcc.component[v];
+                assert_eq!(same, !bcc.bridges.contains(&id));
+            }
+            let bridge_tree = bcc.bridge_tree();
+            let tree_edges: usize = bridge_tree.iter().map(|t| t.len()).sum::<usize>() / 2;
+            assert_eq!(tree_edges, bcc.bridges.len());
+        }
+    }
+}
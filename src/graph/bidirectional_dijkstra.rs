@@ -0,0 +1,201 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap};
+use std::ops::Add;
+
+use super::Graph;
+
+// The state of one of the two searches
+struct Search<V, E> {
+    // the distance of each vertex from where this search started
+    dist: BTreeMap<V, E>,
+    // the previous vertex on the path from where this search started
+    pred: BTreeMap<V, V>,
+    prio: BinaryHeap<Reverse<(E, V)>>,
+}
+
+impl<V: Ord + Copy, E: Ord + Copy + Add<Output = E> + Default> Search<V, E> {
+    fn new(from: V) -> Self {
+        let mut dist = BTreeMap::new();
+        dist.insert(from, E::default());
+        let mut prio = BinaryHeap::new();
+        prio.push(Reverse((E::default(), from)));
+        Search {
+            dist,
+            pred: BTreeMap::new(),
+            prio,
+        }
+    }
+
+    // the smallest distance that hasn't been settled yet
+    fn frontier(&self) -> Option<E> {
+        self.prio.peek().map(|Reverse((d, _))| *d)
+    }
+
+    // settles the closest vertex and relaxes its edges. `best` is updated
+    // whenever an edge reaches a vertex the other search has seen.
+    fn step<I: Iterator<Item = (V, E)>>(
+        &mut self,
+        neighbors: impl Fn(V) -> I,
+        other: &Self,
+        best: &mut Option<(E, V)>,
+    ) {
+        let Reverse((dist, v)) = self.prio.pop().unwrap();
+        if dist > self.dist[&v] {
+            return;
+        }
+        for (next, weight) in neighbors(v) {
+            let dist_next = dist + weight;
+            match self.dist.get(&next) {
+                Some(d) if *d <= dist_next => continue,
+                _ => {
+                    self.dist.insert(next, dist_next);
+                    self.pred.insert(next, v);
+                    self.prio.push(Reverse((dist_next, next)));
+                }
+            }
+            if let Some(&other_dist) = other.dist.get(&next) {
+                let total = dist_next + other_dist;
+                if best.is_none_or(|(b, _)| total < b) {
+                    *best = Some((total, next));
+                }
+            }
+        }
+    }
+
+    // the vertices from `v` back to where this search started
+    fn walk_back(&self, mut v: V) -> Vec<V> {
+        let mut path = vec![v];
+        while let Some(&p) = self.pred.get(&v) {
+            path.push(p);
+            v = p;
+        }
+        path
+    }
+}
+
+// finds a shortest path from start to target by running dijkstra from both
+// ends at the same time, until the two searches meet. It usually settles far
+// fewer vertices than a single search on large graphs like road networks.
+//
+// `reverse` should be `graph` with all its edges reversed, for example
+// `reverse_graph(graph)` (or `graph` itself if it is undirected). It is
+// taken as an argument so it can be built once and reused for many queries.
+//
+// returns the vertices of the path (start and target included) and its total
+// weight, or None if target is unreachable. E::default() should be zero.
+pub fn bidirectional_dijkstra<G, R>(
+    graph: &G,
+    reverse: &R,
+    start: &G::Vertex,
+    target: &G::Vertex,
+) -> Option<(Vec<G::Vertex>, G::Weight)>
+where
+    G: Graph,
+    G::Weight: Ord + Add<Output = G::Weight> + Default,
+    R: Graph<Vertex = G::Vertex, Weight = G::Weight>,
+{
+    if start == target {
+        return Some((vec![*start], G::Weight::default()));
+    }
+    let mut forward = Search::new(*start);
+    let mut backward = Search::new(*target);
+    // the length of the shortest path found so far, and a vertex on it
+    let mut best: Option<(G::Weight, G::Vertex)> = None;
+
+    // once a search has settled everything it can reach, no shorter path can
+    // be found
+    while let (Some(f), Some(b)) = (forward.frontier(), backward.frontier()) {
+        // any path that is still unknown is at least this long
+        if best.is_some_and(|(length, _)| f + b >= length) {
+            break;
+        }
+        if f <= b {
+            forward.step(|v| graph.neighbors(v), &backward, &mut best);
+        } else {
+            backward.step(|v| reverse.neighbors(v), &forward, &mut best);
+        }
+    }
+
+    let (length, middle) = best?;
+    let mut path = forward.walk_back(middle);
+    path.reverse();
+    path.extend(backward.walk_back(middle).into_iter().skip(1));
+    Some((path, length))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::bidirectional_dijkstra;
+    use crate::graph::{reverse_graph, shortest_path, AdjacencyMap, Graph};
+    use crate::math::PCG32;
+
+    fn add_edge<V: Ord + Copy, E: Ord>(graph: &mut AdjacencyMap<V, E>, v1: V, v2: V, c: E) {
+        graph.entry(v1).or_default().insert(v2, c);
+        graph.entry(v2).or_default();
+    }
+
+    fn path_cost(graph: &AdjacencyMap<u32, u32>, path: &[u32]) -> u32 {
+        path.windows(2).map(|e| graph[&e[0]][&e[1]]).sum()
+    }
+
+    #[test]
+    fn small_graph() {
+        let mut graph = AdjacencyMap::new();
+        add_edge(&mut graph, 'a', 'c', 12);
+        add_edge(&mut graph, 'a', 'd', 60);
+        add_edge(&mut graph, 'b', 'a', 10);
+        add_edge(&mut graph, 'c', 'b', 20);
+        add_edge(&mut graph, 'c', 'd', 32);
+        add_edge(&mut graph, 'e', 'a', 7);
+        let reverse = reverse_graph(&graph);
+
+        assert_eq!(
+            bidirectional_dijkstra(&graph, &reverse, &'e', &'d'),
+            Some((vec!['e', 'a', 'c', 'd'], 51))
+        );
+        assert_eq!(
+            bidirectional_dijkstra(&graph, &reverse, &'b', &'d'),
+            Some((vec!['b', 'a', 'c', 'd'], 54))
+        );
+        assert_eq!(
+            bidirectional_dijkstra(&graph, &reverse, &'c', &'c'),
+            Some((vec!['c'], 0))
+        );
+        assert_eq!(bidirectional_dijkstra(&graph, &reverse, &'d', &'a'), None);
+    }
+
+    #[test]
+    fn random_graphs() {
+        let mut rng = PCG32::new_default(2718);
+        for _ in 0..20 {
+            let num_vertices = 50;
+            let mut graph = AdjacencyMap::new();
+            for v in 0..num_vertices {
+                graph.insert(v, Default::default());
+            }
+            for _ in 0..150 {
+                let u = rng.get_u32() % num_vertices;
+                let v = rng.get_u32() % num_vertices;
+                add_edge(&mut graph, u, v, rng.get_u32() % 100);
+            }
+            let reverse = reverse_graph(&graph);
+            for _ in 0..10 {
+                let start = rng.get_u32() % num_vertices;
+                let target = rng.get_u32() % num_vertices;
+                let expected = shortest_path(&graph, &start, &target);
+                let found = bidirectional_dijkstra(&graph, &reverse, &start, &target);
+                match (expected, found) {
+                    (None, None) => {}
+                    (Some((_, expected)), Some((path, cost))) => {
+                        assert_eq!(cost, expected);
+                        assert_eq!(path_cost(&graph, &path), cost);
+                        assert_eq!(path[0], start);
+                        assert_eq!(path[path.len() - 1], target);
+                    }
+                    (expected, found) => panic!("expected {expected:?}, found {found:?}"),
+                }
+            }
+            assert_eq!(reverse.num_edges(), graph.num_edges());
+        }
+    }
+}
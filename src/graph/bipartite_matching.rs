@@ -50,12 +50,18 @@ impl BipartiteMatching {
             self.try_kuhn(v);
         }
     }
+    // The matched (grp1, grp2) pairs, sorted by their vertex in grp1
+    pub fn matching(&self) -> Vec<(usize, usize)> {
+        let mut pairs: Vec<(usize, usize)> = (1..self.num_vertices_grp2 + 1)
+            .filter(|&i| self.mt2[i] != -1)
+            .map(|i| (self.mt2[i] as usize, i))
+            .collect();
+        pairs.sort_unstable();
+        pairs
+    }
     pub fn print_matching(&self) {
-        for i in 1..self.num_vertices_grp2 + 1 {
-            if self.mt2[i] == -1 {
-                continue;
-            }
-            println!("Vertex {} in grp1 matched with {} grp2", self.mt2[i], i)
+        for (u, v) in self.matching() {
+            println!("Vertex {} in grp1 matched with {} grp2", u, v)
         }
     }
     fn bfs(&self, dist: &mut [i32]) -> bool {
@@ -156,6 +162,7 @@ mod tests {
         g.add_edge(6, 6);
         g.kuhn();
         g.print_matching();
+        assert_eq!(g.matching(), vec![(1, 2), (3, 1), (4, 3), (5, 4), (6, 6)]);
         let answer: Vec<i32> = vec![-1, 2, -1, 1, 3, 4, 6];
         for i in 1..g.mt2.len() {
             if g.mt2[i] == -1 {
@@ -187,6 +194,7 @@ mod tests {
         let x = g.hopcroft_karp();
         assert_eq!(x, 5);
         g.print_matching();
+        assert_eq!(g.matching(), vec![(1, 2), (3, 1), (4, 3), (5, 4), (6, 6)]);
         let answer: Vec<i32> = vec![-1, 2, -1, 1, 3, 4, 6];
         for i in 1..g.mt2.len() {
             if g.mt2[i] == -1 {
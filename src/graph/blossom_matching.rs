@@ -0,0 +1,250 @@
+/*
+Edmonds' blossom algorithm for maximum cardinality matching in a general
+(not necessarily bipartite) undirected graph, in O(V^3).
+
+Like in a bipartite graph, the matching is grown along augmenting paths,
+found with a BFS from a free vertex that alternates unmatched and matched
+edges. An odd cycle (a blossom) can make that search miss a path, so when
+one is found, all its vertices are merged into its base and the search goes
+on as if the blossom were a single vertex.
+*/
+
+use std::collections::VecDeque;
+
+// We assume that graph vertices are numbered from 1 to n.
+pub struct BlossomMatching {
+    pub num_vertices: usize,
+    pub adj: Vec<Vec<usize>>,
+    // mate[v] = Some(u) if v is matched with u
+    pub mate: Vec<Option<usize>>,
+    // The base of the blossom each vertex has been merged into
+    base: Vec<usize>,
+    // The previous vertex on the alternating path, for the vertices that
+    // were reached through an unmatched edge
+    parent: Vec<Option<usize>>,
+    used: Vec<bool>,
+    blossom: Vec<bool>,
+}
+
+impl BlossomMatching {
+    pub fn new(num_vertices: usize) -> Self {
+        BlossomMatching {
+            num_vertices,
+            adj: vec![vec![]; num_vertices + 1],
+            mate: vec![None; num_vertices + 1],
+            base: vec![0; num_vertices + 1],
+            parent: vec![None; num_vertices + 1],
+            used: vec![false; num_vertices + 1],
+            blossom: vec![false; num_vertices + 1],
+        }
+    }
+
+    // Add an undirected edge u-v in the graph
+    pub fn add_edge(&mut self, u: usize, v: usize) {
+        self.adj[u].push(v);
+        self.adj[v].push(u);
+    }
+
+    // The base of the smallest blossom containing both a and b, found by
+    // walking up the alternating tree from both of them
+    fn lowest_common_ancestor(&self, mut a: usize, mut b: usize) -> usize {
+        let mut on_path = vec![false; self.num_vertices + 1];
+        loop {
+            a = self.base[a];
+            on_path[a] = true;
+            match self.mate[a] {
+                Some(m) => a = self.parent[m].unwrap(),
+                None => break,
+            }
+        }
+        loop {
+            b = self.base[b];
+            if on_path[b] {
+                return b;
+            }
+            b = self.parent[self.mate[b].unwrap()].unwrap();
+        }
+    }
+
+    // Marks the blossoms on the path from v down to the base `b`, and points
+    // the parents of that path towards `child` so that the path can go
+    // around the blossom in the other direction
+    fn mark_path(&mut self, mut v: usize, b: usize, mut child: usize) {
+        while self.base[v] != b {
+            let m = self.mate[v].unwrap();
+            self.blossom[self.base[v]] = true;
+            self.blossom[self.base[m]] = true;
+            self.parent[v] = Some(child);
+            child = m;
+            v = self.parent[m].unwrap();
+        }
+    }
+
+    // Looks for an augmenting path from the free vertex root, and returns
+    // the free vertex at its other end
+    fn find_path(&mut self, root: usize) -> Option<usize> {
+        self.used.fill(false);
+        self.parent.fill(None);
+        for (v, b) in self.base.iter_mut().enumerate() {
+            *b = v;
+        }
+        self.used[root] = true;
+        let mut q = VecDeque::new();
+        q.push_back(root);
+
+        while let Some(v) = q.pop_front() {
+            for i in 0..self.adj[v].len() {
+                let to = self.adj[v][i];
+                if self.base[v] == self.base[to] || self.mate[v] == Some(to) {
+                    continue;
+                }
+                if to == root || self.mate[to].is_some_and(|m| self.parent[m].is_some()) {
+                    // An odd cycle: contract it into its base
+                    let current_base = self.lowest_common_ancestor(v, to);
+                    self.blossom.fill(false);
+                    self.mark_path(v, current_base, to);
+                    self.mark_path(to, current_base, v);
+                    for u in 1..=self.num_vertices {
+                        if self.blossom[self.base[u]] {
+                            self.base[u] = current_base;
+                            if !self.used[u] {
+                                self.used[u] = true;
+                                q.push_back(u);
+                            }
+                        }
+                    }
+                } else if self.parent[to].is_none() {
+                    self.parent[to] = Some(v);
+                    match self.mate[to] {
+                        None => return Some(to),
+                        Some(m) => {
+                            self.used[m] = true;
+                            q.push_back(m);
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    // Returns the matched pairs (u, v) with u < v, sorted
+    pub fn find_matching(&mut self) -> Vec<(usize, usize)> {
+        self.mate.fill(None);
+        for root in 1..=self.num_vertices {
+            if self.mate[root].is_some() {
+                continue;
+            }
+            if let Some(mut v) = self.find_path(root) {
+                // Flip the edges of the augmenting path
+                while let Some(p) = self.parent[v] {
+                    let next = self.mate[p];
+                    self.mate[v] = Some(p);
+                    self.mate[p] = Some(v);
+                    match next {
+                        Some(n) => v = n,
+                        None => break,
+                    }
+                }
+            }
+        }
+        (1..=self.num_vertices)
+            .filter_map(|v| self.mate[v].filter(|&u| v < u).map(|u| (v, u)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::PCG32;
+
+    // The size of a maximum matching, trying every possibility
+    fn brute_force(num_vertices: usize, edges: &[(usize, usize)]) -> usize {
+        fn go(edges: &[(usize, usize)], matched: &mut Vec<bool>) -> usize {
+            match edges.split_first() {
+                None => 0,
+                Some((&(u, v), rest)) => {
+                    let mut best = go(rest, matched);
+                    if u != v && !matched[u] && !matched[v] {
+                        matched[u] = true;
+                        matched[v] = true;
+                        best = best.max(1 + go(rest, matched));
+                        matched[u] = false;
+                        matched[v] = false;
+                    }
+                    best
+                }
+            }
+        }
+        go(edges, &mut vec![false; num_vertices + 1])
+    }
+
+    fn check_matching(pairs: &[(usize, usize)], edges: &[(usize, usize)], num_vertices: usize) {
+        let mut matched = vec![false; num_vertices + 1];
+        for &(u, v) in pairs {
+            assert!(edges.contains(&(u, v)) || edges.contains(&(v, u)));
+            assert!(!matched[u] && !matched[v]);
+            matched[u] = true;
+            matched[v] = true;
+        }
+    }
+
+    #[test]
+    fn odd_cycle() {
+        // a triangle 1-2-3 with a tail 3-4-5: the bipartite search would get
+        // stuck in the triangle
+        let mut g = BlossomMatching::new(6);
+        g.add_edge(1, 2);
+        g.add_edge(2, 3);
+        g.add_edge(3, 1);
+        g.add_edge(3, 4);
+        g.add_edge(4, 5);
+        g.add_edge(1, 6);
+        assert_eq!(g.find_matching(), vec![(1, 6), (2, 3), (4, 5)]);
+    }
+
+    #[test]
+    fn petersen_graph() {
+        let mut g = BlossomMatching::new(10);
+        let mut edges = vec![];
+        for i in 0..5 {
+            edges.push((i + 1, (i + 1) % 5 + 1));
+            edges.push((i + 1, i + 6));
+            edges.push((i + 6, (i + 2) % 5 + 6));
+        }
+        for &(u, v) in edges.iter() {
+            g.add_edge(u, v);
+        }
+        let pairs = g.find_matching();
+        assert_eq!(pairs.len(), 5);
+        check_matching(&pairs, &edges, 10);
+    }
+
+    #[test]
+    fn no_edges() {
+        let mut g = BlossomMatching::new(3);
+        assert_eq!(g.find_matching(), vec![]);
+        assert_eq!(g.mate, vec![None; 4]);
+    }
+
+    #[test]
+    fn same_as_brute_force() {
+        let mut rng = PCG32::new_default(8088);
+        for round in 0..300 {
+            let num_vertices = 1 + round % 10;
+            let num_edges = rng.get_u32() as usize % 16;
+            let mut g = BlossomMatching::new(num_vertices);
+            let mut edges = vec![];
+            for _ in 0..num_edges {
+                let u = (rng.get_u32() as usize % num_vertices) + 1;
+                let v = (rng.get_u32() as usize % num_vertices) + 1;
+                g.add_edge(u, v);
+                edges.push((u, v));
+            }
+            let pairs = g.find_matching();
+            check_matching(&pairs, &edges, num_vertices);
+            assert_eq!(pairs.len(), brute_force(num_vertices, &edges));
+        }
+    }
+}
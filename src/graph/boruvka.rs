@@ -0,0 +1,249 @@
+/*
+Borůvka's algorithm for the minimum spanning tree (or forest, if the graph
+is not connected).
+
+Each round finds, for every component, its cheapest edge going to another
+component, and adds all of them at once. The number of components is at
+least halved by every round, so there are O(lg(n)) rounds of O(m) each.
+
+The search for the cheapest edges only reads the edges and the components,
+so it can be split between several threads: each one looks at a part of the
+edges, and their results are combined afterwards.
+
+Ties between edges of the same cost are broken by their index, otherwise
+two components could pick two different edges of the same cost that close a
+cycle.
+*/
+
+use std::collections::BTreeMap;
+use std::ops::Add;
+use std::thread;
+
+use super::{all_vertices, DisjointSetUnion, Edge, Graph, SpanningTree};
+
+// For each component (by the index of its root), the index of its cheapest
+// edge among edges[offset..offset + len] going out of it
+fn cheapest_in<E: Ord + Copy>(
+    edges: &[(usize, usize, E)],
+    component: &[usize],
+    offset: usize,
+    len: usize,
+) -> Vec<Option<usize>> {
+    let mut cheapest: Vec<Option<usize>> = vec![None; component.len()];
+    for i in offset..offset + len {
+        let (u, v, cost) = edges[i];
+        let (a, b) = (component[u], component[v]);
+        if a == b {
+            continue;
+        }
+        for c in [a, b] {
+            match cheapest[c] {
+                Some(j) if (edges[j].2, j) <= (cost, i) => {}
+                _ => cheapest[c] = Some(i),
+            }
+        }
+    }
+    cheapest
+}
+
+fn cheapest_edges<E: Ord + Copy + Sync>(
+    edges: &[(usize, usize, E)],
+    component: &[usize],
+    num_threads: usize,
+) -> Vec<Option<usize>> {
+    if num_threads <= 1 || edges.len() < 2 {
+        return cheapest_in(edges, component, 0, edges.len());
+    }
+    let chunk = edges.len().div_ceil(num_threads);
+    let partial: Vec<Vec<Option<usize>>> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..edges.len())
+            .step_by(chunk)
+            .map(|offset| {
+                let len = chunk.min(edges.len() - offset);
+                scope.spawn(move || cheapest_in(edges, component, offset, len))
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect()
+    });
+    // The parts are in the order of the edges, so on ties the first one wins
+    let mut cheapest: Vec<Option<usize>> = vec![None; component.len()];
+    for part in partial {
+        for (c, candidate) in part.into_iter().enumerate() {
+            if let Some(i) = candidate {
+                match cheapest[c] {
+                    Some(j) if edges[j].2 <= edges[i].2 => {}
+                    _ => cheapest[c] = Some(i),
+                }
+            }
+        }
+    }
+    cheapest
+}
+
+// the graph is an undirected graph, it doesn't matter whether its edges are
+// stored in one or both directions
+//
+// returns the total cost of the minimum spanning forest and its edges, sorted
+// by cost, like `kruskal`
+pub fn boruvka<G: Graph>(graph: &G) -> SpanningTree<G::Vertex, G::Weight>
+where
+    G::Weight: Ord + Add<Output = G::Weight> + Default + Sync,
+{
+    boruvka_parallel(graph, 1)
+}
+
+// same as `boruvka`, with the cheapest edges of each round searched by
+// `num_threads` threads
+pub fn boruvka_parallel<G: Graph>(
+    graph: &G,
+    num_threads: usize,
+) -> SpanningTree<G::Vertex, G::Weight>
+where
+    G::Weight: Ord + Add<Output = G::Weight> + Default + Sync,
+{
+    let vertices: Vec<G::Vertex> = all_vertices(graph).into_iter().collect();
+    let index: BTreeMap<G::Vertex, usize> =
+        vertices.iter().enumerate().map(|(i, &v)| (v, i)).collect();
+    let edges: Vec<(usize, usize, G::Weight)> = graph
+        .edges()
+        .map(|(u, v, cost)| (index[&u], index[&v], cost))
+        .filter(|&(u, v, _)| u != v)
+        .collect();
+
+    let mut dsu = DisjointSetUnion::new(vertices.len());
+    let mut chosen = vec![];
+    loop {
+        let component: Vec<usize> = (0..vertices.len()).map(|v| dsu.find_set(v)).collect();
+        let mut merged = false;
+        for i in cheapest_edges(&edges, &component, num_threads)
+            .into_iter()
+            .flatten()
+        {
+            // both ends of an edge may have picked it
+            if dsu.merge(edges[i].0, edges[i].1) < usize::MAX {
+                chosen.push(i);
+                merged = true;
+            }
+        }
+        if !merged {
+            break;
+        }
+    }
+
+    chosen.sort_by_key(|&i| (edges[i].2, i));
+    let mut total_cost = G::Weight::default();
+    let mut final_edges = Vec::new();
+    for i in chosen {
+        let (u, v, cost) = edges[i];
+        total_cost = total_cost + cost;
+        final_edges.push(Edge::new(vertices[u], vertices[v], cost));
+    }
+    (total_cost, final_edges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{kruskal, prim, AdjacencyMap};
+    use crate::math::PCG32;
+
+    fn add_edge(graph: &mut AdjacencyMap<u32, i64>, u: u32, v: u32, cost: i64) {
+        graph.entry(u).or_default().insert(v, cost);
+        graph.entry(v).or_default().insert(u, cost);
+    }
+
+    #[test]
+    fn small_graph() {
+        let mut graph = AdjacencyMap::new();
+        add_edge(&mut graph, 0, 1, 7);
+        add_edge(&mut graph, 0, 3, 5);
+        add_edge(&mut graph, 1, 2, 8);
+        add_edge(&mut graph, 1, 3, 9);
+        add_edge(&mut graph, 1, 4, 7);
+        add_edge(&mut graph, 2, 4, 5);
+        add_edge(&mut graph, 3, 4, 15);
+        add_edge(&mut graph, 3, 5, 6);
+        add_edge(&mut graph, 4, 5, 8);
+        add_edge(&mut graph, 4, 6, 9);
+        add_edge(&mut graph, 5, 6, 11);
+        // a second component
+        add_edge(&mut graph, 7, 8, 1);
+
+        let (total_cost, edges) = boruvka(&graph);
+        assert_eq!(total_cost, 40);
+        let costs: Vec<i64> = edges.iter().map(|edge| edge.cost).collect();
+        assert_eq!(costs, vec![1, 5, 5, 6, 7, 7, 9]);
+        assert_eq!(boruvka_parallel(&graph, 4), (total_cost, edges));
+    }
+
+    #[test]
+    fn equal_costs() {
+        // every spanning tree of a complete graph with equal costs is minimum,
+        // but a cycle must not be created
+        let mut graph = AdjacencyMap::new();
+        for u in 0..6 {
+            for v in u + 1..6 {
+                add_edge(&mut graph, u, v, 1);
+            }
+        }
+        let (total_cost, edges) = boruvka_parallel(&graph, 3);
+        assert_eq!(total_cost, 5);
+        assert_eq!(edges.len(), 5);
+        assert_eq!(boruvka(&AdjacencyMap::<u32, i64>::new()), (0, vec![]));
+        let one_edge = AdjacencyMap::from([(1, BTreeMap::from([(2, 3)]))]);
+        assert_eq!(boruvka(&one_edge), (3, vec![Edge::new(1, 2, 3)]));
+    }
+
+    #[test]
+    fn prim_kruskal_and_boruvka_agree() {
+        let mut rng = PCG32::new_default(314);
+        for _ in 0..30 {
+            let n = 1 + rng.get_u32() % 40;
+            let m = rng.get_u32() % (3 * n);
+            let mut graph = AdjacencyMap::new();
+            for v in 0..n {
+                graph.insert(v, BTreeMap::new());
+            }
+            for _ in 0..m {
+                let u = rng.get_u32() % n;
+                let v = rng.get_u32() % n;
+                if u != v {
+                    add_edge(&mut graph, u, v, (rng.get_u32() % 20) as i64 - 5);
+                }
+            }
+
+            // prim gives back the forest with each edge in both directions
+            let prim_forest = prim(&graph);
+            let prim_cost = prim_forest
+                .values()
+                .flat_map(|neighbors| neighbors.values())
+                .sum::<i64>()
+                / 2;
+            let (kruskal_cost, kruskal_edges) = kruskal(&graph);
+            let (boruvka_cost, boruvka_edges) = boruvka(&graph);
+            let (parallel_cost, parallel_edges) = boruvka_parallel(&graph, 4);
+
+            assert_eq!(kruskal_cost, prim_cost);
+            assert_eq!(boruvka_cost, prim_cost);
+            assert_eq!(parallel_cost, prim_cost);
+            assert_eq!(boruvka_edges, parallel_edges);
+            assert_eq!(kruskal_edges.len(), boruvka_edges.len());
+
+            // the same graph with each edge stored once, from its smaller end:
+            // some vertices only appear as destinations
+            let mut one_way: AdjacencyMap<u32, i64> = AdjacencyMap::new();
+            for (u, v, cost) in graph.edges().filter(|&(u, v, _)| u < v) {
+                one_way.entry(u).or_default().insert(v, cost);
+            }
+            let (kruskal_cost, kruskal_edges) = kruskal(&one_way);
+            let (boruvka_cost, boruvka_edges) = boruvka(&one_way);
+            assert_eq!(kruskal_cost, prim_cost);
+            assert_eq!(boruvka_cost, prim_cost);
+            assert_eq!(boruvka_parallel(&one_way, 4), (boruvka_cost, boruvka_edges));
+            assert_eq!(kruskal_edges.len(), parallel_edges.len());
+        }
+    }
+}
@@ -1,5 +1,8 @@
 use std::collections::HashSet;
 use std::collections::VecDeque;
+use std::hash::Hash;
+
+use super::Graph;
 
 /// Perform a breadth-first search on Graph `graph`.
 ///
@@ -15,17 +18,24 @@ use std::collections::VecDeque;
 /// of nodes visited as its contents.
 ///
 /// If the target is not found or there is no path from the root,
-/// `None` is returned. 
+/// `None` is returned.
 ///
-pub fn breadth_first_search(graph: &Graph, root: Node, target: Node) -> Option<Vec<u32>> {
-    let mut visited: HashSet<Node> = HashSet::new();
-    let mut history: Vec<u32> = Vec::new();
+pub fn breadth_first_search<G: Graph>(
+    graph: &G,
+    root: G::Vertex,
+    target: G::Vertex,
+) -> Option<Vec<G::Vertex>>
+where
+    G::Vertex: Hash,
+{
+    let mut visited: HashSet<G::Vertex> = HashSet::new();
+    let mut history: Vec<G::Vertex> = Vec::new();
     let mut queue = VecDeque::new();
 
     visited.insert(root);
     queue.push_back(root);
     while let Some(currentnode) = queue.pop_front() {
-        history.push(currentnode.value());
+        history.push(currentnode);
 
         // If we reach the goal, return our travel history.
         if currentnode == target {
@@ -33,7 +43,7 @@ pub fn breadth_first_search(graph: &Graph, root: Node, target: Node) -> Option<V
         }
 
         // Check the neighboring nodes for any that we've not visited yet.
-        for neighbor in currentnode.neighbors(graph) {
+        for (neighbor, _) in graph.neighbors(currentnode) {
             if !visited.contains(&neighbor) {
                 visited.insert(neighbor);
                 queue.push_back(neighbor);
@@ -45,57 +55,20 @@ pub fn breadth_first_search(graph: &Graph, root: Node, target: Node) -> Option<V
     None
 }
 
-// Data Structures
-
-#[derive(Copy, Clone, PartialEq, Eq, Hash)]
-pub struct Node(u32);
-
-#[derive(Copy, Clone, PartialEq, Eq, Hash)]
-pub struct Edge(u32, u32);
-
-#[derive(Clone)]
-pub struct Graph {
-    #[allow(dead_code)]
-    nodes: Vec<Node>,
-    edges: Vec<Edge>,
-}
-
-impl Graph {
-    pub fn new(nodes: Vec<Node>, edges: Vec<Edge>) -> Self {
-        Graph { nodes, edges }
-    }
-}
-
-impl From<u32> for Node {
-    fn from(item: u32) -> Self {
-        Node(item)
-    }
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
 
-impl Node {
-    pub fn value(&self) -> u32 {
-        self.0
-    }
+    type Graph = BTreeMap<u32, Vec<u32>>;
 
-    pub fn neighbors(&self, graph: &Graph) -> Vec<Node> {
+    fn from_edges(nodes: Vec<u32>, edges: Vec<(u32, u32)>) -> Graph {
+        let mut graph: Graph = nodes.into_iter().map(|v| (v, vec![])).collect();
+        for (u, v) in edges {
+            graph.entry(u).or_default().push(v);
+        }
         graph
-            .edges
-            .iter()
-            .filter(|e| e.0 == self.0)
-            .map(|e| e.1.into())
-            .collect()
     }
-}
-
-impl From<(u32, u32)> for Edge {
-    fn from(item: (u32, u32)) -> Self {
-        Edge(item.0, item.1)
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
 
     /* Example graph #1:
      *
@@ -111,10 +84,7 @@ mod tests {
         let nodes = vec![1, 2, 3, 4, 5, 6, 7];
         let edges = vec![(1, 2), (1, 3), (2, 4), (2, 5), (3, 6), (3, 7), (5, 8)];
 
-        Graph::new(
-            nodes.into_iter().map(|v| v.into()).collect(),
-            edges.into_iter().map(|e| e.into()).collect(),
-        )
+        from_edges(nodes, edges)
     }
 
     #[test]
@@ -123,10 +93,7 @@ mod tests {
         let root = 1;
         let target = 10;
 
-        assert_eq!(
-            breadth_first_search(&graph, root.into(), target.into()),
-            None
-        );
+        assert_eq!(breadth_first_search(&graph, root, target), None);
     }
 
     #[test]
@@ -138,7 +105,7 @@ mod tests {
         let expected_path = vec![1, 2, 3, 4, 5, 6, 7, 8];
 
         assert_eq!(
-            breadth_first_search(&graph, root.into(), target.into()),
+            breadth_first_search(&graph, root, target),
             Some(expected_path)
         );
     }
@@ -170,10 +137,7 @@ mod tests {
             (7, 6),
         ];
 
-        Graph::new(
-            nodes.into_iter().map(|v| v.into()).collect(),
-            undirected_edges.into_iter().map(|e| e.into()).collect(),
-        )
+        from_edges(nodes, undirected_edges)
     }
 
     #[test]
@@ -182,10 +146,7 @@ mod tests {
         let root = 8;
         let target = 4;
 
-        assert_eq!(
-            breadth_first_search(&graph, root.into(), target.into()),
-            None
-        );
+        assert_eq!(breadth_first_search(&graph, root, target), None);
     }
 
     #[test]
@@ -197,7 +158,7 @@ mod tests {
         let expected_path = vec![4, 3, 7, 6, 2, 1];
 
         assert_eq!(
-            breadth_first_search(&graph, root.into(), target.into()),
+            breadth_first_search(&graph, root, target),
             Some(expected_path)
         );
     }
@@ -0,0 +1,310 @@
+/*
+Distance queries on a tree with its centroid decomposition.
+
+Every path of the tree goes through the lowest common ancestor of its ends in
+the centroid tree (see `centroid_decomposition.rs`), so a distance from a
+vertex v to the other vertices can be split between the O(lg(n)) centroid
+ancestors of v: for each one c, the distance from v to c plus the distance
+from c to the other vertex.
+
+`CentroidQueries` keeps a value of any type for every centroid, and lets a
+vertex update or read the values of its centroid ancestors along with its
+distance to each of them (computed with `LowestCommonAncestorOnline`). An
+update also gets the distance to the parent of each centroid, and a query the
+value of the child centroid it comes from, so that aggregates can subtract
+what the child component already counted. This is enough for many problems,
+for example:
+- `NearestPainted`: paint vertices, and find the distance from a vertex to the
+  nearest painted one, both in O(lg(n)^2);
+- `count_pairs_within_distance`: the number of pairs of vertices at distance
+  at most k, in O(n.lg(n)^2).
+
+The tree should have its vertices numbered from 1 to n, and
+`graph_enumeration.rs` (or `RootedTree`) may help to convert other
+representations.
+*/
+
+use super::{CentroidDecomposition, LowestCommonAncestorOnline};
+
+pub struct CentroidQueries<T> {
+    // decomposition[v] is the parent of v in the centroid tree, 0 for its root
+    pub decomposition: Vec<usize>,
+    // The value kept for each centroid
+    pub values: Vec<T>,
+    lca: LowestCommonAncestorOnline,
+}
+
+impl<T: Clone> CentroidQueries<T> {
+    // every centroid starts with `initial` as its value
+    pub fn new(adj: &[Vec<usize>], initial: T) -> Self {
+        let num_vertices = adj.len() - 1;
+        let mut centroids = CentroidDecomposition::new(num_vertices);
+        centroids.decompose_tree(adj);
+        let mut lca = LowestCommonAncestorOnline::new(num_vertices);
+        lca.fill_sparse_table(1, 0, 0, adj);
+        CentroidQueries {
+            decomposition: centroids.decomposition,
+            values: vec![initial; num_vertices + 1],
+            lca,
+        }
+    }
+}
+
+impl<T> CentroidQueries<T> {
+    pub fn distance(&self, u: usize, v: usize) -> usize {
+        let ancestor = self.lca.get_ancestor(u, v);
+        self.lca.height[u] + self.lca.height[v] - 2 * self.lca.height[ancestor]
+    }
+
+    // the centroid ancestors of v (v first, the root of the centroid tree
+    // last), with the distance from v to each of them
+    pub fn centroid_ancestors(&self, v: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        std::iter::successors(Some(v), |&c| {
+            Some(self.decomposition[c]).filter(|&p| p != 0)
+        })
+        .map(move |c| (c, self.distance(v, c)))
+    }
+
+    // calls `update` on the value of each centroid ancestor c of v, with the
+    // distance from v to c and from v to the parent of c in the centroid tree
+    // (None for its root), so that a centroid can also keep what its
+    // component gives to its parent
+    pub fn update<F: FnMut(&mut T, usize, Option<usize>)>(&mut self, v: usize, mut update: F) {
+        let ancestors: Vec<(usize, usize)> = self.centroid_ancestors(v).collect();
+        for (i, &(c, distance)) in ancestors.iter().enumerate() {
+            let to_parent = ancestors.get(i + 1).map(|&(_, d)| d);
+            update(&mut self.values[c], distance, to_parent);
+        }
+    }
+
+    // folds the values of the centroid ancestors c of v, with the distance
+    // from v to c and the value of the previous ancestor (the child of c
+    // whose component has v, None when c is v), which usually has to be
+    // subtracted so the vertices of its component are not counted twice
+    pub fn query<R, F: FnMut(R, &T, usize, Option<&T>) -> R>(
+        &self,
+        v: usize,
+        init: R,
+        mut fold: F,
+    ) -> R {
+        let mut child: Option<usize> = None;
+        let mut acc = init;
+        for (c, distance) in self.centroid_ancestors(v) {
+            acc = fold(
+                acc,
+                &self.values[c],
+                distance,
+                child.map(|d| &self.values[d]),
+            );
+            child = Some(c);
+        }
+        acc
+    }
+}
+
+// Vertices can be painted, and the distance to the nearest painted vertex is
+// asked. The value of a centroid is the distance to the nearest painted
+// vertex of its component.
+pub struct NearestPainted {
+    queries: CentroidQueries<usize>,
+}
+
+impl NearestPainted {
+    pub fn new(adj: &[Vec<usize>]) -> Self {
+        NearestPainted {
+            queries: CentroidQueries::new(adj, usize::MAX),
+        }
+    }
+
+    pub fn paint(&mut self, v: usize) {
+        self.queries.update(v, |nearest, distance, _| {
+            *nearest = (*nearest).min(distance)
+        });
+    }
+
+    // the distance from v to the nearest painted vertex, None if no vertex is
+    // painted
+    pub fn nearest(&self, v: usize) -> Option<usize> {
+        let nearest = self
+            .queries
+            .query(v, usize::MAX, |best, &nearest, distance, _| {
+                best.min(nearest.saturating_add(distance))
+            });
+        (nearest != usize::MAX).then_some(nearest)
+    }
+}
+
+// the number of pairs of distinct vertices {u, v} (unordered) at distance at
+// most k from each other
+pub fn count_pairs_within_distance(adj: &[Vec<usize>], k: usize) -> usize {
+    let queries = CentroidQueries::new(adj, ());
+    let num_vertices = adj.len() - 1;
+    // for each centroid c: the distances from c to the vertices of its
+    // component, and from the parent of c to the same vertices
+    let mut to_centroid: Vec<Vec<usize>> = vec![vec![]; num_vertices + 1];
+    let mut to_parent: Vec<Vec<usize>> = vec![vec![]; num_vertices + 1];
+    for v in 1..=num_vertices {
+        let mut previous: Option<usize> = None;
+        for (c, distance) in queries.centroid_ancestors(v) {
+            to_centroid[c].push(distance);
+            if let Some(child) = previous {
+                to_parent[child].push(distance);
+            }
+            previous = Some(c);
+        }
+    }
+
+    // the pairs are counted at the lowest common centroid ancestor of their
+    // ends: the pairs of the component of c, minus those in the component of
+    // a single child of c
+    let mut count = 0;
+    for c in 1..=num_vertices {
+        count += pairs_with_sum_at_most(&mut to_centroid[c], k);
+        count -= pairs_with_sum_at_most(&mut to_parent[c], k);
+    }
+    count
+}
+
+// the number of pairs i < j with values[i] + values[j] <= k
+fn pairs_with_sum_at_most(values: &mut [usize], k: usize) -> usize {
+    values.sort_unstable();
+    let mut count = 0;
+    let mut j = values.len();
+    for i in 0..values.len() {
+        while j > 0 && values[i] + values[j - 1] > k {
+            j -= 1;
+        }
+        // the pairs (i, l) for i < l < j
+        if j <= i + 1 {
+            break;
+        }
+        count += j - i - 1;
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{adjacency_lists, random_tree};
+    use crate::math::PCG32;
+
+    // the distances from v to every vertex, by a DFS
+    fn distances_from(adj: &[Vec<usize>], v: usize) -> Vec<usize> {
+        let mut distance = vec![usize::MAX; adj.len()];
+        distance[v] = 0;
+        let mut stack = vec![v];
+        while let Some(u) = stack.pop() {
+            for &w in &adj[u] {
+                if distance[w] == usize::MAX {
+                    distance[w] = distance[u] + 1;
+                    stack.push(w);
+                }
+            }
+        }
+        distance
+    }
+
+    #[test]
+    fn path() {
+        // 1 - 2 - 3 - 4 - 5 - 6 - 7
+        let mut adj = vec![vec![]; 8];
+        for v in 1..7 {
+            adj[v].push(v + 1);
+            adj[v + 1].push(v);
+        }
+        let mut painted = NearestPainted::new(&adj);
+        assert_eq!(painted.nearest(4), None);
+        painted.paint(1);
+        assert_eq!(painted.nearest(7), Some(6));
+        painted.paint(6);
+        assert_eq!(painted.nearest(7), Some(1));
+        assert_eq!(painted.nearest(3), Some(2));
+        assert_eq!(painted.nearest(6), Some(0));
+
+        assert_eq!(count_pairs_within_distance(&adj, 0), 0);
+        assert_eq!(count_pairs_within_distance(&adj, 1), 6);
+        assert_eq!(count_pairs_within_distance(&adj, 2), 11);
+        assert_eq!(count_pairs_within_distance(&adj, 6), 21);
+    }
+
+    #[test]
+    fn random_trees() {
+        let mut rng = PCG32::new_default(8128);
+        for _ in 0..20 {
+            let n = 1 + rng.get_u32() as usize % 60;
+            let adj = adjacency_lists(&random_tree(n, &mut rng));
+            let distances: Vec<Vec<usize>> = (0..=n)
+                .map(|v| {
+                    if v == 0 {
+                        vec![]
+                    } else {
+                        distances_from(&adj, v)
+                    }
+                })
+                .collect();
+
+            let queries = CentroidQueries::new(&adj, ());
+            for (u, row) in distances.iter().enumerate().skip(1) {
+                for (v, &distance) in row.iter().enumerate().skip(1) {
+                    assert_eq!(queries.distance(u, v), distance);
+                }
+            }
+
+            let mut painted = NearestPainted::new(&adj);
+            let mut painted_vertices = vec![];
+            for _ in 0..n {
+                let v = 1 + rng.get_u32() as usize % n;
+                if rng.get_u32().is_multiple_of(2) {
+                    painted.paint(v);
+                    painted_vertices.push(v);
+                } else {
+                    let expected = painted_vertices.iter().map(|&p| distances[v][p]).min();
+                    assert_eq!(painted.nearest(v), expected);
+                }
+            }
+
+            for k in 0..6 {
+                let expected = (1..=n)
+                    .flat_map(|u| (u + 1..=n).map(move |v| (u, v)))
+                    .filter(|&(u, v)| distances[u][v] <= k)
+                    .count();
+                assert_eq!(count_pairs_within_distance(&adj, k), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn custom_aggregate() {
+        // the sum of the distances to a set of marked vertices: each centroid
+        // keeps the number of marked vertices of its component and the sum of
+        // their distances, to itself and to its parent
+        let mut rng = PCG32::new_default(31);
+        let n = 40;
+        let adj = adjacency_lists(&random_tree(n, &mut rng));
+        let mut queries = CentroidQueries::new(&adj, (0, 0, 0));
+        let mut marked = vec![];
+        for _ in 0..15 {
+            let v = 1 + rng.get_u32() as usize % n;
+            marked.push(v);
+            queries.update(v, |value, distance, to_parent| {
+                *value = (
+                    value.0 + 1,
+                    value.1 + distance,
+                    value.2 + to_parent.unwrap_or(0),
+                );
+            });
+        }
+
+        for v in 1..=n {
+            // the vertices of the component of the child were already
+            // counted, through the child
+            let total = queries.query(v, 0, |total, &(count, sum, _), distance, child| {
+                let (child_count, _, child_to_parent) = child.copied().unwrap_or((0, 0, 0));
+                total + sum + count * distance - child_to_parent - child_count * distance
+            });
+            let expected: usize = marked.iter().map(|&m| queries.distance(v, m)).sum();
+            assert_eq!(total, expected);
+        }
+    }
+}
@@ -0,0 +1,372 @@
+/*
+Compressed Sparse Row (CSR) representation of a static graph.
+
+Vertices are numbered from 0 to n - 1. The out-going edges of vertex `v`
+are stored contiguously in `targets[offsets[v]..offsets[v + 1]]` (and their
+weights at the same positions of `weights`), so walking the neighbours of a
+vertex is a linear scan over memory without any pointer-chasing.
+
+The graph can't be modified once it is built. Graphs whose vertices are not
+numbered that way can be converted with `from_graph` or
+`from_labelled_edges`, which also return a `VertexIndex` to translate between
+the original vertices and their dense ids.
+
+Besides implementing `Graph`, it has its own versions of `dijkstra` and
+`breadth_first_search` that keep their state in `Vec`s indexed by vertex
+instead of maps, and `StronglyConnectedComponents::find_components_compressed`
+runs Tarjan's algorithm on it directly.
+*/
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use std::ops::Add;
+
+use super::graph_representation::Graph;
+
+/// Maps the vertices of a graph to dense ids from 0 to n - 1 (in increasing
+/// order of the vertices) and back.
+pub struct VertexIndex<V> {
+    labels: Vec<V>,
+}
+
+impl<V: Ord + Copy> VertexIndex<V> {
+    pub fn new(mut labels: Vec<V>) -> Self {
+        labels.sort_unstable();
+        labels.dedup();
+        VertexIndex { labels }
+    }
+
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    /// The dense id of `vertex`, or None if it wasn't in the graph
+    pub fn id(&self, vertex: &V) -> Option<usize> {
+        self.labels.binary_search(vertex).ok()
+    }
+
+    /// The original vertex with the dense id `id`
+    pub fn vertex(&self, id: usize) -> V {
+        self.labels[id]
+    }
+
+    pub fn vertices(&self) -> &[V] {
+        &self.labels
+    }
+}
+
+pub struct CompressedSparseRowGraph<E> {
+    offsets: Vec<usize>,
+    targets: Vec<usize>,
+    weights: Vec<E>,
+}
+
+impl<E: Copy> CompressedSparseRowGraph<E> {
+    /// Builds the graph from a list of directed (source, destination, weight)
+    /// edges. Every endpoint should be less than `num_vertices`. Edges of each
+    /// vertex keep the order they had in `edges`.
+    pub fn from_edges(num_vertices: usize, edges: &[(usize, usize, E)]) -> Self {
+        let mut offsets = vec![0; num_vertices + 1];
+        for &(u, _, _) in edges {
+            offsets[u + 1] += 1;
+        }
+        for v in 0..num_vertices {
+            offsets[v + 1] += offsets[v];
+        }
+        // counting sort the edges by their source
+        let mut next = offsets.clone();
+        let mut targets = vec![0; edges.len()];
+        let mut weights = Vec::with_capacity(edges.len());
+        let mut order = vec![0; edges.len()];
+        for (i, &(u, _, _)) in edges.iter().enumerate() {
+            order[next[u]] = i;
+            next[u] += 1;
+        }
+        for (pos, &i) in order.iter().enumerate() {
+            targets[pos] = edges[i].1;
+            weights.push(edges[i].2);
+        }
+        CompressedSparseRowGraph {
+            offsets,
+            targets,
+            weights,
+        }
+    }
+
+    /// Builds the graph from directed edges between arbitrary vertices
+    pub fn from_labelled_edges<V: Ord + Copy>(edges: &[(V, V, E)]) -> (Self, VertexIndex<V>) {
+        let index = VertexIndex::new(edges.iter().flat_map(|&(u, v, _)| [u, v]).collect());
+        let edges: Vec<(usize, usize, E)> = edges
+            .iter()
+            .map(|&(u, v, w)| (index.id(&u).unwrap(), index.id(&v).unwrap(), w))
+            .collect();
+        (Self::from_edges(index.len(), &edges), index)
+    }
+
+    /// Copies any other `Graph`, for example an `AdjacencyMap`
+    pub fn from_graph<G: Graph<Weight = E>>(graph: &G) -> (Self, VertexIndex<G::Vertex>) {
+        let index = VertexIndex::new(
+            graph
+                .vertices()
+                .chain(graph.edges().map(|(_, v, _)| v))
+                .collect(),
+        );
+        let edges: Vec<(usize, usize, E)> = graph
+            .edges()
+            .map(|(u, v, w)| (index.id(&u).unwrap(), index.id(&v).unwrap(), w))
+            .collect();
+        (Self::from_edges(index.len(), &edges), index)
+    }
+
+    #[inline]
+    pub fn degree(&self, vertex: usize) -> usize {
+        self.offsets[vertex + 1] - self.offsets[vertex]
+    }
+
+    /// The destinations of the out-going edges of `vertex`
+    #[inline]
+    pub fn targets(&self, vertex: usize) -> &[usize] {
+        &self.targets[self.offsets[vertex]..self.offsets[vertex + 1]]
+    }
+
+    /// The weights of the out-going edges of `vertex`, in the same order as
+    /// `targets(vertex)`
+    #[inline]
+    pub fn weights(&self, vertex: usize) -> &[E] {
+        &self.weights[self.offsets[vertex]..self.offsets[vertex + 1]]
+    }
+
+    /// Same as `graph::dijkstra`, in a `Vec`: result[v] is what
+    /// `dijkstra(&graph, &start).get(&v)` gives, None if v isn't reachable
+    /// from `start`, Some(None) for `start` itself and Some(Some((predecessor,
+    /// distance))) otherwise. E::default() should be zero.
+    pub fn dijkstra(&self, start: usize) -> Vec<Option<Option<(usize, E)>>>
+    where
+        E: Ord + Add<Output = E> + Default,
+    {
+        let mut ans: Vec<Option<Option<(usize, E)>>> = vec![None; self.num_vertices()];
+        if !self.contains_vertex(start) {
+            return ans;
+        }
+        let mut done = vec![false; self.num_vertices()];
+        let mut prio = BinaryHeap::new();
+
+        ans[start] = Some(None);
+        prio.push(Reverse((E::default(), start)));
+
+        while let Some(Reverse((dist, v))) = prio.pop() {
+            // an older, longer, entry of a vertex we've already handled
+            if done[v] {
+                continue;
+            }
+            done[v] = true;
+
+            for (&next, &weight) in self.targets(v).iter().zip(self.weights(v)) {
+                let dist_next = dist + weight;
+                match ans[next] {
+                    // the start keeps no predecessor, like in `graph::dijkstra`
+                    Some(None) => {}
+                    Some(Some((_, d))) if d <= dist_next => {}
+                    _ => {
+                        ans[next] = Some(Some((v, dist_next)));
+                        prio.push(Reverse((dist_next, next)));
+                    }
+                }
+            }
+        }
+
+        ans
+    }
+
+    /// Same as `graph::breadth_first_search`: the vertices visited until
+    /// `target` was found, or None if it isn't reachable from `root`. A root
+    /// that is not in the graph has no neighbours.
+    pub fn breadth_first_search(&self, root: usize, target: usize) -> Option<Vec<usize>> {
+        if !self.contains_vertex(root) {
+            return (root == target).then(|| vec![root]);
+        }
+        let mut visited = vec![false; self.num_vertices()];
+        let mut history = Vec::new();
+        let mut queue = VecDeque::new();
+
+        visited[root] = true;
+        queue.push_back(root);
+        while let Some(v) = queue.pop_front() {
+            history.push(v);
+            if v == target {
+                return Some(history);
+            }
+            for &u in self.targets(v) {
+                if !visited[u] {
+                    visited[u] = true;
+                    queue.push_back(u);
+                }
+            }
+        }
+
+        None
+    }
+}
+
+impl<E: Copy> Graph for CompressedSparseRowGraph<E> {
+    type Vertex = usize;
+    type Weight = E;
+
+    fn num_vertices(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    fn num_edges(&self) -> usize {
+        self.targets.len()
+    }
+
+    fn vertices(&self) -> impl Iterator<Item = usize> + '_ {
+        0..self.num_vertices()
+    }
+
+    fn neighbors(&self, vertex: usize) -> impl Iterator<Item = (usize, E)> + '_ {
+        let range = if self.contains_vertex(vertex) {
+            self.offsets[vertex]..self.offsets[vertex + 1]
+        } else {
+            0..0
+        };
+        self.targets[range.clone()]
+            .iter()
+            .copied()
+            .zip(self.weights[range].iter().copied())
+    }
+
+    fn contains_vertex(&self, vertex: usize) -> bool {
+        vertex < self.num_vertices()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{
+        breadth_first_search, dijkstra, topological_sort, AdjacencyMap, StronglyConnectedComponents,
+    };
+    use crate::math::PCG32;
+    use std::collections::BTreeMap;
+
+    fn random_graph(num_vertices: usize, num_edges: usize, seed: u64) -> AdjacencyMap<usize, u64> {
+        let mut rng = PCG32::new_default(seed);
+        let mut graph: AdjacencyMap<usize, u64> =
+            (0..num_vertices).map(|v| (v, Default::default())).collect();
+        for _ in 0..num_edges {
+            let u = rng.get_u32() as usize % num_vertices;
+            let v = rng.get_u32() as usize % num_vertices;
+            let w = (rng.get_u32() % 1000) as u64;
+            graph.get_mut(&u).unwrap().insert(v, w);
+        }
+        graph
+    }
+
+    // Turns the dense result of `CompressedSparseRowGraph::dijkstra` back into
+    // the map returned by `graph::dijkstra`
+    fn to_predecessor_map(
+        dists: &[Option<Option<(usize, u64)>>],
+        index: &VertexIndex<usize>,
+    ) -> BTreeMap<usize, Option<(usize, u64)>> {
+        dists
+            .iter()
+            .enumerate()
+            .filter_map(|(v, d)| {
+                d.map(|entry| (index.vertex(v), entry.map(|(p, d)| (index.vertex(p), d))))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn from_edges() {
+        let graph =
+            CompressedSparseRowGraph::from_edges(4, &[(2, 0, 'a'), (0, 1, 'b'), (2, 3, 'c')]);
+        assert_eq!(graph.num_vertices(), 4);
+        assert_eq!(graph.num_edges(), 3);
+        assert_eq!(graph.degree(2), 2);
+        assert_eq!(graph.degree(3), 0);
+        assert_eq!(
+            graph.edges().collect::<Vec<_>>(),
+            vec![(0, 1, 'b'), (2, 0, 'a'), (2, 3, 'c')]
+        );
+        assert_eq!(graph.neighbors(7).count(), 0);
+    }
+
+    #[test]
+    fn same_results_as_adjacency_map() {
+        let edges = [(0, 1, 4), (0, 2, 1), (2, 1, 2), (1, 3, 5), (2, 3, 8)];
+        let csr = CompressedSparseRowGraph::from_edges(4, &edges);
+        let mut map: AdjacencyMap<usize, u32> = (0..4).map(|v| (v, Default::default())).collect();
+        for &(u, v, w) in edges.iter() {
+            map.get_mut(&u).unwrap().insert(v, w);
+        }
+        assert_eq!(dijkstra(&csr, &0), dijkstra(&map, &0));
+        let expected = dijkstra(&map, &0);
+        for (v, entry) in csr.dijkstra(0).into_iter().enumerate() {
+            assert_eq!(entry, expected.get(&v).copied());
+        }
+        assert_eq!(topological_sort(&csr), topological_sort(&map));
+
+        // a root outside of the graph has no neighbours
+        assert_eq!(csr.breadth_first_search(9, 1), None);
+        assert_eq!(csr.breadth_first_search(9, 9), Some(vec![9]));
+        assert_eq!(breadth_first_search(&csr, 9, 1), None);
+        assert_eq!(breadth_first_search(&csr, 9, 9), Some(vec![9]));
+    }
+
+    #[test]
+    fn labelled_vertices() {
+        let (graph, index) =
+            CompressedSparseRowGraph::from_labelled_edges(&[("b", "z", 1), ("a", "b", 2)]);
+        assert_eq!(index.vertices(), &["a", "b", "z"]);
+        assert_eq!(index.id(&"z"), Some(2));
+        assert_eq!(index.id(&"c"), None);
+        assert_eq!(graph.targets(0), &[1]);
+        assert_eq!(graph.weights(1), &[1]);
+        assert_eq!(
+            graph.dijkstra(0),
+            vec![Some(None), Some(Some((0, 2))), Some(Some((1, 3)))]
+        );
+        assert_eq!(graph.dijkstra(2), vec![None, None, Some(None)]);
+        assert_eq!(graph.dijkstra(3), vec![None; 3]);
+    }
+
+    #[test]
+    fn specialised_algorithms_match_generic_ones() {
+        let map = random_graph(200, 1000, 314);
+        let (graph, index) = CompressedSparseRowGraph::from_graph(&map);
+        for start in [0, 17, 199] {
+            let id = index.id(&start).unwrap();
+            assert_eq!(
+                to_predecessor_map(&graph.dijkstra(id), &index),
+                dijkstra(&map, &start)
+            );
+            assert_eq!(
+                graph.breadth_first_search(id, 42),
+                breadth_first_search(&map, start, 42)
+            );
+        }
+
+        // `StronglyConnectedComponents` numbers vertices from 1, so vertex 0
+        // is left without edges
+        let adj: Vec<Vec<usize>> = (0..=200)
+            .map(|v| match v {
+                0 => vec![],
+                v => map[&(v - 1)].keys().map(|u| u + 1).collect(),
+            })
+            .collect();
+        let shifted: Vec<(usize, usize, u64)> =
+            map.edges().map(|(u, v, w)| (u + 1, v + 1, w)).collect();
+        let mut expected = StronglyConnectedComponents::new(200);
+        expected.find_components(&adj);
+        let mut sccs = StronglyConnectedComponents::new(200);
+        sccs.find_components_compressed(&CompressedSparseRowGraph::from_edges(201, &shifted));
+        assert_eq!(sccs.component, expected.component);
+        assert_eq!(sccs.num_components, expected.num_components);
+    }
+}
@@ -24,6 +24,8 @@ SOFTWARE.
 #[allow(unused_imports)]
 use std::io;
 
+use super::{Game, GameSearch, Outcome, Side, ZobristTable};
+
 //Interactive Tic-Tac-Toe play needs the "rand = "0.8.3" crate.
 //#[cfg(not(test))]
 //extern crate rand;
@@ -43,12 +45,6 @@ pub enum Players {
     PlayerO,
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
-struct SinglePlayAction {
-    position: Position,
-    side: Players,
-}
-
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct PlayActions {
     positions: Vec<Position>,
@@ -192,139 +188,133 @@ fn win_check(player: Players, board: &[Vec<Players>]) -> bool {
     false
 }
 
-//Minimize the actions of the opponent while maximizing the game state of the current player.
-pub fn minimax(side: Players, board: &[Vec<Players>]) -> Option<PlayActions> {
-    //Check that board is in a valid state.
-    if win_check(Players::PlayerX, board) || win_check(Players::PlayerO, board) {
-        return None;
-    }
+//The board as a `Game`, so that the positions can be searched by `GameSearch`.
+struct TicTacToe {
+    board: Vec<Vec<Players>>,
+    side: Players,
+    hash: u64,
+    keys: ZobristTable,
+}
 
-    let opposite = match side {
+//Zobrist features: one per cell and player, and one for the turn of PlayerO.
+fn feature(position: Position, player: Players) -> usize {
+    2 * (3 * position.y as usize + position.x as usize) + (player == Players::PlayerO) as usize
+}
+
+const SIDE_FEATURE: usize = 18;
+
+fn opponent(side: Players) -> Players {
+    match side {
         Players::PlayerX => Players::PlayerO,
         Players::PlayerO => Players::PlayerX,
         Players::Blank => panic!("Minimax can't operate when a player isn't specified."),
-    };
-
-    let positions = available_positions(board);
-    if positions.is_empty() {
-        return None;
     }
+}
 
-    //Play position
-    let mut best_move: Option<PlayActions> = None;
-
-    for pos in positions {
-        let mut board_next = board.to_owned();
-        board_next[pos.y as usize][pos.x as usize] = side;
-
-        //Check for a win condition before recursion to determine if this node is terminal.
-        if win_check(Players::PlayerX, &board_next) {
-            append_playaction(
-                side,
-                &mut best_move,
-                SinglePlayAction {
-                    position: pos,
-                    side: Players::PlayerX,
-                },
-            );
-            continue;
-        }
-
-        if win_check(Players::PlayerO, &board_next) {
-            append_playaction(
-                side,
-                &mut best_move,
-                SinglePlayAction {
-                    position: pos,
-                    side: Players::PlayerO,
-                },
-            );
-            continue;
-        }
-
-        let result = minimax(opposite, &board_next);
-        let current_score = match result {
-            Some(x) => x.side,
-            _ => Players::Blank,
+impl TicTacToe {
+    fn new(board: &[Vec<Players>], side: Players) -> Self {
+        let keys = ZobristTable::new(SIDE_FEATURE + 1, 2021);
+        let mut hash = if side == Players::PlayerO {
+            keys.key(SIDE_FEATURE)
+        } else {
+            0
         };
-
-        append_playaction(
+        for (y, board_row) in board.iter().enumerate() {
+            for (x, &board_cell) in board_row.iter().enumerate() {
+                if board_cell != Players::Blank {
+                    let position = Position {
+                        x: x as u8,
+                        y: y as u8,
+                    };
+                    hash ^= keys.key(feature(position, board_cell));
+                }
+            }
+        }
+        TicTacToe {
+            board: board.to_owned(),
             side,
-            &mut best_move,
-            SinglePlayAction {
-                position: pos,
-                side: current_score,
-            },
-        )
+            hash,
+            keys,
+        }
     }
-    best_move
-}
 
-//Promote only better or collate equally scored game plays
-fn append_playaction(
-    current_side: Players,
-    opt_play_actions: &mut Option<PlayActions>,
-    appendee: SinglePlayAction,
-) {
-    if opt_play_actions.is_none() {
-        *opt_play_actions = Some(PlayActions {
-            positions: vec![appendee.position],
-            side: appendee.side,
-        });
-        return;
+    fn play(&mut self, pos: Position, player: Players) {
+        self.board[pos.y as usize][pos.x as usize] = player;
+        self.hash ^= self.keys.key(feature(pos, self.side)) ^ self.keys.key(SIDE_FEATURE);
     }
+}
 
-    let mut play_actions = opt_play_actions.as_mut().unwrap();
-
-    //New game action is scored from the current side and the current saved best score against the new game action.
-    match (current_side, play_actions.side, appendee.side) {
-        (Players::Blank, _, _) => panic!("Unreachable state."),
+impl Game for TicTacToe {
+    type Move = Position;
 
-        //Winning scores
-        (Players::PlayerX, Players::PlayerX, Players::PlayerX) => {
-            play_actions.positions.push(appendee.position);
-        }
-        (Players::PlayerX, Players::PlayerX, _) => {}
-        (Players::PlayerO, Players::PlayerO, Players::PlayerO) => {
-            play_actions.positions.push(appendee.position);
+    fn side_to_move(&self) -> Side {
+        match self.side {
+            Players::PlayerX => Side::First,
+            _ => Side::Second,
         }
-        (Players::PlayerO, Players::PlayerO, _) => {}
+    }
 
-        //Non-winning to Winning scores
-        (Players::PlayerX, _, Players::PlayerX) => {
-            play_actions.side = Players::PlayerX;
-            play_actions.positions.clear();
-            play_actions.positions.push(appendee.position);
-        }
-        (Players::PlayerO, _, Players::PlayerO) => {
-            play_actions.side = Players::PlayerO;
-            play_actions.positions.clear();
-            play_actions.positions.push(appendee.position);
+    fn legal_moves(&self) -> Vec<Position> {
+        if self.outcome().is_some() {
+            return Vec::new();
         }
+        available_positions(&self.board)
+    }
 
-        //Losing to Neutral scores
-        (Players::PlayerX, Players::PlayerO, Players::Blank) => {
-            play_actions.side = Players::Blank;
-            play_actions.positions.clear();
-            play_actions.positions.push(appendee.position);
-        }
+    fn apply(&mut self, pos: Position) {
+        self.play(pos, self.side);
+        self.side = opponent(self.side);
+    }
 
-        (Players::PlayerO, Players::PlayerX, Players::Blank) => {
-            play_actions.side = Players::Blank;
-            play_actions.positions.clear();
-            play_actions.positions.push(appendee.position);
+    fn undo(&mut self, pos: Position) {
+        self.side = opponent(self.side);
+        self.play(pos, Players::Blank);
+    }
+
+    fn outcome(&self) -> Option<Outcome> {
+        if win_check(self.side, &self.board) {
+            Some(Outcome::Win)
+        } else if win_check(opponent(self.side), &self.board) {
+            Some(Outcome::Loss)
+        } else if available_positions(&self.board).is_empty() {
+            Some(Outcome::Draw)
+        } else {
+            None
         }
+    }
 
-        //Ignoring lower scored plays
-        (Players::PlayerX, Players::Blank, Players::PlayerO) => {}
-        (Players::PlayerO, Players::Blank, Players::PlayerX) => {}
+    fn hash(&self) -> u64 {
+        self.hash
+    }
+}
 
-        //No change hence append only
-        (_, _, _) => {
-            assert!(play_actions.side == appendee.side);
-            play_actions.positions.push(appendee.position);
-        }
+//Minimize the actions of the opponent while maximizing the game state of the current player.
+pub fn minimax(side: Players, board: &[Vec<Players>]) -> Option<PlayActions> {
+    //Check that board is in a valid state.
+    if win_check(Players::PlayerX, board) || win_check(Players::PlayerO, board) {
+        return None;
     }
+
+    let opposite = opponent(side);
+
+    //Score every play position with a search to the end of the game.
+    let mut game = TicTacToe::new(board, side);
+    let scores = GameSearch::new(12).score_moves(&mut game, 9);
+
+    //Only whether a play wins, draws or loses matters, not how fast.
+    let best = scores.iter().map(|&(_, score)| score.signum()).max()?;
+    Some(PlayActions {
+        positions: scores
+            .iter()
+            .filter(|&&(_, score)| score.signum() == best)
+            .map(|&(pos, _)| pos)
+            .collect(),
+        side: match best {
+            1 => side,
+            -1 => opposite,
+            _ => Players::Blank,
+        },
+    })
 }
 
 #[cfg(test)]
@@ -408,4 +398,18 @@ mod test {
             })
         );
     }
+
+    #[test]
+    fn empty_board_is_a_draw() {
+        let board = vec![vec![Players::Blank; 3]; 3];
+        let responses = minimax(Players::PlayerX, &board).unwrap();
+        assert_eq!(responses.side, Players::Blank);
+        assert_eq!(responses.positions.len(), 9);
+
+        let mut game = TicTacToe::new(&board, Players::PlayerX);
+        let result = GameSearch::new(12).search(&mut game, 9);
+        assert_eq!(result.score, 0);
+        assert_eq!(result.depth, 9);
+        assert_eq!(game.board, board);
+    }
 }
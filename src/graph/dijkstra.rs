@@ -2,46 +2,112 @@ use std::cmp::Reverse;
 use std::collections::{BTreeMap, BinaryHeap};
 use std::ops::Add;
 
-type Graph<V, E> = BTreeMap<V, BTreeMap<V, E>>;
+use super::{Graph, PredecessorMap};
 
 // performs Dijsktra's algorithm on the given graph from the given start
 // the graph is a positively-weighted undirected graph
 //
 // returns a map that for each reachable vertex associates the distance and the predecessor
 // since the start has no predecessor but is reachable, map[start] will be None
-pub fn dijkstra<V: Ord + Copy, E: Ord + Copy + Add<Output = E>>(
-    graph: &Graph<V, E>,
-    start: &V,
-) -> BTreeMap<V, Option<(V, E)>> {
+pub fn dijkstra<G: Graph>(graph: &G, start: &G::Vertex) -> PredecessorMap<G::Vertex, G::Weight>
+where
+    G::Weight: Ord + Add<Output = G::Weight>,
+{
+    search(graph, start, None)
+}
+
+// finds a shortest path from start to target, stopping as soon as the
+// distance of target is known instead of exploring the whole graph
+//
+// returns the vertices of the path (start and target included) and its total
+// weight, or None if target is unreachable. E::default() should be zero.
+pub fn shortest_path<G: Graph>(
+    graph: &G,
+    start: &G::Vertex,
+    target: &G::Vertex,
+) -> Option<(Vec<G::Vertex>, G::Weight)>
+where
+    G::Weight: Ord + Add<Output = G::Weight> + Default,
+{
+    reconstruct_path(&search(graph, start, Some(*target)), target)
+}
+
+// walks a map returned by `dijkstra` (or `bellman_ford`) back from target
+//
+// returns the vertices of the path from the start to target and its total
+// weight, or None if target is unreachable. E::default() should be zero.
+pub fn reconstruct_path<V: Ord + Copy, E: Copy + Default>(
+    predecessors: &PredecessorMap<V, E>,
+    target: &V,
+) -> Option<(Vec<V>, E)> {
+    let cost = match predecessors.get(target)? {
+        Some((_, dist)) => *dist,
+        None => E::default(),
+    };
+    let mut path = vec![*target];
+    let mut current = *target;
+    while let Some((prev, _)) = predecessors[&current] {
+        path.push(prev);
+        current = prev;
+    }
+    path.reverse();
+    Some((path, cost))
+}
+
+fn search<G: Graph>(
+    graph: &G,
+    start: &G::Vertex,
+    target: Option<G::Vertex>,
+) -> PredecessorMap<G::Vertex, G::Weight>
+where
+    G::Weight: Ord + Add<Output = G::Weight>,
+{
     let mut ans = BTreeMap::new();
     let mut prio = BinaryHeap::new();
 
     // start is the special case that doesn't have a predecessor
     ans.insert(*start, None);
+    if target == Some(*start) {
+        return ans;
+    }
 
-    for (new, weight) in &graph[start] {
-        ans.insert(*new, Some((*start, *weight)));
-        prio.push(Reverse((*weight, new, start)));
+    for (new, weight) in graph.neighbors(*start) {
+        // a loop on start doesn't give it a predecessor
+        if new == *start {
+            continue;
+        }
+        match ans.get(&new) {
+            // a parallel edge that was at least as short
+            Some(Some((_, dist))) if *dist <= weight => {}
+            _ => {
+                ans.insert(new, Some((*start, weight)));
+                prio.push(Reverse((weight, new, *start)));
+            }
+        }
     }
 
     while let Some(Reverse((dist_new, new, prev))) = prio.pop() {
-        match ans[new] {
+        match ans[&new] {
             // what we popped is what is in ans, we'll compute it
-            Some((p, d)) if p == *prev && d == dist_new => {}
+            Some((p, d)) if p == prev && d == dist_new => {}
             // otherwise it's not interesting
             _ => continue,
         }
+        // the distance of target can't change anymore
+        if target == Some(new) {
+            break;
+        }
 
-        for (next, weight) in &graph[new] {
-            match ans.get(next) {
+        for (next, weight) in graph.neighbors(new) {
+            match ans.get(&next) {
                 // if ans[next] is a lower dist than the alternative one, we do nothing
-                Some(Some((_, dist_next))) if dist_new + *weight >= *dist_next => {}
+                Some(Some((_, dist_next))) if dist_new + weight >= *dist_next => {}
                 // if ans[next] is None then next is start and so the distance won't be changed, it won't be added again in prio
                 Some(None) => {}
                 // the new path is shorter, either new was not in ans or it was farther
                 _ => {
-                    ans.insert(*next, Some((*new, *weight + dist_new)));
-                    prio.push(Reverse((*weight + dist_new, next, new)));
+                    ans.insert(next, Some((new, weight + dist_new)));
+                    prio.push(Reverse((weight + dist_new, next, new)));
                 }
             }
         }
@@ -52,17 +118,18 @@ pub fn dijkstra<V: Ord + Copy, E: Ord + Copy + Add<Output = E>>(
 
 #[cfg(test)]
 mod tests {
-    use super::{dijkstra, Graph};
+    use super::{dijkstra, reconstruct_path, shortest_path};
+    use crate::graph::AdjacencyMap;
     use std::collections::BTreeMap;
 
-    fn add_edge<V: Ord + Copy, E: Ord>(graph: &mut Graph<V, E>, v1: V, v2: V, c: E) {
-        graph.entry(v1).or_insert_with(BTreeMap::new).insert(v2, c);
-        graph.entry(v2).or_insert_with(BTreeMap::new);
+    fn add_edge<V: Ord + Copy, E: Ord>(graph: &mut AdjacencyMap<V, E>, v1: V, v2: V, c: E) {
+        graph.entry(v1).or_default().insert(v2, c);
+        graph.entry(v2).or_default();
     }
 
     #[test]
     fn single_vertex() {
-        let mut graph: Graph<usize, usize> = BTreeMap::new();
+        let mut graph: AdjacencyMap<usize, usize> = BTreeMap::new();
         graph.insert(0, BTreeMap::new());
 
         let mut dists = BTreeMap::new();
@@ -155,4 +222,53 @@ mod tests {
         dists_e.insert('b', Some(('c', 39)));
         assert_eq!(dijkstra(&graph, &'e'), dists_e);
     }
+
+    #[test]
+    fn path_to_target() {
+        let mut graph = BTreeMap::new();
+        add_edge(&mut graph, 'a', 'c', 12);
+        add_edge(&mut graph, 'a', 'd', 60);
+        add_edge(&mut graph, 'b', 'a', 10);
+        add_edge(&mut graph, 'c', 'b', 20);
+        add_edge(&mut graph, 'c', 'd', 32);
+        add_edge(&mut graph, 'e', 'a', 7);
+
+        assert_eq!(
+            shortest_path(&graph, &'e', &'d'),
+            Some((vec!['e', 'a', 'c', 'd'], 51))
+        );
+        assert_eq!(shortest_path(&graph, &'b', &'b'), Some((vec!['b'], 0)));
+        assert_eq!(shortest_path(&graph, &'d', &'a'), None);
+
+        let dists = dijkstra(&graph, &'b');
+        assert_eq!(
+            reconstruct_path(&dists, &'d'),
+            Some((vec!['b', 'a', 'c', 'd'], 54))
+        );
+        assert_eq!(reconstruct_path(&dists, &'e'), None);
+    }
+
+    #[test]
+    fn stops_at_target() {
+        let mut graph = BTreeMap::new();
+        for i in 0..100 {
+            add_edge(&mut graph, i, i + 1, 1);
+        }
+        assert_eq!(shortest_path(&graph, &0, &3), Some((vec![0, 1, 2, 3], 3)));
+        // nothing after the target should have been explored
+        assert!(!super::search(&graph, &0, Some(3)).contains_key(&4));
+    }
+
+    #[test]
+    fn loop_on_start() {
+        let mut graph = BTreeMap::new();
+        add_edge(&mut graph, 0, 0, 1);
+        add_edge(&mut graph, 0, 1, 2);
+
+        let mut dists = BTreeMap::new();
+        dists.insert(0, None);
+        dists.insert(1, Some((0, 2)));
+        assert_eq!(dijkstra(&graph, &0), dists);
+        assert_eq!(shortest_path(&graph, &0, &1), Some((vec![0, 1], 2)));
+    }
 }
@@ -11,6 +11,9 @@ pub struct FlowEdge<T> {
     pub sink: usize,
     pub capacity: T,
     pub flow: T,
+    /// The cost of sending one unit of flow through the edge. It is only
+    /// used by `MinCostMaxFlow`, and is zero for `DinicMaxFlow`.
+    pub cost: T,
 }
 
 pub struct FlowResultEdge<T> {
@@ -27,8 +30,33 @@ impl<T: Clone + Copy + Add + AddAssign + Sub<Output = T> + SubAssign + Ord + Neg
             sink,
             capacity,
             flow: T::default(),
+            cost: T::default(),
         }
     }
+
+    pub fn with_cost(sink: usize, capacity: T, cost: T) -> Self {
+        FlowEdge {
+            sink,
+            capacity,
+            flow: T::default(),
+            cost,
+        }
+    }
+}
+
+/// The interface shared by the max flow solvers, so they can be swapped (or
+/// compared) without changing the code that builds the network
+pub trait MaxFlow<T> {
+    fn new(source: usize, sink: usize, num_vertices: usize) -> Self
+    where
+        Self: Sized;
+
+    fn add_edge(&mut self, source: usize, sink: usize, capacity: T);
+
+    /// `infinite_flow` should be greater than any capacity
+    fn find_maxflow(&mut self, infinite_flow: T) -> T;
+
+    fn get_flow_edges(&mut self, infinite_flow: T) -> Vec<FlowResultEdge<T>>;
 }
 
 pub struct DinicMaxFlow<T> {
@@ -173,6 +201,67 @@ impl<T: Clone + Copy + Add + AddAssign + Sub<Output = T> + SubAssign + Neg + Ord
         }
         result
     }
+
+    /// Returns a minimum s-t cut: the vertices that are still reachable from
+    /// the source in the residual network, and the edges going from them to
+    /// the other vertices. Those edges are saturated, so the sum of their
+    /// flows is the max flow.
+    pub fn find_min_cut(&mut self, infinite_flow: T) -> (Vec<usize>, Vec<FlowResultEdge<T>>) {
+        if !self.network_solved {
+            self.find_maxflow(infinite_flow);
+        }
+        let mut reachable = vec![false; self.adj.len()];
+        let mut q: VecDeque<usize> = VecDeque::new();
+        reachable[self.source] = true;
+        q.push_back(self.source);
+        while let Some(v) = q.pop_front() {
+            for &e in self.adj[v].iter() {
+                let u = self.edges[e].sink;
+                if self.edges[e].capacity > self.edges[e].flow && !reachable[u] {
+                    reachable[u] = true;
+                    q.push_back(u);
+                }
+            }
+        }
+
+        let source_side: Vec<usize> = (1..self.adj.len()).filter(|&v| reachable[v]).collect();
+        let mut cut_edges = Vec::new();
+        for &v in source_side.iter() {
+            for &e_ind in self.adj[v].iter() {
+                let e = &self.edges[e_ind];
+                // Edges with an odd index are the reverse edges of the
+                // residual network
+                if e_ind % 2 == 0 && !reachable[e.sink] {
+                    cut_edges.push(FlowResultEdge {
+                        source: v,
+                        sink: e.sink,
+                        flow: e.flow,
+                    });
+                }
+            }
+        }
+        (source_side, cut_edges)
+    }
+}
+
+impl<T: Clone + Copy + Add + AddAssign + Sub<Output = T> + SubAssign + Neg + Ord + Default>
+    MaxFlow<T> for DinicMaxFlow<T>
+{
+    fn new(source: usize, sink: usize, num_vertices: usize) -> Self {
+        DinicMaxFlow::new(source, sink, num_vertices)
+    }
+
+    fn add_edge(&mut self, source: usize, sink: usize, capacity: T) {
+        DinicMaxFlow::add_edge(self, source, sink, capacity)
+    }
+
+    fn find_maxflow(&mut self, infinite_flow: T) -> T {
+        DinicMaxFlow::find_maxflow(self, infinite_flow)
+    }
+
+    fn get_flow_edges(&mut self, infinite_flow: T) -> Vec<FlowResultEdge<T>> {
+        DinicMaxFlow::get_flow_edges(self, infinite_flow)
+    }
 }
 
 #[cfg(test)]
@@ -210,4 +299,38 @@ mod tests {
         assert_eq!(sm_in[6], max_flow);
         assert_eq!(sm_out[6], 0);
     }
+
+    #[test]
+    fn min_cut() {
+        let mut flow: DinicMaxFlow<i32> = DinicMaxFlow::new(1, 6, 6);
+        flow.add_edge(1, 2, 16);
+        flow.add_edge(1, 4, 13);
+        flow.add_edge(2, 3, 12);
+        flow.add_edge(3, 4, 9);
+        flow.add_edge(3, 6, 20);
+        flow.add_edge(4, 2, 4);
+        flow.add_edge(4, 5, 14);
+        flow.add_edge(5, 3, 7);
+        flow.add_edge(5, 6, 4);
+
+        let (source_side, cut_edges) = flow.find_min_cut(i32::MAX);
+        assert_eq!(source_side, vec![1, 2, 4, 5]);
+        let mut cut: Vec<(usize, usize, i32)> = cut_edges
+            .iter()
+            .map(|e| (e.source, e.sink, e.flow))
+            .collect();
+        cut.sort();
+        assert_eq!(cut, vec![(2, 3, 12), (5, 3, 7), (5, 6, 4)]);
+        assert_eq!(cut.iter().map(|e| e.2).sum::<i32>(), 23);
+    }
+
+    #[test]
+    fn min_cut_of_disconnected_network() {
+        let mut flow: DinicMaxFlow<i32> = DinicMaxFlow::new(1, 4, 4);
+        flow.add_edge(1, 2, 5);
+        flow.add_edge(3, 4, 5);
+        let (source_side, cut_edges) = flow.find_min_cut(i32::MAX);
+        assert_eq!(source_side, vec![1, 2]);
+        assert!(cut_edges.is_empty());
+    }
 }
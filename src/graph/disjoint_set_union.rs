@@ -1,30 +1,97 @@
+/*
+Disjoint set union with union by rank, that can optionally undo its merges.
+
+By default `find_set` also compresses paths, so both operations take
+O(α(n)) amortized. A DSU made with `with_rollback` doesn't compress paths,
+which would change many parents in a single `find_set`: union by rank alone
+keeps the trees of height O(lg(n)), and each merge changes a single parent
+(and maybe a rank), which is easy to undo. `find_set` is then O(lg(n)), and
+rolling back is O(1) per merge undone.
+
+Rollback is what offline dynamic connectivity and divide and conquer over
+time need: merge, answer queries, then go back to an earlier state. To query
+any past version, see `PersistentDisjointSetUnion`. `data_structures::UnionFind`
+is a thin wrapper over this one.
+*/
+
 pub struct DSUNode {
     parent: usize,
     size: usize,
+    rank: usize,
+}
+
+// What a successful merge changed
+struct Change {
+    // The root that was attached to the other one
+    child: usize,
+    parent: usize,
+    rank_increased: bool,
 }
 
 pub struct DisjointSetUnion {
     nodes: Vec<DSUNode>,
+    num_sets: usize,
+    // The successful merges, if they can be rolled back
+    history: Option<Vec<Change>>,
 }
 
-// We are using both path compression and union by size
 impl DisjointSetUnion {
     // Create n+1 sets [0, n]
     pub fn new(n: usize) -> DisjointSetUnion {
         let mut nodes = Vec::new();
         nodes.reserve_exact(n + 1);
         for i in 0..=n {
-            nodes.push(DSUNode { parent: i, size: 1 });
+            nodes.push(DSUNode {
+                parent: i,
+                size: 1,
+                rank: 0,
+            });
+        }
+        DisjointSetUnion {
+            nodes,
+            num_sets: n + 1,
+            history: None,
         }
-        DisjointSetUnion { nodes }
     }
+
+    // Create n+1 sets [0, n], whose merges can be undone with `rollback` and
+    // `rollback_to`
+    pub fn with_rollback(n: usize) -> DisjointSetUnion {
+        DisjointSetUnion {
+            history: Some(vec![]),
+            ..DisjointSetUnion::new(n)
+        }
+    }
+
     pub fn find_set(&mut self, v: usize) -> usize {
         if v == self.nodes[v].parent {
             return v;
         }
+        if self.history.is_some() {
+            let mut v = v;
+            while self.nodes[v].parent != v {
+                v = self.nodes[v].parent;
+            }
+            return v;
+        }
         self.nodes[v].parent = self.find_set(self.nodes[v].parent);
         self.nodes[v].parent
     }
+
+    pub fn same_set(&mut self, u: usize, v: usize) -> bool {
+        self.find_set(u) == self.find_set(v)
+    }
+
+    // The number of elements in the set of v
+    pub fn set_size(&mut self, v: usize) -> usize {
+        let root = self.find_set(v);
+        self.nodes[root].size
+    }
+
+    pub fn num_sets(&self) -> usize {
+        self.num_sets
+    }
+
     // Returns the new component of the merged sets,
     // or std::usize::MAX if they were the same.
     pub fn merge(&mut self, u: usize, v: usize) -> usize {
@@ -33,18 +100,71 @@ impl DisjointSetUnion {
         if a == b {
             return std::usize::MAX;
         }
-        if self.nodes[a].size < self.nodes[b].size {
+        if self.nodes[a].rank < self.nodes[b].rank {
             std::mem::swap(&mut a, &mut b);
         }
+        let rank_increased = self.nodes[a].rank == self.nodes[b].rank;
         self.nodes[b].parent = a;
         self.nodes[a].size += self.nodes[b].size;
+        if rank_increased {
+            self.nodes[a].rank += 1;
+        }
+        self.num_sets -= 1;
+        if let Some(history) = self.history.as_mut() {
+            history.push(Change {
+                child: b,
+                parent: a,
+                rank_increased,
+            });
+        }
         a
     }
+
+    fn history(&mut self) -> &mut Vec<Change> {
+        self.history
+            .as_mut()
+            .expect("only a DisjointSetUnion made with `with_rollback` can roll back")
+    }
+
+    // A point the structure can be rolled back to (the number of successful
+    // merges so far)
+    pub fn snapshot(&mut self) -> usize {
+        self.history().len()
+    }
+
+    // Undoes the last successful merge, returns false if there was none
+    pub fn rollback(&mut self) -> bool {
+        match self.history().pop() {
+            None => false,
+            Some(Change {
+                child,
+                parent,
+                rank_increased,
+            }) => {
+                self.nodes[child].parent = child;
+                self.nodes[parent].size -= self.nodes[child].size;
+                if rank_increased {
+                    self.nodes[parent].rank -= 1;
+                }
+                self.num_sets += 1;
+                true
+            }
+        }
+    }
+
+    // Undoes every merge done after `snapshot()` returned `snapshot`
+    pub fn rollback_to(&mut self, snapshot: usize) {
+        while self.history().len() > snapshot {
+            self.rollback();
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::math::PCG32;
+
     #[test]
     fn create_acyclic_graph() {
         let mut dsu = DisjointSetUnion::new(10);
@@ -91,5 +211,83 @@ mod tests {
             assert_eq!(comp_1, dsu.find_set(i));
         }
         assert_ne!(comp_1, dsu.find_set(10));
+        assert_eq!(dsu.set_size(5), 9);
+        assert_eq!(dsu.num_sets(), 3);
+    }
+
+    #[test]
+    fn merge_and_rollback() {
+        let mut dsu = DisjointSetUnion::with_rollback(6);
+        assert_eq!(dsu.num_sets(), 7);
+        assert_ne!(dsu.merge(1, 2), usize::MAX);
+        assert_ne!(dsu.merge(3, 4), usize::MAX);
+        let before = dsu.snapshot();
+        assert_ne!(dsu.merge(2, 3), usize::MAX);
+        assert_eq!(dsu.merge(1, 4), usize::MAX);
+        assert_ne!(dsu.merge(5, 6), usize::MAX);
+        assert!(dsu.same_set(1, 4));
+        assert_eq!(dsu.set_size(4), 4);
+        assert_eq!(dsu.num_sets(), 3);
+
+        assert!(dsu.rollback());
+        assert!(!dsu.same_set(5, 6));
+        dsu.rollback_to(before);
+        assert!(!dsu.same_set(1, 4));
+        assert!(dsu.same_set(1, 2));
+        assert!(dsu.same_set(3, 4));
+        assert_eq!(dsu.set_size(1), 2);
+        assert_eq!(dsu.num_sets(), 5);
+
+        dsu.rollback_to(0);
+        assert_eq!(dsu.num_sets(), 7);
+        assert!(!dsu.rollback());
+    }
+
+    #[test]
+    #[should_panic(expected = "with_rollback")]
+    fn rollback_needs_history() {
+        let mut dsu = DisjointSetUnion::new(3);
+        dsu.merge(1, 2);
+        dsu.rollback();
+    }
+
+    #[test]
+    fn same_as_rebuilding() {
+        // after random merges and rollbacks, the sets are the same as with a
+        // DSU built from the merges that are left
+        let mut rng = PCG32::new_default(12);
+        let n = 30;
+        let mut dsu = DisjointSetUnion::with_rollback(n);
+        let mut merges: Vec<(usize, usize)> = vec![];
+        let mut snapshots: Vec<(usize, usize)> = vec![];
+        for _ in 0..500 {
+            match rng.get_u32() % 5 {
+                0 => snapshots.push((dsu.snapshot(), merges.len())),
+                1 => {
+                    if let Some((snapshot, len)) = snapshots.pop() {
+                        dsu.rollback_to(snapshot);
+                        merges.truncate(len);
+                    }
+                }
+                _ => {
+                    let u = rng.get_u32() as usize % (n + 1);
+                    let v = rng.get_u32() as usize % (n + 1);
+                    if dsu.merge(u, v) != usize::MAX {
+                        merges.push((u, v));
+                    }
+                }
+            }
+            let mut fresh = DisjointSetUnion::new(n);
+            for &(u, v) in merges.iter() {
+                assert_ne!(fresh.merge(u, v), usize::MAX);
+            }
+            assert_eq!(fresh.num_sets(), dsu.num_sets());
+            for u in 0..=n {
+                for v in 0..=n {
+                    assert_eq!(fresh.same_set(u, v), dsu.same_set(u, v));
+                    assert_eq!(fresh.set_size(u), dsu.set_size(u));
+                }
+            }
+        }
     }
 }
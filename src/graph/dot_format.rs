@@ -0,0 +1,572 @@
+/*
+Reading and writing graphs in the Graphviz DOT language.
+
+The parser understands the part of the language used to describe a graph's
+structure: `graph` and `digraph` (optionally `strict`, which is ignored),
+node statements, edge statements with chains like `a -> b -> c`, attribute
+lists, `edge [...]` defaults and `key = value` statements. The weight of an
+edge is its `weight` attribute; other attributes are ignored. Identifiers,
+numerals and quoted strings can all be vertex names. Comments (`//`,
+`/* */` and lines starting with `#`) are skipped. Subgraphs, ports and HTML
+strings are not supported.
+*/
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fmt::Write;
+use std::str::FromStr;
+
+use super::{AdjacencyList, Graph, ParseError};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    // An identifier, numeral or quoted string (`quoted` tells which, as only
+    // unquoted keywords are keywords)
+    Id { text: String, quoted: bool },
+    Symbol(&'static str),
+}
+
+struct Lexer {
+    chars: Vec<char>,
+    position: usize,
+    line: usize,
+    column: usize,
+    // Only used to tell `#` lines apart
+    at_line_start: bool,
+}
+
+const SYMBOLS: [&str; 10] = ["->", "--", "{", "}", "[", "]", "=", ";", ",", ":"];
+
+impl Lexer {
+    fn new(text: &str) -> Self {
+        Lexer {
+            chars: text.chars().collect(),
+            position: 0,
+            line: 1,
+            column: 1,
+            at_line_start: true,
+        }
+    }
+
+    fn peek(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.position + offset).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek(0)?;
+        self.position += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+            self.at_line_start = true;
+        } else {
+            self.column += 1;
+            if !c.is_whitespace() {
+                self.at_line_start = false;
+            }
+        }
+        Some(c)
+    }
+
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError::new(self.line, self.column, message)
+    }
+
+    fn skip_whitespace_and_comments(&mut self) -> Result<(), ParseError> {
+        loop {
+            match (self.peek(0), self.peek(1)) {
+                (Some(c), _) if c.is_whitespace() => {
+                    self.bump();
+                }
+                (Some('#'), _) if self.at_line_start => {
+                    while !matches!(self.peek(0), None | Some('\n')) {
+                        self.bump();
+                    }
+                }
+                (Some('/'), Some('/')) => {
+                    while !matches!(self.peek(0), None | Some('\n')) {
+                        self.bump();
+                    }
+                }
+                (Some('/'), Some('*')) => {
+                    let start = self.error("unterminated comment");
+                    self.bump();
+                    self.bump();
+                    while (self.peek(0), self.peek(1)) != (Some('*'), Some('/')) {
+                        if self.bump().is_none() {
+                            return Err(start);
+                        }
+                    }
+                    self.bump();
+                    self.bump();
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    // The next token with the line and column where it starts, or None at
+    // the end of the text
+    fn next_token(&mut self) -> Result<Option<(Token, usize, usize)>, ParseError> {
+        self.skip_whitespace_and_comments()?;
+        let (line, column) = (self.line, self.column);
+        let c = match self.peek(0) {
+            None => return Ok(None),
+            Some(c) => c,
+        };
+        for symbol in SYMBOLS {
+            if symbol
+                .chars()
+                .enumerate()
+                .all(|(i, e)| self.peek(i) == Some(e))
+            {
+                for _ in 0..symbol.len() {
+                    self.bump();
+                }
+                return Ok(Some((Token::Symbol(symbol), line, column)));
+            }
+        }
+        let token = if c == '"' {
+            let start = self.error("unterminated string");
+            self.bump();
+            let mut text = String::new();
+            loop {
+                match self.bump() {
+                    None => return Err(start),
+                    Some('"') => break,
+                    Some('\\') if self.peek(0) == Some('"') => {
+                        self.bump();
+                        text.push('"');
+                    }
+                    Some('\\') if self.peek(0) == Some('\\') => {
+                        self.bump();
+                        text.push('\\');
+                    }
+                    // a backslash at the end of a line continues the string
+                    Some('\\') if self.peek(0) == Some('\n') => {
+                        self.bump();
+                    }
+                    Some(c) => text.push(c),
+                }
+            }
+            Token::Id { text, quoted: true }
+        } else if c.is_alphanumeric() || c == '_' || c == '.' || c == '-' {
+            let mut text = String::new();
+            while let Some(c) = self.peek(0) {
+                if !(c.is_alphanumeric() || c == '_' || c == '.' || c == '-') {
+                    break;
+                }
+                // `a->b` and `a--b` are an edge, not a name
+                if c == '-' && matches!(self.peek(1), Some('-') | Some('>')) {
+                    break;
+                }
+                text.push(c);
+                self.bump();
+            }
+            if !is_id(&text) {
+                return Err(ParseError::new(
+                    line,
+                    column,
+                    format!("invalid identifier `{text}`"),
+                ));
+            }
+            Token::Id {
+                text,
+                quoted: false,
+            }
+        } else if c == '<' {
+            return Err(self.error("HTML strings are not supported"));
+        } else {
+            return Err(self.error(format!("unexpected character `{c}`")));
+        };
+        Ok(Some((token, line, column)))
+    }
+}
+
+// Whether `text` can be written as it is: an identifier (not starting with
+// a digit) or a numeral
+fn is_id(text: &str) -> bool {
+    let identifier = text
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_')
+        && text.chars().all(|c| c.is_alphanumeric() || c == '_');
+    let digits = text.strip_prefix('-').unwrap_or(text);
+    let numeral = digits.chars().any(|c| c.is_ascii_digit())
+        && digits.chars().all(|c| c.is_ascii_digit() || c == '.')
+        && digits.matches('.').count() <= 1;
+    identifier || numeral
+}
+
+// A graph read from DOT, with its vertices numbered from 0 to n - 1 in the
+// order they first appear
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DotGraph<E> {
+    pub directed: bool,
+    // The name of each vertex: vertex v of `graph` is `names[v]`
+    pub names: Vec<String>,
+    pub graph: AdjacencyList<usize, E>,
+}
+
+struct Parser {
+    lexer: Lexer,
+    next: Option<(Token, usize, usize)>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.next.as_ref().map(|(token, _, _)| token)
+    }
+
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        match &self.next {
+            Some((_, line, column)) => ParseError::new(*line, *column, message),
+            None => self.lexer.error(message),
+        }
+    }
+
+    fn advance(&mut self) -> Result<Option<Token>, ParseError> {
+        let next = self.lexer.next_token()?;
+        Ok(std::mem::replace(&mut self.next, next).map(|(token, _, _)| token))
+    }
+
+    fn is_symbol(&self, symbol: &str) -> bool {
+        matches!(self.peek(), Some(Token::Symbol(s)) if *s == symbol)
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Id { text, quoted: false }) if text.eq_ignore_ascii_case(keyword))
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<(), ParseError> {
+        if !self.is_symbol(symbol) {
+            return Err(self.error(format!("expected `{symbol}`")));
+        }
+        self.advance()?;
+        Ok(())
+    }
+
+    fn expect_id(&mut self, what: &str) -> Result<String, ParseError> {
+        match self.peek() {
+            Some(Token::Id { .. }) => match self.advance()? {
+                Some(Token::Id { text, .. }) => Ok(text),
+                _ => unreachable!(),
+            },
+            _ => Err(self.error(format!("expected {what}"))),
+        }
+    }
+
+    // Any number of `[key = value, ...]` lists, as (key, value, line, column
+    // of the value) tuples
+    fn attributes(&mut self) -> Result<Vec<(String, String, usize, usize)>, ParseError> {
+        let mut attributes = vec![];
+        while self.is_symbol("[") {
+            self.advance()?;
+            while !self.is_symbol("]") {
+                let key = self.expect_id("an attribute name or `]`")?;
+                self.expect_symbol("=")?;
+                let (line, column) = match &self.next {
+                    Some((_, line, column)) => (*line, *column),
+                    None => (self.lexer.line, self.lexer.column),
+                };
+                let value = self.expect_id("an attribute value")?;
+                attributes.push((key, value, line, column));
+                if self.is_symbol(",") || self.is_symbol(";") {
+                    self.advance()?;
+                }
+            }
+            self.advance()?;
+        }
+        Ok(attributes)
+    }
+}
+
+// The weight attribute in `attributes`, if there is one
+fn weight<E: FromStr>(
+    attributes: &[(String, String, usize, usize)],
+) -> Result<Option<E>, ParseError> {
+    let mut weight = None;
+    for (key, value, line, column) in attributes {
+        if key == "weight" {
+            let parsed = value.parse().map_err(|_| {
+                ParseError::new(*line, *column, format!("invalid weight `{value}`"))
+            })?;
+            weight = Some(parsed);
+        }
+    }
+    Ok(weight)
+}
+
+// Edges without a weight attribute (nor a default one from `edge [...]`)
+// get `E::default()`. In an undirected graph, edges are added in both
+// directions, except self-loops which are added once.
+pub fn parse_dot<E>(text: &str) -> Result<DotGraph<E>, ParseError>
+where
+    E: FromStr + Copy + Default,
+{
+    let mut parser = Parser {
+        lexer: Lexer::new(text),
+        next: None,
+    };
+    parser.advance()?;
+
+    if parser.is_keyword("strict") {
+        parser.advance()?;
+    }
+    let directed = if parser.is_keyword("digraph") {
+        true
+    } else if parser.is_keyword("graph") {
+        false
+    } else {
+        return Err(parser.error("expected `graph` or `digraph`"));
+    };
+    parser.advance()?;
+    if let Some(Token::Id { .. }) = parser.peek() {
+        parser.advance()?;
+    }
+    parser.expect_symbol("{")?;
+
+    let mut names: Vec<String> = vec![];
+    let mut index: BTreeMap<String, usize> = BTreeMap::new();
+    let mut graph: AdjacencyList<usize, E> = BTreeMap::new();
+    let mut vertex = |name: String, graph: &mut AdjacencyList<usize, E>| -> usize {
+        *index.entry(name.clone()).or_insert_with(|| {
+            names.push(name);
+            graph.insert(names.len() - 1, vec![]);
+            names.len() - 1
+        })
+    };
+    let mut default_weight = E::default();
+    let edge_symbol = if directed { "->" } else { "--" };
+
+    while !parser.is_symbol("}") {
+        if parser.peek().is_none() {
+            return Err(parser.error("expected `}`"));
+        }
+        if parser.is_keyword("subgraph") || parser.is_symbol("{") {
+            return Err(parser.error("subgraphs are not supported"));
+        }
+        if parser.is_keyword("graph") || parser.is_keyword("node") || parser.is_keyword("edge") {
+            let is_edge = parser.is_keyword("edge");
+            parser.advance()?;
+            let attributes = parser.attributes()?;
+            if is_edge {
+                if let Some(w) = weight(&attributes)? {
+                    default_weight = w;
+                }
+            }
+        } else {
+            let name = parser.expect_id("a statement")?;
+            if parser.is_symbol("=") {
+                // a graph attribute
+                parser.advance()?;
+                parser.expect_id("an attribute value")?;
+            } else {
+                let mut chain = vec![vertex(name, &mut graph)];
+                while parser.is_symbol("->") || parser.is_symbol("--") {
+                    if !parser.is_symbol(edge_symbol) {
+                        return Err(parser.error(format!(
+                            "expected `{edge_symbol}` in a {}",
+                            if directed { "digraph" } else { "graph" }
+                        )));
+                    }
+                    parser.advance()?;
+                    chain.push(vertex(parser.expect_id("a vertex")?, &mut graph));
+                }
+                if parser.is_symbol(":") {
+                    return Err(parser.error("ports are not supported"));
+                }
+                let attributes = parser.attributes()?;
+                let w = weight(&attributes)?.unwrap_or(default_weight);
+                for pair in chain.windows(2) {
+                    graph.get_mut(&pair[0]).unwrap().push((pair[1], w));
+                    if !directed && pair[0] != pair[1] {
+                        graph.get_mut(&pair[1]).unwrap().push((pair[0], w));
+                    }
+                }
+            }
+        }
+        if parser.is_symbol(";") || parser.is_symbol(",") {
+            parser.advance()?;
+        }
+    }
+    parser.advance()?;
+    if parser.peek().is_some() {
+        return Err(parser.error("unexpected text after the graph"));
+    }
+
+    Ok(DotGraph {
+        directed,
+        names,
+        graph,
+    })
+}
+
+const KEYWORDS: [&str; 6] = ["strict", "graph", "digraph", "node", "edge", "subgraph"];
+
+// The name of a vertex, quoted if it has to be
+fn dot_id(name: &str) -> String {
+    let keyword = KEYWORDS.iter().any(|k| k.eq_ignore_ascii_case(name));
+    if is_id(name) && !keyword {
+        name.to_string()
+    } else {
+        // backslashes first, so that the ones added before quotes stay single
+        format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+// Every vertex is written, so isolated ones are kept. If `directed` is false,
+// the graph should have its edges in both directions (and its self-loops
+// once, like `parse_dot` reads them), and each of them is written once.
+pub fn write_dot<G: Graph>(graph: &G, directed: bool) -> String
+where
+    G::Vertex: fmt::Display,
+    G::Weight: fmt::Display,
+{
+    let mut text = String::from(if directed { "digraph {\n" } else { "graph {\n" });
+    for v in graph.vertices() {
+        writeln!(text, "    {};", dot_id(&v.to_string())).unwrap();
+    }
+    let edge_symbol = if directed { "->" } else { "--" };
+    for (u, v, w) in graph.edges() {
+        if directed || u <= v {
+            writeln!(
+                text,
+                "    {} {edge_symbol} {} [weight={}];",
+                dot_id(&u.to_string()),
+                dot_id(&v.to_string()),
+                dot_id(&w.to_string())
+            )
+            .unwrap();
+        }
+    }
+    text.push_str("}\n");
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{dijkstra, AdjacencyMap};
+
+    #[test]
+    fn read_digraph() {
+        let text = r#"
+            # generated by hand
+            strict digraph "roads" {
+                node [shape=circle];
+                edge [weight=10, color=red]
+                rankdir = LR;
+                a -> b -> "c d" [weight=3];
+                a->"c d"  // uses the default weight
+                /* an isolated vertex */
+                e;
+                -1.5 -> a [label="minus one and a half", weight=1]
+            }
+        "#;
+        let dot: DotGraph<i32> = parse_dot(text).unwrap();
+        assert!(dot.directed);
+        assert_eq!(dot.names, vec!["a", "b", "c d", "e", "-1.5"]);
+        assert_eq!(dot.graph[&0], vec![(1, 3), (2, 10)]);
+        assert_eq!(dot.graph[&1], vec![(2, 3)]);
+        assert_eq!(dot.graph[&3], vec![]);
+        assert_eq!(dot.graph[&4], vec![(0, 1)]);
+
+        let distances = dijkstra(&dot.graph, &4);
+        assert_eq!(distances[&2], Some((1, 7)));
+    }
+
+    #[test]
+    fn read_undirected_graph() {
+        let dot: DotGraph<u32> = parse_dot("graph { 1 -- 2 -- 3; 3 -- 1 }").unwrap();
+        assert!(!dot.directed);
+        assert_eq!(dot.graph[&0], vec![(1, 0), (2, 0)]);
+        assert_eq!(dot.graph[&2], vec![(1, 0), (0, 0)]);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            parse_dot::<i32>("digraph {\n  a -- b\n}"),
+            Err(ParseError::new(2, 5, "expected `->` in a digraph"))
+        );
+        assert_eq!(
+            parse_dot::<i32>("digraph {\n  a -> b [weight=x]\n}"),
+            Err(ParseError::new(2, 18, "invalid weight `x`"))
+        );
+        assert_eq!(
+            parse_dot::<i32>("graph { a -- b "),
+            Err(ParseError::new(1, 16, "expected `}`"))
+        );
+        assert_eq!(
+            parse_dot::<i32>("graph {\n subgraph s { a }\n}"),
+            Err(ParseError::new(2, 2, "subgraphs are not supported"))
+        );
+        assert_eq!(
+            parse_dot::<i32>("tree { }"),
+            Err(ParseError::new(1, 1, "expected `graph` or `digraph`"))
+        );
+        assert_eq!(
+            parse_dot::<i32>("graph { a -- \"b }"),
+            Err(ParseError::new(1, 14, "unterminated string"))
+        );
+        assert_eq!(
+            parse_dot::<i32>("graph { a } b"),
+            Err(ParseError::new(1, 13, "unexpected text after the graph"))
+        );
+    }
+
+    #[test]
+    fn write_and_read_back() {
+        let mut graph: AdjacencyMap<&str, i32> = BTreeMap::new();
+        graph.insert("a", BTreeMap::from([("b", 2), ("my vertex", -4)]));
+        graph.insert("b", BTreeMap::from([("a", 2)]));
+        graph.insert("my vertex", BTreeMap::from([("a", -4)]));
+        graph.insert("alone", BTreeMap::new());
+
+        let text = write_dot(&graph, false);
+        assert_eq!(
+            text,
+            "graph {\n    a;\n    alone;\n    b;\n    \"my vertex\";\n    \
+             a -- b [weight=2];\n    a -- \"my vertex\" [weight=-4];\n}\n"
+        );
+        let dot: DotGraph<i32> = parse_dot(&text).unwrap();
+        assert_eq!(dot.names, vec!["a", "alone", "b", "my vertex"]);
+        assert_eq!(dot.graph[&0], vec![(2, 2), (3, -4)]);
+        assert_eq!(dot.graph[&3], vec![(0, -4)]);
+        assert_eq!(dot.graph[&1], vec![]);
+
+        let directed = parse_dot::<i32>(&write_dot(&dot.graph, true)).unwrap();
+        assert_eq!(directed.graph, dot.graph);
+    }
+
+    #[test]
+    fn backslashes_and_quotes() {
+        let names = ["a\\", "\\\"", "say \"hi\"", "c:\\dir\\file"];
+        let graph: AdjacencyMap<&str, i32> = names
+            .iter()
+            .map(|&name| (name, BTreeMap::from([(names[0], 1)])))
+            .collect();
+        let text = write_dot(&graph, true);
+        assert!(text.contains("    \"a\\\\\";\n"));
+        let dot: DotGraph<i32> = parse_dot(&text).unwrap();
+        let mut sorted = names.to_vec();
+        sorted.sort_unstable();
+        assert_eq!(dot.names, sorted);
+        assert_eq!(dot.graph.len(), names.len());
+    }
+
+    #[test]
+    fn self_loops() {
+        let mut graph: AdjacencyMap<u32, i32> = BTreeMap::new();
+        graph.insert(1, BTreeMap::from([(1, 5), (2, 3)]));
+        graph.insert(2, BTreeMap::from([(1, 3)]));
+        for directed in [false, true] {
+            let dot: DotGraph<i32> = parse_dot(&write_dot(&graph, directed)).unwrap();
+            assert_eq!(dot.graph[&0], vec![(0, 5), (1, 3)]);
+            assert_eq!(dot.graph[&1], vec![(0, 3)]);
+        }
+        let dot: DotGraph<i32> = parse_dot("graph { a -- a -- b }").unwrap();
+        assert_eq!(dot.graph[&0], vec![(0, 0), (1, 0)]);
+    }
+}
@@ -0,0 +1,238 @@
+/*
+Offline dynamic connectivity: edges of an undirected graph are added and
+removed over time, and we want to know at some moments whether two vertices
+are connected, or how many connected components there are.
+
+Knowing all the events in advance, each edge is alive during an interval of
+time. The intervals are put in a segment tree over time (each one in
+O(lg(q)) nodes), and a DFS of that tree merges the edges of a node when
+entering it and rolls them back when leaving it, with a
+`DisjointSetUnion::with_rollback`. At a leaf, exactly the edges alive at that time
+have been merged. It takes O(q.lg(q).lg(n)) for q events.
+
+Vertices are numbered from 1 to n.
+*/
+
+use std::collections::BTreeMap;
+
+use super::DisjointSetUnion;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectivityEvent {
+    AddEdge(usize, usize),
+    // Removes one copy of the edge (it can have been added several times).
+    // Removing an edge that isn't there does nothing.
+    RemoveEdge(usize, usize),
+    // Are the two vertices connected?
+    AreConnected(usize, usize),
+    // How many connected components are there?
+    CountComponents,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectivityAnswer {
+    Connected(bool),
+    Components(usize),
+}
+
+struct TimeSegmentTree {
+    // The edges alive during the whole range of each node
+    edges: Vec<Vec<(usize, usize)>>,
+}
+
+impl TimeSegmentTree {
+    fn add(
+        &mut self,
+        node: usize,
+        start: usize,
+        end: usize,
+        (l, r): (usize, usize),
+        edge: (usize, usize),
+    ) {
+        if r < start || end < l {
+            return;
+        }
+        if l <= start && end <= r {
+            self.edges[node].push(edge);
+            return;
+        }
+        let mid = (start + end) / 2;
+        self.add(2 * node, start, mid, (l, r), edge);
+        self.add(2 * node + 1, mid + 1, end, (l, r), edge);
+    }
+
+    fn solve(
+        &self,
+        node: usize,
+        start: usize,
+        end: usize,
+        dsu: &mut DisjointSetUnion,
+        events: &[ConnectivityEvent],
+        answers: &mut Vec<ConnectivityAnswer>,
+    ) {
+        let snapshot = dsu.snapshot();
+        for &(u, v) in self.edges[node].iter() {
+            dsu.merge(u, v);
+        }
+        if start == end {
+            match events[start] {
+                ConnectivityEvent::AreConnected(u, v) => {
+                    answers.push(ConnectivityAnswer::Connected(dsu.same_set(u, v)))
+                }
+                // Vertex 0 is not used, it is a set on its own
+                ConnectivityEvent::CountComponents => {
+                    answers.push(ConnectivityAnswer::Components(dsu.num_sets() - 1))
+                }
+                _ => {}
+            }
+        } else {
+            let mid = (start + end) / 2;
+            self.solve(2 * node, start, mid, dsu, events, answers);
+            self.solve(2 * node + 1, mid + 1, end, dsu, events, answers);
+        }
+        dsu.rollback_to(snapshot);
+    }
+}
+
+// Returns the answers to the `AreConnected` and `CountComponents` events, in
+// the order they appear in `events`
+pub fn offline_dynamic_connectivity(
+    num_vertices: usize,
+    events: &[ConnectivityEvent],
+) -> Vec<ConnectivityAnswer> {
+    let mut answers = vec![];
+    if events.is_empty() {
+        return answers;
+    }
+    let last = events.len() - 1;
+    let mut tree = TimeSegmentTree {
+        edges: vec![vec![]; 4 * events.len()],
+    };
+
+    // The times at which each edge was added and is still there
+    let mut alive: BTreeMap<(usize, usize), Vec<usize>> = BTreeMap::new();
+    for (time, &event) in events.iter().enumerate() {
+        match event {
+            ConnectivityEvent::AddEdge(u, v) => {
+                alive.entry((u.min(v), u.max(v))).or_default().push(time);
+            }
+            ConnectivityEvent::RemoveEdge(u, v) => {
+                let edge = (u.min(v), u.max(v));
+                if let Some(added) = alive.get_mut(&edge).and_then(|times| times.pop()) {
+                    tree.add(1, 0, last, (added, time), edge);
+                }
+            }
+            _ => {}
+        }
+    }
+    // The edges that are never removed
+    for (edge, times) in alive {
+        for added in times {
+            tree.add(1, 0, last, (added, last), edge);
+        }
+    }
+
+    let mut dsu = DisjointSetUnion::with_rollback(num_vertices);
+    tree.solve(1, 0, last, &mut dsu, events, &mut answers);
+    answers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ConnectivityAnswer::*;
+    use super::ConnectivityEvent::*;
+    use super::*;
+    use crate::math::PCG32;
+
+    #[test]
+    fn small_timeline() {
+        let events = vec![
+            CountComponents,
+            AddEdge(1, 2),
+            AddEdge(2, 3),
+            AreConnected(1, 3),
+            CountComponents,
+            RemoveEdge(2, 1),
+            AreConnected(1, 3),
+            AreConnected(2, 3),
+            AddEdge(1, 4),
+            AddEdge(4, 3),
+            AreConnected(1, 2),
+            CountComponents,
+            // a removed edge that was never there
+            RemoveEdge(1, 3),
+            CountComponents,
+        ];
+        assert_eq!(
+            offline_dynamic_connectivity(4, &events),
+            vec![
+                Components(4),
+                Connected(true),
+                Components(2),
+                Connected(false),
+                Connected(true),
+                Connected(true),
+                Components(1),
+                Components(1),
+            ]
+        );
+    }
+
+    #[test]
+    fn parallel_edges() {
+        let events = vec![
+            AddEdge(1, 2),
+            AddEdge(2, 1),
+            RemoveEdge(1, 2),
+            AreConnected(1, 2),
+            RemoveEdge(1, 2),
+            AreConnected(1, 2),
+        ];
+        assert_eq!(
+            offline_dynamic_connectivity(2, &events),
+            vec![Connected(true), Connected(false)]
+        );
+        assert_eq!(offline_dynamic_connectivity(2, &[]), vec![]);
+    }
+
+    #[test]
+    fn same_as_rebuilding() {
+        // answer each query with a DSU built from the edges alive at its time
+        let mut rng = PCG32::new_default(404);
+        let n = 12;
+        let mut events = vec![];
+        let mut edges: Vec<(usize, usize)> = vec![];
+        let mut expected = vec![];
+        for _ in 0..400 {
+            let u = rng.get_u32() as usize % n + 1;
+            let v = rng.get_u32() as usize % n + 1;
+            match rng.get_u32() % 5 {
+                0 | 1 => {
+                    events.push(AddEdge(u, v));
+                    edges.push((u, v));
+                }
+                2 => {
+                    if !edges.is_empty() {
+                        let (a, b) = edges.swap_remove(rng.get_u32() as usize % edges.len());
+                        events.push(RemoveEdge(b, a));
+                    }
+                }
+                k => {
+                    // element 0 is not used
+                    let mut sets = DisjointSetUnion::new(n);
+                    for &(a, b) in edges.iter() {
+                        sets.merge(a, b);
+                    }
+                    if k == 3 {
+                        events.push(AreConnected(u, v));
+                        expected.push(Connected(sets.same_set(u, v)));
+                    } else {
+                        events.push(CountComponents);
+                        expected.push(Components(sets.num_sets() - 1));
+                    }
+                }
+            }
+        }
+        assert_eq!(offline_dynamic_connectivity(n, &events), expected);
+    }
+}
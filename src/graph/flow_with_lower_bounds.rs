@@ -0,0 +1,244 @@
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+
+use super::dinic_maxflow::DinicMaxFlow;
+
+// We assume that graph vertices are numbered from 1 to n.
+
+/// A network where the flow through each edge must be between a lower and an
+/// upper bound.
+///
+/// Both problems are reduced to a plain max flow, solved with `DinicMaxFlow`:
+/// the lower bound of every edge is sent in advance, which leaves some
+/// vertices with more flow coming in than going out and others with less.
+/// A new source gives the missing flow to the first ones and a new sink takes
+/// it back from the others; the bounds can be met exactly when the max flow
+/// between them saturates all those edges.
+pub struct FlowWithLowerBounds<T> {
+    pub num_vertices: usize,
+
+    /// (source, sink, lower bound, upper bound) of every edge
+    pub edges: Vec<(usize, usize, T, T)>,
+}
+
+impl<T: Clone + Copy + Add + AddAssign + Sub<Output = T> + SubAssign + Neg + Ord + Default>
+    FlowWithLowerBounds<T>
+{
+    pub fn new(num_vertices: usize) -> Self {
+        FlowWithLowerBounds {
+            num_vertices,
+            edges: vec![],
+        }
+    }
+
+    pub fn add_edge(&mut self, source: usize, sink: usize, lower: T, upper: T) {
+        self.edges.push((source, sink, lower, upper));
+    }
+
+    // Builds the reduced network, where the i-th edge is at index 2i of the
+    // flow edges, and adds `extra` edges after the demand ones. Returns the
+    // network and the flow that must leave the new source.
+    fn reduce(&self, extra: &[(usize, usize, T)]) -> (DinicMaxFlow<T>, T) {
+        let n = self.num_vertices;
+        let mut flow = DinicMaxFlow::new(n + 1, n + 2, n + 2);
+        // How much more of the lower bounds goes into each vertex than out
+        let mut balance = vec![T::default(); n + 1];
+        for &(source, sink, lower, upper) in self.edges.iter() {
+            flow.add_edge(source, sink, upper - lower);
+            balance[sink] += lower;
+            balance[source] -= lower;
+        }
+        let mut required = T::default();
+        for (v, &b) in balance.iter().enumerate().skip(1) {
+            if b > T::default() {
+                flow.add_edge(n + 1, v, b);
+                required += b;
+            } else if b < T::default() {
+                flow.add_edge(v, n + 2, T::default() - b);
+            }
+        }
+        for &(source, sink, capacity) in extra {
+            flow.add_edge(source, sink, capacity);
+        }
+        (flow, required)
+    }
+
+    fn edge_flows(&self, flow: &DinicMaxFlow<T>) -> Vec<T> {
+        let mut result = Vec::with_capacity(self.edges.len());
+        for (i, &(_, _, lower, _)) in self.edges.iter().enumerate() {
+            let mut f = lower;
+            f += flow.edges[2 * i].flow;
+            result.push(f);
+        }
+        result
+    }
+
+    /// Looks for a circulation, a flow where as much goes into every vertex
+    /// as out of it, that meets the bounds of every edge. Returns the flow
+    /// through each edge in the order they were added, or None if there is
+    /// none. `infinite_flow` should be greater than the sum of the bounds.
+    pub fn find_circulation(&self, infinite_flow: T) -> Option<Vec<T>> {
+        if self.edges.iter().any(|&(_, _, lower, upper)| lower > upper) {
+            return None;
+        }
+        let (mut flow, required) = self.reduce(&[]);
+        if flow.find_maxflow(infinite_flow) != required {
+            return None;
+        }
+        Some(self.edge_flows(&flow))
+    }
+
+    /// Finds a maximum flow from source to sink that meets the bounds of
+    /// every edge. Returns its value and the flow through each edge in the
+    /// order they were added, or None if the bounds can't be met.
+    /// `infinite_flow` should be greater than the sum of the bounds.
+    pub fn find_maxflow(
+        &self,
+        source: usize,
+        sink: usize,
+        infinite_flow: T,
+    ) -> Option<(T, Vec<T>)> {
+        if self.edges.iter().any(|&(_, _, lower, upper)| lower > upper) {
+            return None;
+        }
+        // With an edge from the sink back to the source, a flow from source
+        // to sink is a circulation
+        let (mut flow, required) = self.reduce(&[(sink, source, infinite_flow)]);
+        if flow.find_maxflow(infinite_flow) != required {
+            return None;
+        }
+        let back_edge = flow.num_edges - 2;
+        let feasible = flow.edges[back_edge].flow;
+
+        // Remove the edge back to the source, and augment the feasible flow
+        // as much as possible. The edges of the new source and sink are all
+        // saturated, so they can't be used anymore.
+        flow.edges[back_edge].capacity = T::default();
+        flow.edges[back_edge].flow = T::default();
+        flow.edges[back_edge + 1].flow = T::default();
+        flow.source = source;
+        flow.sink = sink;
+        let mut total = feasible;
+        total += flow.find_maxflow(infinite_flow);
+        Some((total, self.edge_flows(&flow)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::DinicMaxFlow;
+    use crate::math::PCG32;
+
+    // Checks the bounds of every edge, and that the flow is conserved
+    // everywhere but at the source and the sink. Returns the flow out of the
+    // source.
+    fn check_flow(
+        network: &FlowWithLowerBounds<i64>,
+        flows: &[i64],
+        terminals: Option<(usize, usize)>,
+    ) -> i64 {
+        let mut balance = vec![0; network.num_vertices + 1];
+        for (&(u, v, lower, upper), &f) in network.edges.iter().zip(flows) {
+            assert!(lower <= f && f <= upper);
+            balance[u] -= f;
+            balance[v] += f;
+        }
+        for (v, &b) in balance.iter().enumerate() {
+            if terminals.is_none_or(|(source, sink)| v != source && v != sink) {
+                assert_eq!(b, 0);
+            }
+        }
+        terminals.map_or(0, |(source, _)| -balance[source])
+    }
+
+    #[test]
+    fn circulation() {
+        // a cycle 1 -> 2 -> 3 -> 1 where 1 -> 2 needs at least 3 units, and a
+        // shortcut 1 -> 3 that can't carry more than 2
+        let mut network = FlowWithLowerBounds::new(3);
+        network.add_edge(1, 2, 3, 5);
+        network.add_edge(2, 3, 0, 4);
+        network.add_edge(3, 1, 2, 10);
+        network.add_edge(1, 3, 0, 2);
+        let flows = network.find_circulation(i64::MAX).unwrap();
+        check_flow(&network, &flows, None);
+
+        // 2 -> 3 can't take the 3 units 1 -> 2 needs to send
+        network.edges[1].3 = 2;
+        assert_eq!(network.find_circulation(i64::MAX), None);
+
+        // bounds that contradict each other
+        let mut network = FlowWithLowerBounds::new(2);
+        network.add_edge(1, 2, 3, 1);
+        network.add_edge(2, 1, 0, 5);
+        assert_eq!(network.find_circulation(i64::MAX), None);
+    }
+
+    #[test]
+    fn maxflow_with_lower_bounds() {
+        let mut network = FlowWithLowerBounds::new(4);
+        network.add_edge(1, 2, 0, 4);
+        network.add_edge(1, 3, 0, 2);
+        network.add_edge(2, 4, 1, 3);
+        network.add_edge(3, 4, 2, 5);
+        network.add_edge(2, 3, 0, 1);
+        let (value, flows) = network.find_maxflow(1, 4, i64::MAX).unwrap();
+        assert_eq!(value, 6);
+        assert_eq!(check_flow(&network, &flows, Some((1, 4))), 6);
+
+        // 3 -> 4 must carry 4 units but only 3 can get to 3
+        network.edges[3].2 = 4;
+        assert_eq!(network.find_maxflow(1, 4, i64::MAX), None);
+    }
+
+    #[test]
+    fn same_as_plain_maxflow_without_lower_bounds() {
+        let mut rng = PCG32::new_default(4242);
+        for _ in 0..30 {
+            let n = 10;
+            let mut network = FlowWithLowerBounds::new(n);
+            let mut dinic: DinicMaxFlow<i64> = DinicMaxFlow::new(1, n, n);
+            for _ in 0..30 {
+                let u = (rng.get_u32() as usize % n) + 1;
+                let v = (rng.get_u32() as usize % n) + 1;
+                let capacity = (rng.get_u32() % 20) as i64;
+                network.add_edge(u, v, 0, capacity);
+                dinic.add_edge(u, v, capacity);
+            }
+            let (value, flows) = network.find_maxflow(1, n, i64::MAX).unwrap();
+            assert_eq!(value, dinic.find_maxflow(i64::MAX));
+            assert_eq!(check_flow(&network, &flows, Some((1, n))), value);
+        }
+    }
+
+    #[test]
+    fn random_lower_bounds() {
+        let mut rng = PCG32::new_default(777);
+        let mut feasible = 0;
+        for _ in 0..100 {
+            let n = 6;
+            let mut network = FlowWithLowerBounds::new(n);
+            for _ in 0..12 {
+                let u = (rng.get_u32() as usize % n) + 1;
+                let v = (rng.get_u32() as usize % n) + 1;
+                let lower = (rng.get_u32() % 4) as i64;
+                network.add_edge(u, v, lower, lower + (rng.get_u32() % 10) as i64);
+            }
+            if let Some(flows) = network.find_circulation(i64::MAX) {
+                check_flow(&network, &flows, None);
+            }
+            if let Some((value, flows)) = network.find_maxflow(1, n, i64::MAX) {
+                feasible += 1;
+                assert_eq!(check_flow(&network, &flows, Some((1, n))), value);
+                // no more can be sent from 1 to n: without the lower bounds
+                // the plain max flow is an upper bound
+                let mut dinic: DinicMaxFlow<i64> = DinicMaxFlow::new(1, n, n);
+                for &(u, v, _, upper) in network.edges.iter() {
+                    dinic.add_edge(u, v, upper);
+                }
+                assert!(value <= dinic.find_maxflow(i64::MAX));
+            }
+        }
+        assert!(feasible > 0);
+    }
+}
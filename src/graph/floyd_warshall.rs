@@ -2,7 +2,7 @@ use num_traits::Zero;
 use std::collections::BTreeMap;
 use std::ops::Add;
 
-type Graph<V, E> = BTreeMap<V, BTreeMap<V, E>>;
+use super::{all_vertices, Graph};
 
 /// Performs the Floyd-Warshall algorithm on the input graph
 /// The graph is a weighted, directed graph with no negative cycles
@@ -14,29 +14,27 @@ type Graph<V, E> = BTreeMap<V, BTreeMap<V, E>>;
 ///
 /// For a key v, if map[v].len() == 0, then v cannot reach any other vertex, but is in the graph
 /// (island node, or sink in the case of a directed graph)
-pub fn floyd_warshall<V: Ord + Copy, E: Ord + Copy + Add<Output = E> + num_traits::Zero>(
-    graph: &Graph<V, E>,
-) -> BTreeMap<V, BTreeMap<V, E>> {
-    let mut map: BTreeMap<V, BTreeMap<V, E>> = BTreeMap::new();
-    for (u, edges) in graph.iter() {
-        if !map.contains_key(u) {
-            map.insert(*u, BTreeMap::new());
-        }
-        map.entry(*u).or_default().insert(*u, Zero::zero());
-        for (v, weight) in edges.iter() {
-            if !map.contains_key(v) {
-                map.insert(*v, BTreeMap::new());
-            }
-            map.entry(*v).or_default().insert(*v, Zero::zero());
-            map.entry(*u).and_modify(|mp| {
-                mp.insert(*v, *weight);
+pub fn floyd_warshall<G: Graph>(graph: &G) -> BTreeMap<G::Vertex, BTreeMap<G::Vertex, G::Weight>>
+where
+    G::Weight: Ord + Add<Output = G::Weight> + num_traits::Zero,
+{
+    let mut map: BTreeMap<G::Vertex, BTreeMap<G::Vertex, G::Weight>> = BTreeMap::new();
+    for u in graph.vertices() {
+        map.entry(u).or_default().insert(u, Zero::zero());
+        for (v, weight) in graph.neighbors(u) {
+            map.entry(v).or_default().insert(v, Zero::zero());
+            // keep the cheapest of parallel edges, like `floyd_warshall_dense`
+            map.entry(u).and_modify(|mp| {
+                mp.entry(v)
+                    .and_modify(|w| *w = (*w).min(weight))
+                    .or_insert(weight);
             });
         }
     }
     let keys = map.keys().copied().collect::<Vec<_>>();
     for &k in &keys {
         for &i in &keys {
-            if map[&i].get(&k).is_none() {
+            if !map[&i].contains_key(&k) {
                 continue;
             }
             for &j in &keys {
@@ -65,23 +63,230 @@ pub fn floyd_warshall<V: Ord + Copy, E: Ord + Copy + Add<Output = E> + num_trait
     map
 }
 
+/// The shortest paths between all pairs of vertices numbered 0..n, computed by
+/// `floyd_warshall_dense`, with everything stored in `Vec`s
+///
+/// The distance from u to v has no minimum if a path from u to v can go
+/// around a negative cycle: those pairs are said to be affected by a negative
+/// cycle, and they have no distance and no path.
+pub struct DenseShortestPaths<E> {
+    distances: Vec<Vec<Distance<E>>>,
+    /// next[u][v] is the vertex after u on a shortest path from u to v
+    next: Vec<Vec<Option<usize>>>,
+}
+
+#[derive(Clone, Copy)]
+enum Distance<E> {
+    Unreachable,
+    Finite(E),
+    /// The paths can go around a negative cycle, so they have no minimum
+    Unbounded,
+}
+
+/// Performs the Floyd-Warshall algorithm on a directed graph whose vertices
+/// are numbered 0..num_vertices, given by its edges (source, destination,
+/// weight). Negative weights and negative cycles are allowed.
+///
+/// The paths that can go around a negative cycle are marked as unbounded as
+/// soon as the cycle is found, instead of being relaxed around it, so the
+/// finite distances are always the weights of simple paths and don't
+/// overflow.
+pub fn floyd_warshall_dense<E>(
+    num_vertices: usize,
+    edges: &[(usize, usize, E)],
+) -> DenseShortestPaths<E>
+where
+    E: Ord + Copy + Add<Output = E> + Zero,
+{
+    use Distance::*;
+
+    let n = num_vertices;
+    let mut distances = vec![vec![Unreachable; n]; n];
+    let mut next = vec![vec![None; n]; n];
+    for v in 0..n {
+        distances[v][v] = Finite(E::zero());
+        next[v][v] = Some(v);
+    }
+    for &(u, v, weight) in edges {
+        match distances[u][v] {
+            Finite(d) if d <= weight => {}
+            _ => {
+                distances[u][v] = Finite(weight);
+                next[u][v] = Some(v);
+            }
+        }
+    }
+
+    // after round k, distances[i][j] is the lowest weight of the paths from i
+    // to j whose inner vertices are below k + 1
+    for k in 0..n {
+        // whether those paths can go around k as many times as wanted
+        let around_k = match distances[k][k] {
+            Finite(d) => d < E::zero(),
+            _ => true,
+        };
+        for i in 0..n {
+            let i_k = distances[i][k];
+            if let Unreachable = i_k {
+                continue;
+            }
+            for j in 0..n {
+                let through_k = match (i_k, distances[k][j]) {
+                    (_, Unreachable) => continue,
+                    (Finite(i_k), Finite(k_j)) if !around_k => Finite(i_k + k_j),
+                    _ => Unbounded,
+                };
+                match (distances[i][j], through_k) {
+                    (Unbounded, _) => {}
+                    (Finite(i_j), Finite(d)) if i_j <= d => {}
+                    _ => {
+                        distances[i][j] = through_k;
+                        next[i][j] = next[i][k];
+                    }
+                }
+            }
+        }
+    }
+
+    DenseShortestPaths { distances, next }
+}
+
+impl<E: Copy> DenseShortestPaths<E> {
+    pub fn num_vertices(&self) -> usize {
+        self.distances.len()
+    }
+
+    /// The distance from u to v, or None if v can't be reached from u or if
+    /// the pair is affected by a negative cycle
+    pub fn distance(&self, u: usize, v: usize) -> Option<E> {
+        match self.distances[u][v] {
+            Distance::Finite(d) => Some(d),
+            _ => None,
+        }
+    }
+
+    /// Whether a path from u to v can go around a negative cycle, so that it
+    /// can be made as short as wanted
+    pub fn is_affected_by_negative_cycle(&self, u: usize, v: usize) -> bool {
+        matches!(self.distances[u][v], Distance::Unbounded)
+    }
+
+    pub fn has_negative_cycle(&self) -> bool {
+        !self.negative_cycle_vertices().is_empty()
+    }
+
+    /// The vertices that are on some negative cycle
+    pub fn negative_cycle_vertices(&self) -> Vec<usize> {
+        (0..self.num_vertices())
+            .filter(|&v| self.is_affected_by_negative_cycle(v, v))
+            .collect()
+    }
+
+    /// The vertices of a shortest path from u to v (both included), or None
+    /// if there is no distance from u to v
+    pub fn path(&self, u: usize, v: usize) -> Option<Vec<usize>> {
+        self.distance(u, v)?;
+        let mut path = vec![u];
+        let mut current = u;
+        while current != v {
+            current = self.next[current][v]?;
+            path.push(current);
+        }
+        Some(path)
+    }
+}
+
+/// Same as `DenseShortestPaths`, for the vertices of any `Graph`, computed by
+/// `floyd_warshall_paths`
+pub struct ShortestPaths<V, E> {
+    vertices: Vec<V>,
+    index: BTreeMap<V, usize>,
+    dense: DenseShortestPaths<E>,
+}
+
+/// Performs the Floyd-Warshall algorithm on the input graph, keeping what is
+/// needed to rebuild the shortest paths and to find the negative cycles
+///
+/// The vertices are numbered and the work is done by `floyd_warshall_dense`.
+pub fn floyd_warshall_paths<G: Graph>(graph: &G) -> ShortestPaths<G::Vertex, G::Weight>
+where
+    G::Weight: Ord + Add<Output = G::Weight> + Zero,
+{
+    // like `floyd_warshall`, the vertices that are only the destination of
+    // edges are in the result too
+    let vertices: Vec<G::Vertex> = all_vertices(graph).into_iter().collect();
+    let index: BTreeMap<G::Vertex, usize> =
+        vertices.iter().enumerate().map(|(i, &v)| (v, i)).collect();
+    let edges: Vec<(usize, usize, G::Weight)> = graph
+        .edges()
+        .map(|(u, v, weight)| (index[&u], index[&v], weight))
+        .collect();
+    let dense = floyd_warshall_dense(vertices.len(), &edges);
+    ShortestPaths {
+        vertices,
+        index,
+        dense,
+    }
+}
+
+impl<V: Ord + Copy, E: Copy> ShortestPaths<V, E> {
+    /// The distance from u to v, or None if v can't be reached from u or if
+    /// the pair is affected by a negative cycle
+    pub fn distance(&self, u: &V, v: &V) -> Option<E> {
+        self.dense.distance(self.index[u], self.index[v])
+    }
+
+    pub fn is_affected_by_negative_cycle(&self, u: &V, v: &V) -> bool {
+        self.dense
+            .is_affected_by_negative_cycle(self.index[u], self.index[v])
+    }
+
+    pub fn has_negative_cycle(&self) -> bool {
+        self.dense.has_negative_cycle()
+    }
+
+    /// The vertices that are on some negative cycle
+    pub fn negative_cycle_vertices(&self) -> Vec<V> {
+        self.dense
+            .negative_cycle_vertices()
+            .into_iter()
+            .map(|v| self.vertices[v])
+            .collect()
+    }
+
+    /// The vertices of a shortest path from u to v (both included), or None
+    /// if there is no distance from u to v
+    pub fn path(&self, u: &V, v: &V) -> Option<Vec<V>> {
+        let path = self.dense.path(self.index[u], self.index[v])?;
+        Some(path.into_iter().map(|v| self.vertices[v]).collect())
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{floyd_warshall, Graph};
+    use super::{floyd_warshall, floyd_warshall_dense, floyd_warshall_paths};
+    use crate::graph::johnson;
+    use crate::graph::{AdjacencyList, AdjacencyMap};
+    use crate::math::PCG32;
     use std::collections::BTreeMap;
 
-    fn add_edge<V: Ord + Copy, E: Ord + Copy>(graph: &mut Graph<V, E>, v1: V, v2: V, c: E) {
-        graph.entry(v1).or_insert_with(BTreeMap::new).insert(v2, c);
+    fn add_edge<V: Ord + Copy, E: Ord + Copy>(graph: &mut AdjacencyMap<V, E>, v1: V, v2: V, c: E) {
+        graph.entry(v1).or_default().insert(v2, c);
     }
 
-    fn bi_add_edge<V: Ord + Copy, E: Ord + Copy>(graph: &mut Graph<V, E>, v1: V, v2: V, c: E) {
+    fn bi_add_edge<V: Ord + Copy, E: Ord + Copy>(
+        graph: &mut AdjacencyMap<V, E>,
+        v1: V,
+        v2: V,
+        c: E,
+    ) {
         add_edge(graph, v1, v2, c);
         add_edge(graph, v2, v1, c);
     }
 
     #[test]
     fn single_vertex() {
-        let mut graph: Graph<usize, usize> = BTreeMap::new();
+        let mut graph: AdjacencyMap<usize, usize> = BTreeMap::new();
         graph.insert(0, BTreeMap::new());
 
         let mut dists = BTreeMap::new();
@@ -188,4 +393,159 @@ mod tests {
 
         assert_eq!(floyd_warshall(&graph), dists_a);
     }
+
+    #[test]
+    fn paths() {
+        let mut graph = BTreeMap::new();
+        add_edge(&mut graph, 'a', 'c', 12);
+        add_edge(&mut graph, 'a', 'd', 60);
+        add_edge(&mut graph, 'b', 'a', 10);
+        add_edge(&mut graph, 'c', 'b', 20);
+        add_edge(&mut graph, 'c', 'd', 32);
+        add_edge(&mut graph, 'e', 'a', 7);
+        graph.insert('d', BTreeMap::new());
+
+        let paths = floyd_warshall_paths(&graph);
+        assert!(!paths.has_negative_cycle());
+        assert_eq!(paths.path(&'e', &'d'), Some(vec!['e', 'a', 'c', 'd']));
+        assert_eq!(paths.distance(&'e', &'d'), Some(51));
+        assert_eq!(paths.path(&'b', &'b'), Some(vec!['b']));
+        assert_eq!(paths.path(&'d', &'a'), None);
+        assert_eq!(paths.distance(&'d', &'a'), None);
+    }
+
+    #[test]
+    fn negative_cycle() {
+        // 1 -> 2 -> 3 -> 1 costs -1, 0 reaches it and 4 is reached from it
+        let edges = [
+            (0, 1, 5),
+            (1, 2, 2),
+            (2, 3, -4),
+            (3, 1, 1),
+            (3, 4, 3),
+            (5, 0, 1),
+            (4, 6, -2),
+        ];
+        let paths = floyd_warshall_dense(7, &edges);
+        assert!(paths.has_negative_cycle());
+        assert_eq!(paths.negative_cycle_vertices(), vec![1, 2, 3]);
+        assert!(paths.is_affected_by_negative_cycle(0, 4));
+        assert!(paths.is_affected_by_negative_cycle(5, 2));
+        assert_eq!(paths.distance(0, 6), None);
+        assert_eq!(paths.path(0, 6), None);
+        assert!(!paths.is_affected_by_negative_cycle(5, 0));
+        assert_eq!(paths.path(5, 0), Some(vec![5, 0]));
+        assert_eq!(paths.path(4, 6), Some(vec![4, 6]));
+        assert_eq!(paths.distance(4, 6), Some(-2));
+        assert!(!paths.is_affected_by_negative_cycle(4, 1));
+        assert_eq!(paths.distance(4, 1), None);
+    }
+
+    #[test]
+    fn no_overflow_around_negative_cycles() {
+        let n = 60;
+        let edges: Vec<(usize, usize, i64)> = (0..n)
+            .flat_map(|u| (0..n).filter(move |&v| v != u).map(move |v| (u, v, -1)))
+            .collect();
+        let paths = floyd_warshall_dense(n, &edges);
+        assert_eq!(paths.negative_cycle_vertices().len(), n);
+        assert!((0..n).all(|u| (0..n).all(|v| paths.is_affected_by_negative_cycle(u, v))));
+
+        let edges = [(0, 1, i64::MIN / 2), (1, 0, i64::MIN / 2), (2, 0, 1)];
+        let paths = floyd_warshall_dense(3, &edges);
+        assert!(paths.is_affected_by_negative_cycle(2, 1));
+        assert_eq!(paths.distance(2, 2), Some(0));
+    }
+
+    #[test]
+    fn vertex_without_entry() {
+        // 2 is only the destination of an edge, like in `single_edge`
+        let mut graph = BTreeMap::new();
+        add_edge(&mut graph, 1, 2, 3);
+        let paths = floyd_warshall_paths(&graph);
+        assert_eq!(paths.distance(&1, &2), Some(3));
+        assert_eq!(paths.distance(&2, &1), None);
+        assert_eq!(paths.path(&2, &2), Some(vec![2]));
+    }
+
+    #[test]
+    fn parallel_edges() {
+        let graph: AdjacencyList<u32, i32> = BTreeMap::from([(1, vec![(2, 3), (2, 5), (1, 4)])]);
+        let distances = floyd_warshall(&graph);
+        assert_eq!(distances[&1][&2], 3);
+        assert_eq!(distances[&1][&1], 0);
+        assert_eq!(distances, johnson(&graph).unwrap());
+        let graph: AdjacencyList<u32, i32> = BTreeMap::from([(1, vec![(2, 5), (2, 3)])]);
+        assert_eq!(floyd_warshall(&graph)[&1][&2], 3);
+    }
+
+    #[test]
+    fn random_graphs() {
+        let mut rng = PCG32::new_default(577);
+        for _ in 0..40 {
+            let n = 1 + rng.get_u32() % 20;
+            let mut graph: AdjacencyMap<u32, i64> = BTreeMap::new();
+            for v in 0..n {
+                graph.insert(v, BTreeMap::new());
+            }
+            for _ in 0..rng.get_u32() % (3 * n) {
+                let u = rng.get_u32() % n;
+                let v = rng.get_u32() % n;
+                if u != v {
+                    add_edge(&mut graph, u, v, (rng.get_u32() % 40) as i64 - 4);
+                }
+            }
+
+            let paths = floyd_warshall_paths(&graph);
+            match johnson(&graph) {
+                Ok(distances) => {
+                    assert!(!paths.has_negative_cycle());
+                    for u in 0..n {
+                        for v in 0..n {
+                            let expected = distances[&u].get(&v).copied();
+                            assert_eq!(paths.distance(&u, &v), expected);
+                            // the path has the right length and uses edges of the graph
+                            if let Some(path) = paths.path(&u, &v) {
+                                let length: i64 =
+                                    path.windows(2).map(|e| graph[&e[0]][&e[1]]).sum();
+                                assert_eq!(Some(length), expected);
+                            }
+                        }
+                    }
+                }
+                Err(cycle) => {
+                    let on_cycle = paths.negative_cycle_vertices();
+                    assert!(cycle.iter().all(|v| on_cycle.contains(v)));
+                    // a pair is affected if it can go through a vertex on a
+                    // negative cycle
+                    let n = n as usize;
+                    let mut reach = vec![vec![false; n]; n];
+                    for (u, row) in reach.iter_mut().enumerate() {
+                        row[u] = true;
+                        for &v in graph[&(u as u32)].keys() {
+                            row[v as usize] = true;
+                        }
+                    }
+                    for k in 0..n {
+                        for i in 0..n {
+                            for j in 0..n {
+                                reach[i][j] |= reach[i][k] && reach[k][j];
+                            }
+                        }
+                    }
+                    for u in 0..n {
+                        for v in 0..n {
+                            let expected = on_cycle
+                                .iter()
+                                .any(|&c| reach[u][c as usize] && reach[c as usize][v]);
+                            assert_eq!(
+                                paths.is_affected_by_negative_cycle(&(u as u32), &(v as u32)),
+                                expected
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
 }
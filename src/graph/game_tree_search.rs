@@ -0,0 +1,585 @@
+/*
+A search engine for two-player, zero-sum, turn-based games with perfect
+information (tic-tac-toe, connect four, chess, ...).
+
+A game only has to implement the `Game` trait: list the legal moves, play and
+undo a move, say whether the game is over and who won, and give a hash of the
+position. The engine then looks for the best move with:
+
+- negamax with alpha-beta pruning: the score of a position for the side to
+  move is minus the score of the best answer of the opponent, and the moves
+  that can't change the result are not explored;
+- iterative deepening: the search is done with a depth limit of 1, 2, 3...
+  up to the maximum depth, each search being made faster by what the previous
+  ones stored in the transposition table;
+- a transposition table, indexed by the Zobrist hash of the positions, that
+  remembers the score and the best move of the positions already searched;
+- move ordering: the best move found in the table is tried first, then the
+  "killer" moves that made a cut at the same depth elsewhere in the tree, then
+  the other moves in the order of `legal_moves`.
+
+When the depth limit is reached before the end of the game, `evaluate` gives
+an estimation of the position. A won game is worth `WIN_SCORE` minus the
+number of moves it took, so that faster wins (and slower losses) are
+preferred.
+*/
+
+use crate::math::PCG32;
+
+// every score given by `evaluate` should be less than this (in absolute value)
+pub const WIN_SCORE: i32 = 1_000_000;
+const INFINITY: i32 = 2 * WIN_SCORE;
+// scores above this are wins in a number of moves
+const WIN_THRESHOLD: i32 = WIN_SCORE / 2;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Side {
+    First,
+    Second,
+}
+
+impl Side {
+    pub fn opponent(self) -> Side {
+        match self {
+            Side::First => Side::Second,
+            Side::Second => Side::First,
+        }
+    }
+}
+
+// the result of a finished game, for the side to move
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Outcome {
+    Win,
+    Loss,
+    Draw,
+}
+
+pub trait Game {
+    type Move: Copy + Eq;
+
+    fn side_to_move(&self) -> Side;
+
+    // the moves the side to move can play, empty if the game is over. The
+    // first ones are searched first, so the most promising should come first.
+    fn legal_moves(&self) -> Vec<Self::Move>;
+
+    fn apply(&mut self, mv: Self::Move);
+
+    // takes back `mv`, which was the last move applied
+    fn undo(&mut self, mv: Self::Move);
+
+    // None while the game is not over
+    fn outcome(&self) -> Option<Outcome>;
+
+    // an estimation of a position where the game is not over, for the side to
+    // move: positive if it is ahead. It must stay below WIN_SCORE / 2.
+    fn evaluate(&self) -> i32 {
+        0
+    }
+
+    // a hash of the position (side to move included), usually kept up to
+    // date by `apply` and `undo` with a `ZobristTable`
+    fn hash(&self) -> u64;
+}
+
+// random keys for Zobrist hashing: the hash of a position is the xor of the
+// keys of its features (for example "a cross on the middle square" or "it is
+// the second player's turn"), which can be updated in O(1) when a move is
+// applied or undone
+pub struct ZobristTable {
+    keys: Vec<u64>,
+}
+
+impl ZobristTable {
+    pub fn new(num_features: usize, seed: u64) -> Self {
+        let mut rng = PCG32::new_default(seed);
+        ZobristTable {
+            keys: (0..num_features).map(|_| rng.get_u64()).collect(),
+        }
+    }
+
+    pub fn key(&self, feature: usize) -> u64 {
+        self.keys[feature]
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum Bound {
+    Exact,
+    // the score is at least the stored one
+    Lower,
+    // the score is at most the stored one
+    Upper,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct TableEntry<M> {
+    hash: u64,
+    depth: u32,
+    score: i32,
+    bound: Bound,
+    best_move: Option<M>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SearchResult<M> {
+    // None if the game is already over
+    pub best_move: Option<M>,
+    // for the side to move, see `WIN_SCORE`
+    pub score: i32,
+    // the depth of the last completed iteration
+    pub depth: u32,
+    // the number of positions visited by the whole search
+    pub nodes: u64,
+}
+
+impl<M> SearchResult<M> {
+    // whether the score is a forced win (or loss, if negative)
+    pub fn is_decisive(&self) -> bool {
+        self.score.abs() > WIN_THRESHOLD
+    }
+}
+
+pub struct GameSearch<G: Game> {
+    table: Vec<Option<TableEntry<G::Move>>>,
+    // for each ply, two moves that recently caused a cut
+    killers: Vec<[Option<G::Move>; 2]>,
+    // the best move found by the last search at the root
+    root_move: Option<G::Move>,
+    nodes: u64,
+}
+
+// win scores depend on the ply they are found at, but the table needs them
+// relative to the position they are stored for
+fn to_table(score: i32, ply: u32) -> i32 {
+    if score > WIN_THRESHOLD {
+        score + ply as i32
+    } else if score < -WIN_THRESHOLD {
+        score - ply as i32
+    } else {
+        score
+    }
+}
+
+fn from_table(score: i32, ply: u32) -> i32 {
+    if score > WIN_THRESHOLD {
+        score - ply as i32
+    } else if score < -WIN_THRESHOLD {
+        score + ply as i32
+    } else {
+        score
+    }
+}
+
+impl<G: Game> GameSearch<G> {
+    // the transposition table has 2^table_bits entries
+    pub fn new(table_bits: u32) -> Self {
+        GameSearch {
+            table: vec![None; 1 << table_bits],
+            killers: Vec::new(),
+            root_move: None,
+            nodes: 0,
+        }
+    }
+
+    // forgets everything learnt by the previous searches
+    pub fn clear(&mut self) {
+        self.table.iter_mut().for_each(|entry| *entry = None);
+        self.killers.clear();
+    }
+
+    // searches the best move with iterative deepening, up to max_depth moves
+    // ahead, stopping early when a forced win or loss is found
+    //
+    // the game is given back in the same position
+    pub fn search(&mut self, game: &mut G, max_depth: u32) -> SearchResult<G::Move> {
+        self.nodes = 0;
+        let mut result = SearchResult {
+            best_move: None,
+            score: self.negamax(game, 0, 0, -INFINITY, INFINITY),
+            depth: 0,
+            nodes: 0,
+        };
+        for depth in 1..=max_depth {
+            if game.outcome().is_some() {
+                break;
+            }
+            result.score = self.negamax(game, depth, 0, -INFINITY, INFINITY);
+            result.best_move = self.root_move;
+            result.depth = depth;
+            if result.is_decisive() {
+                break;
+            }
+        }
+        result.nodes = self.nodes;
+        result
+    }
+
+    // the exact score of every legal move (for the side to move), searched
+    // depth moves ahead, in the order of `legal_moves`
+    pub fn score_moves(&mut self, game: &mut G, depth: u32) -> Vec<(G::Move, i32)> {
+        let mut scores = Vec::new();
+        for mv in game.legal_moves() {
+            game.apply(mv);
+            let score = -self.negamax(game, depth.saturating_sub(1), 1, -INFINITY, INFINITY);
+            game.undo(mv);
+            scores.push((mv, score));
+        }
+        scores
+    }
+
+    fn table_entry(&self, hash: u64) -> Option<TableEntry<G::Move>> {
+        let slot = (hash as usize) & (self.table.len() - 1);
+        self.table[slot].filter(|entry| entry.hash == hash)
+    }
+
+    fn store(&mut self, entry: TableEntry<G::Move>) {
+        let slot = (entry.hash as usize) & (self.table.len() - 1);
+        match self.table[slot] {
+            // keep the deeper search of the same position
+            Some(old) if old.hash == entry.hash && old.depth > entry.depth => {}
+            _ => self.table[slot] = Some(entry),
+        }
+    }
+
+    // the table move first, then the killer moves, then the others
+    fn ordered_moves(&self, game: &G, ply: usize, table_move: Option<G::Move>) -> Vec<G::Move> {
+        let mut moves = game.legal_moves();
+        let killers = self.killers.get(ply).copied().unwrap_or([None, None]);
+        let mut front = 0;
+        for preferred in [table_move, killers[0], killers[1]].into_iter().flatten() {
+            if let Some(i) = moves[front..].iter().position(|&mv| mv == preferred) {
+                moves[front..=front + i].rotate_right(1);
+                front += 1;
+            }
+        }
+        moves
+    }
+
+    fn remember_killer(&mut self, ply: usize, mv: G::Move) {
+        if self.killers.len() <= ply {
+            self.killers.resize(ply + 1, [None, None]);
+        }
+        let killers = &mut self.killers[ply];
+        if killers[0] != Some(mv) {
+            killers[1] = killers[0];
+            killers[0] = Some(mv);
+        }
+    }
+
+    fn negamax(&mut self, game: &mut G, depth: u32, ply: u32, mut alpha: i32, beta: i32) -> i32 {
+        self.nodes += 1;
+        match game.outcome() {
+            Some(Outcome::Win) => return WIN_SCORE - ply as i32,
+            Some(Outcome::Loss) => return -(WIN_SCORE - ply as i32),
+            Some(Outcome::Draw) => return 0,
+            None => {}
+        }
+        if depth == 0 {
+            return game.evaluate();
+        }
+
+        let hash = game.hash();
+        let entry = self.table_entry(hash);
+        // the root is always searched, to know its best move
+        if let Some(entry) = entry.filter(|entry| ply > 0 && entry.depth >= depth) {
+            let score = from_table(entry.score, ply);
+            match entry.bound {
+                Bound::Exact => return score,
+                Bound::Lower if score >= beta => return score,
+                Bound::Upper if score <= alpha => return score,
+                _ => {}
+            }
+        }
+
+        let moves = self.ordered_moves(game, ply as usize, entry.and_then(|e| e.best_move));
+        if moves.is_empty() {
+            return game.evaluate();
+        }
+        let original_alpha = alpha;
+        let mut best_score = -INFINITY;
+        let mut best_move = None;
+        for mv in moves {
+            game.apply(mv);
+            let score = -self.negamax(game, depth - 1, ply + 1, -beta, -alpha);
+            game.undo(mv);
+            if score > best_score {
+                best_score = score;
+                best_move = Some(mv);
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                self.remember_killer(ply as usize, mv);
+                break;
+            }
+        }
+
+        let bound = if best_score <= original_alpha {
+            Bound::Upper
+        } else if best_score >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        self.store(TableEntry {
+            hash,
+            depth,
+            score: to_table(best_score, ply),
+            bound,
+            best_move,
+        });
+        if ply == 0 {
+            self.root_move = best_move;
+        }
+        best_score
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROWS: usize = 6;
+    const COLUMNS: usize = 7;
+
+    // Connect Four: the players drop their pieces in the columns of a 7x6
+    // grid, the first one to align four of them wins
+    struct ConnectFour {
+        // cells[column][row], row 0 at the bottom
+        cells: [[Option<Side>; ROWS]; COLUMNS],
+        heights: [usize; COLUMNS],
+        side: Side,
+        moves_played: usize,
+        // the side that just won, if any
+        winner: Option<Side>,
+        hash: u64,
+        keys: ZobristTable,
+    }
+
+    fn feature(column: usize, row: usize, side: Side) -> usize {
+        2 * (column * ROWS + row) + (side == Side::Second) as usize
+    }
+
+    const SIDE_FEATURE: usize = 2 * ROWS * COLUMNS;
+
+    impl ConnectFour {
+        fn new() -> Self {
+            ConnectFour {
+                cells: [[None; ROWS]; COLUMNS],
+                heights: [0; COLUMNS],
+                side: Side::First,
+                moves_played: 0,
+                winner: None,
+                hash: 0,
+                keys: ZobristTable::new(SIDE_FEATURE + 1, 42),
+            }
+        }
+
+        fn from_moves(columns: &[usize]) -> Self {
+            let mut game = ConnectFour::new();
+            for &column in columns {
+                game.apply(column);
+            }
+            game
+        }
+
+        fn owner(&self, column: i32, row: i32) -> Option<Side> {
+            if (0..COLUMNS as i32).contains(&column) && (0..ROWS as i32).contains(&row) {
+                self.cells[column as usize][row as usize]
+            } else {
+                None
+            }
+        }
+
+        // whether the piece at (column, row) is part of four in a row
+        fn aligns_four(&self, column: usize, row: usize) -> bool {
+            let side = self.cells[column][row];
+            let (column, row) = (column as i32, row as i32);
+            [(1, 0), (0, 1), (1, 1), (1, -1)].iter().any(|&(dc, dr)| {
+                let count = |sign: i32| {
+                    (1..4)
+                        .take_while(|&k| {
+                            self.owner(column + sign * k * dc, row + sign * k * dr) == side
+                        })
+                        .count()
+                };
+                1 + count(1) + count(-1) >= 4
+            })
+        }
+    }
+
+    impl Game for ConnectFour {
+        type Move = usize;
+
+        fn side_to_move(&self) -> Side {
+            self.side
+        }
+
+        // the center columns first, they are usually better
+        fn legal_moves(&self) -> Vec<usize> {
+            if self.winner.is_some() {
+                return vec![];
+            }
+            [3, 2, 4, 1, 5, 0, 6]
+                .into_iter()
+                .filter(|&column| self.heights[column] < ROWS)
+                .collect()
+        }
+
+        fn apply(&mut self, column: usize) {
+            let row = self.heights[column];
+            self.cells[column][row] = Some(self.side);
+            self.heights[column] += 1;
+            self.moves_played += 1;
+            self.hash ^=
+                self.keys.key(feature(column, row, self.side)) ^ self.keys.key(SIDE_FEATURE);
+            if self.aligns_four(column, row) {
+                self.winner = Some(self.side);
+            }
+            self.side = self.side.opponent();
+        }
+
+        fn undo(&mut self, column: usize) {
+            self.side = self.side.opponent();
+            self.heights[column] -= 1;
+            let row = self.heights[column];
+            self.cells[column][row] = None;
+            self.moves_played -= 1;
+            self.hash ^=
+                self.keys.key(feature(column, row, self.side)) ^ self.keys.key(SIDE_FEATURE);
+            self.winner = None;
+        }
+
+        fn outcome(&self) -> Option<Outcome> {
+            match self.winner {
+                Some(side) if side == self.side => Some(Outcome::Win),
+                Some(_) => Some(Outcome::Loss),
+                None if self.moves_played == ROWS * COLUMNS => Some(Outcome::Draw),
+                None => None,
+            }
+        }
+
+        // the pieces in the center columns take part in more lines
+        fn evaluate(&self) -> i32 {
+            const WEIGHTS: [i32; COLUMNS] = [0, 1, 2, 4, 2, 1, 0];
+            let mut score = 0;
+            for (column, cells) in self.cells.iter().enumerate() {
+                for cell in cells.iter().flatten() {
+                    score += if *cell == self.side {
+                        WEIGHTS[column]
+                    } else {
+                        -WEIGHTS[column]
+                    };
+                }
+            }
+            score
+        }
+
+        fn hash(&self) -> u64 {
+            self.hash
+        }
+    }
+
+    // plain negamax without pruning, table nor ordering
+    fn reference_negamax<G: Game>(game: &mut G, depth: u32, ply: u32) -> i32 {
+        match game.outcome() {
+            Some(Outcome::Win) => return WIN_SCORE - ply as i32,
+            Some(Outcome::Loss) => return -(WIN_SCORE - ply as i32),
+            Some(Outcome::Draw) => return 0,
+            None => {}
+        }
+        if depth == 0 {
+            return game.evaluate();
+        }
+        let mut best = -INFINITY;
+        for mv in game.legal_moves() {
+            game.apply(mv);
+            best = best.max(-reference_negamax(game, depth - 1, ply + 1));
+            game.undo(mv);
+        }
+        best
+    }
+
+    #[test]
+    fn wins_immediately() {
+        // the first player has three in the bottom row (columns 0, 1, 2)
+        let mut game = ConnectFour::from_moves(&[0, 0, 1, 1, 2, 2]);
+        let mut search = GameSearch::new(16);
+        let result = search.search(&mut game, 6);
+        assert_eq!(result.best_move, Some(3));
+        assert_eq!(result.score, WIN_SCORE - 1);
+        assert!(result.is_decisive());
+        assert_eq!(result.depth, 1);
+        // the position is given back untouched
+        assert_eq!(
+            game.hash(),
+            ConnectFour::from_moves(&[0, 0, 1, 1, 2, 2]).hash()
+        );
+        assert_eq!(game.moves_played, 6);
+    }
+
+    #[test]
+    fn blocks_and_finds_forced_wins() {
+        // the second player must block the column 3
+        let mut game = ConnectFour::from_moves(&[3, 0, 3, 1, 3]);
+        let mut search = GameSearch::new(16);
+        assert_eq!(search.search(&mut game, 6).best_move, Some(3));
+
+        // the first player has 1, 2, 3 on the bottom row and can complete it
+        // at either end
+        let mut game = ConnectFour::from_moves(&[1, 1, 2, 2, 3, 3]);
+        let scores = search.score_moves(&mut game, 1);
+        assert!(scores.contains(&(0, WIN_SCORE - 1)));
+        assert!(scores.contains(&(4, WIN_SCORE - 1)));
+        // same with both ends open, but it is the second player's turn: it
+        // can only block one of them
+        let mut game = ConnectFour::from_moves(&[1, 6, 2, 6, 3]);
+        let result = search.search(&mut game, 6);
+        assert_eq!(result.score, -(WIN_SCORE - 2));
+    }
+
+    #[test]
+    fn same_scores_as_plain_negamax() {
+        // positions where the game is decided within the depth, so that the
+        // scores don't depend on the depth the positions are searched at
+        let mut rng = PCG32::new_default(99);
+        for _ in 0..20 {
+            let mut game = ConnectFour::new();
+            for _ in 0..30 {
+                let moves = game.legal_moves();
+                if moves.is_empty() {
+                    break;
+                }
+                game.apply(moves[rng.get_u32() as usize % moves.len()]);
+            }
+            if game.outcome().is_some() {
+                continue;
+            }
+            let remaining = (ROWS * COLUMNS - game.moves_played) as u32;
+            let depth = remaining.min(6);
+            let expected = reference_negamax(&mut game, depth, 0);
+            if expected.abs() > WIN_THRESHOLD || depth == remaining {
+                let mut search = GameSearch::new(12);
+                let result = search.search(&mut game, depth);
+                assert_eq!(result.score, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn playing_against_itself() {
+        let mut game = ConnectFour::new();
+        let mut search = GameSearch::new(18);
+        let mut moves = 0;
+        while game.outcome().is_none() {
+            let result = search.search(&mut game, 5);
+            game.apply(result.best_move.unwrap());
+            moves += 1;
+        }
+        assert_eq!(moves, game.moves_played);
+        let result = search.search(&mut game, 5);
+        assert_eq!(result.best_move, None);
+        assert_eq!(result.depth, 0);
+    }
+}
@@ -0,0 +1,781 @@
+/*
+Reading and writing graphs as text, in the formats benchmark instances are
+usually distributed in:
+
+- edge lists: one `u v` (or `u v w` when weighted) edge per line, as used by
+  SNAP and most datasets. Blank lines, and lines starting with `#` or `%`,
+  are ignored.
+- adjacency matrices: one row per vertex, the j-th entry of row i being the
+  weight of the edge from i to j, or `-` if there is none. Vertices are
+  numbered from 1 to n.
+- DIMACS shortest path (`.gr`, 9th DIMACS challenge): a `p sp n m` problem
+  line, then m `a u v w` arc lines. Vertices are numbered from 1 to n.
+- DIMACS max flow (`.max`, 1st DIMACS challenge): a `p max n m` problem line,
+  `n v s` and `n v t` lines giving the source and sink, and m `a u v c` arc
+  lines with capacities.
+- DIMACS CNF (`.cnf`, SAT competitions): a `p cnf n m` problem line, then m
+  clauses, each one a list of literals ended by `0` (possibly spanning several
+  lines). The literal m is the variable m, and -m its negation. Some files
+  end with a `%` line, after which everything is ignored.
+
+In all DIMACS formats, `c` lines are comments. The Graphviz DOT format is
+in `dot_format.rs`.
+
+The parsers give back the graph types the algorithms take (an
+`AdjacencyList` for `dijkstra` and the other `Graph` algorithms, any
+`MaxFlow` solver for flows), and report where the text is wrong with a
+`ParseError` (lines and columns start from 1). `write_dimacs_solution`
+writes the result of `solve_sat` like SAT competition solvers do.
+*/
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fmt::Write;
+use std::str::FromStr;
+
+use super::{AdjacencyList, AdjacencyMap, Graph, MaxFlow, SatResult};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(line: usize, column: usize, message: impl Into<String>) -> Self {
+        ParseError {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// A line split into whitespace separated tokens
+struct Line<'a> {
+    number: usize,
+    // Each token with the column where it starts
+    tokens: Vec<(usize, &'a str)>,
+    // The column just after the end of the line, where missing tokens are
+    // reported
+    end: usize,
+}
+
+impl<'a> Line<'a> {
+    fn new(number: usize, line: &'a str) -> Self {
+        let mut tokens = vec![];
+        let mut start: Option<(usize, usize)> = None;
+        let mut end = 1;
+        for (column, (i, c)) in line.char_indices().enumerate() {
+            if c.is_whitespace() {
+                if let Some((byte, first)) = start.take() {
+                    tokens.push((first + 1, &line[byte..i]));
+                }
+            } else if start.is_none() {
+                start = Some((i, column));
+            }
+            end = column + 2;
+        }
+        if let Some((byte, first)) = start {
+            tokens.push((first + 1, &line[byte..]));
+        }
+        Line {
+            number,
+            tokens,
+            end,
+        }
+    }
+
+    fn error(&self, column: usize, message: impl Into<String>) -> ParseError {
+        ParseError::new(self.number, column, message)
+    }
+
+    // Checks that the line has exactly one token for each of `what`
+    fn expect(&self, what: &[&str]) -> Result<(), ParseError> {
+        if let Some(&(column, token)) = self.tokens.get(what.len()) {
+            return Err(self.error(column, format!("unexpected `{token}`")));
+        }
+        match what.get(self.tokens.len()) {
+            Some(missing) => Err(self.error(self.end, format!("expected {missing}"))),
+            None => Ok(()),
+        }
+    }
+
+    fn parse<T: FromStr>(&self, i: usize, what: &str) -> Result<T, ParseError> {
+        let (column, token) = self.tokens[i];
+        token
+            .parse()
+            .map_err(|_| self.error(column, format!("invalid {what} `{token}`")))
+    }
+
+    // A vertex between 1 and n
+    fn parse_vertex(&self, i: usize, n: usize) -> Result<usize, ParseError> {
+        let v = self.parse(i, "vertex")?;
+        if v == 0 || v > n {
+            return Err(self.error(
+                self.tokens[i].0,
+                format!("vertex {v} is not between 1 and {n}"),
+            ));
+        }
+        Ok(v)
+    }
+}
+
+// The lines that are not blank, nor comments starting with one of `comments`
+fn lines<'a>(text: &'a str, comments: &'a [&'a str]) -> impl Iterator<Item = Line<'a>> + 'a {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| Line::new(i + 1, line))
+        .filter(move |line| match line.tokens.first() {
+            None => false,
+            Some((_, first)) => !comments.iter().any(|c| first.starts_with(c)),
+        })
+}
+
+// Where errors about something missing at the end of the text are reported
+fn end_of_text(text: &str) -> ParseError {
+    ParseError::new(text.lines().count() + 1, 1, "")
+}
+
+// Each line is `u v w`, an edge from u to v of weight w, which is also added
+// from v to u if `directed` is false. Vertices without edges can't be
+// written in this format.
+pub fn parse_edge_list<V, E>(text: &str, directed: bool) -> Result<AdjacencyList<V, E>, ParseError>
+where
+    V: FromStr + Ord + Copy,
+    E: FromStr + Copy,
+{
+    let mut graph: AdjacencyList<V, E> = BTreeMap::new();
+    for line in lines(text, &["#", "%"]) {
+        line.expect(&["a source vertex", "a destination vertex", "a weight"])?;
+        let u: V = line.parse(0, "vertex")?;
+        let v: V = line.parse(1, "vertex")?;
+        let w: E = line.parse(2, "weight")?;
+        graph.entry(u).or_default().push((v, w));
+        if directed {
+            graph.entry(v).or_default();
+        } else {
+            graph.entry(v).or_default().push((u, w));
+        }
+    }
+    Ok(graph)
+}
+
+// Each line is `u v`, an edge from u to v, which is also added from v to u if
+// `directed` is false
+pub fn parse_unweighted_edge_list<V>(
+    text: &str,
+    directed: bool,
+) -> Result<BTreeMap<V, Vec<V>>, ParseError>
+where
+    V: FromStr + Ord + Copy,
+{
+    let mut graph: BTreeMap<V, Vec<V>> = BTreeMap::new();
+    for line in lines(text, &["#", "%"]) {
+        line.expect(&["a source vertex", "a destination vertex"])?;
+        let u: V = line.parse(0, "vertex")?;
+        let v: V = line.parse(1, "vertex")?;
+        graph.entry(u).or_default().push(v);
+        if directed {
+            graph.entry(v).or_default();
+        } else {
+            graph.entry(v).or_default().push(u);
+        }
+    }
+    Ok(graph)
+}
+
+// Every edge as it is stored, so an undirected graph (with its edges in both
+// directions) should be read back with `directed` set to true
+pub fn write_edge_list<G: Graph>(graph: &G) -> String
+where
+    G::Vertex: fmt::Display,
+    G::Weight: fmt::Display,
+{
+    let mut text = String::new();
+    for (u, v, w) in graph.edges() {
+        writeln!(text, "{u} {v} {w}").unwrap();
+    }
+    text
+}
+
+pub fn write_unweighted_edge_list<G: Graph>(graph: &G) -> String
+where
+    G::Vertex: fmt::Display,
+{
+    let mut text = String::new();
+    for (u, v, _) in graph.edges() {
+        writeln!(text, "{u} {v}").unwrap();
+    }
+    text
+}
+
+pub fn parse_adjacency_matrix<E>(text: &str) -> Result<AdjacencyMap<usize, E>, ParseError>
+where
+    E: FromStr + Copy,
+{
+    let mut graph: AdjacencyMap<usize, E> = BTreeMap::new();
+    let mut n = 0;
+    for line in lines(text, &["#", "%"]) {
+        if graph.is_empty() {
+            n = line.tokens.len();
+        }
+        let u = graph.len() + 1;
+        if u > n {
+            return Err(line.error(1, format!("more than {n} rows")));
+        }
+        if line.tokens.len() != n {
+            let column = line.tokens.get(n).map_or(line.end, |&(column, _)| column);
+            return Err(line.error(column, format!("expected {n} entries in the row")));
+        }
+        let mut neighbors = BTreeMap::new();
+        for (i, &(_, token)) in line.tokens.iter().enumerate() {
+            if token != "-" {
+                neighbors.insert(i + 1, line.parse(i, "weight")?);
+            }
+        }
+        graph.insert(u, neighbors);
+    }
+    if graph.len() < n {
+        let error = end_of_text(text);
+        return Err(ParseError {
+            message: format!("expected {n} rows, found {}", graph.len()),
+            ..error
+        });
+    }
+    Ok(graph)
+}
+
+// The rows and columns are in the order of `graph.vertices()`. With parallel
+// edges, only the last one is kept.
+pub fn write_adjacency_matrix<G: Graph>(graph: &G) -> String
+where
+    G::Weight: fmt::Display,
+{
+    let vertices: Vec<G::Vertex> = graph.vertices().collect();
+    let mut text = String::new();
+    for &u in vertices.iter() {
+        let row: BTreeMap<G::Vertex, G::Weight> = graph.neighbors(u).collect();
+        let entries: Vec<String> = vertices
+            .iter()
+            .map(|v| match row.get(v) {
+                Some(w) => w.to_string(),
+                None => "-".to_string(),
+            })
+            .collect();
+        writeln!(text, "{}", entries.join(" ")).unwrap();
+    }
+    text
+}
+
+// The `p <kind> n m` line, with the line where it was found
+struct ProblemLine {
+    line: usize,
+    num_vertices: usize,
+    num_arcs: usize,
+    // Where m is, to report a wrong number of arcs
+    arcs_column: usize,
+}
+
+// `counted` names what n and m count (vertices and arcs, except for CNF)
+fn parse_problem_line(
+    line: &Line,
+    kind: &str,
+    counted: [&str; 2],
+) -> Result<ProblemLine, ParseError> {
+    let [vertices, arcs] = counted;
+    line.expect(&[
+        "`p`",
+        "a problem kind",
+        &format!("a number of {vertices}"),
+        &format!("a number of {arcs}"),
+    ])?;
+    if line.tokens[1].1 != kind {
+        return Err(line.error(
+            line.tokens[1].0,
+            format!(
+                "expected problem kind `{kind}`, found `{}`",
+                line.tokens[1].1
+            ),
+        ));
+    }
+    Ok(ProblemLine {
+        line: line.number,
+        num_vertices: line.parse(2, &format!("number of {vertices}"))?,
+        num_arcs: line.parse(3, &format!("number of {arcs}"))?,
+        arcs_column: line.tokens[3].0,
+    })
+}
+
+// Reads a DIMACS file: the problem line, then `handle` is called for every
+// other line that is not a comment
+fn parse_dimacs<'a>(
+    text: &'a str,
+    kind: &str,
+    mut handle: impl FnMut(&ProblemLine, &Line<'a>) -> Result<(), ParseError>,
+) -> Result<ProblemLine, ParseError> {
+    let mut problem: Option<ProblemLine> = None;
+    let mut num_arcs = 0;
+    for line in lines(text, &["c"]) {
+        let (column, first) = line.tokens[0];
+        match (first, &problem) {
+            ("p", None) => problem = Some(parse_problem_line(&line, kind, ["vertices", "arcs"])?),
+            ("p", Some(_)) => return Err(line.error(column, "a second problem line")),
+            (_, None) => return Err(line.error(column, "expected the problem line first")),
+            (_, Some(p)) => {
+                if first == "a" {
+                    num_arcs += 1;
+                }
+                handle(p, &line)?;
+            }
+        }
+    }
+    let problem = problem.ok_or_else(|| ParseError {
+        message: "missing problem line".to_string(),
+        ..end_of_text(text)
+    })?;
+    if num_arcs != problem.num_arcs {
+        return Err(ParseError::new(
+            problem.line,
+            problem.arcs_column,
+            format!("expected {} arcs, found {num_arcs}", problem.num_arcs),
+        ));
+    }
+    Ok(problem)
+}
+
+// The graph has every vertex from 1 to n, even those without arcs
+pub fn parse_dimacs_shortest_path<E>(text: &str) -> Result<AdjacencyList<usize, E>, ParseError>
+where
+    E: FromStr + Copy,
+{
+    let mut arcs = vec![];
+    let problem = parse_dimacs(text, "sp", |problem, line| {
+        let (column, first) = line.tokens[0];
+        if first != "a" {
+            return Err(line.error(column, format!("unknown line type `{first}`")));
+        }
+        line.expect(&["`a`", "a source vertex", "a destination vertex", "a weight"])?;
+        let u = line.parse_vertex(1, problem.num_vertices)?;
+        let v = line.parse_vertex(2, problem.num_vertices)?;
+        arcs.push((u, v, line.parse(3, "weight")?));
+        Ok(())
+    })?;
+    let mut graph: AdjacencyList<usize, E> =
+        (1..=problem.num_vertices).map(|v| (v, vec![])).collect();
+    for (u, v, w) in arcs {
+        graph.get_mut(&u).unwrap().push((v, w));
+    }
+    Ok(graph)
+}
+
+// The vertices should be numbered from 1 to n
+pub fn write_dimacs_shortest_path<G: Graph<Vertex = usize>>(graph: &G) -> String
+where
+    G::Weight: fmt::Display,
+{
+    let n = graph.vertices().max().unwrap_or(0);
+    let mut text = format!("p sp {n} {}\n", graph.num_edges());
+    for (u, v, w) in graph.edges() {
+        writeln!(text, "a {u} {v} {w}").unwrap();
+    }
+    text
+}
+
+// A max flow instance, with vertices numbered from 1 to n like for the
+// `MaxFlow` solvers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DimacsMaxFlow<T> {
+    pub num_vertices: usize,
+    pub source: usize,
+    pub sink: usize,
+    // (from, to, capacity)
+    pub arcs: Vec<(usize, usize, T)>,
+}
+
+impl<T: Copy> DimacsMaxFlow<T> {
+    // A solver (`DinicMaxFlow`, `PushRelabelMaxFlow`...) with the arcs added
+    pub fn network<M: MaxFlow<T>>(&self) -> M {
+        let mut network = M::new(self.source, self.sink, self.num_vertices);
+        for &(u, v, capacity) in self.arcs.iter() {
+            network.add_edge(u, v, capacity);
+        }
+        network
+    }
+}
+
+pub fn parse_dimacs_max_flow<T>(text: &str) -> Result<DimacsMaxFlow<T>, ParseError>
+where
+    T: FromStr + Copy,
+{
+    let mut arcs = vec![];
+    let (mut source, mut sink) = (None, None);
+    let problem = parse_dimacs(text, "max", |problem, line| {
+        let (column, first) = line.tokens[0];
+        match first {
+            "a" => {
+                line.expect(&[
+                    "`a`",
+                    "a source vertex",
+                    "a destination vertex",
+                    "a capacity",
+                ])?;
+                let u = line.parse_vertex(1, problem.num_vertices)?;
+                let v = line.parse_vertex(2, problem.num_vertices)?;
+                arcs.push((u, v, line.parse(3, "capacity")?));
+            }
+            "n" => {
+                line.expect(&["`n`", "a vertex", "`s` or `t`"])?;
+                let v = line.parse_vertex(1, problem.num_vertices)?;
+                let terminal = match line.tokens[2].1 {
+                    "s" => &mut source,
+                    "t" => &mut sink,
+                    other => {
+                        return Err(line.error(
+                            line.tokens[2].0,
+                            format!("expected `s` or `t`, found `{other}`"),
+                        ))
+                    }
+                };
+                if terminal.replace(v).is_some() {
+                    return Err(line.error(column, "a second source or sink"));
+                }
+            }
+            _ => return Err(line.error(column, format!("unknown line type `{first}`"))),
+        }
+        Ok(())
+    })?;
+    let missing = |what: &str| ParseError {
+        message: format!("missing {what}"),
+        ..end_of_text(text)
+    };
+    Ok(DimacsMaxFlow {
+        num_vertices: problem.num_vertices,
+        source: source.ok_or_else(|| missing("source"))?,
+        sink: sink.ok_or_else(|| missing("sink"))?,
+        arcs,
+    })
+}
+
+pub fn write_dimacs_max_flow<T: fmt::Display>(instance: &DimacsMaxFlow<T>) -> String {
+    let mut text = format!(
+        "p max {} {}\nn {} s\nn {} t\n",
+        instance.num_vertices,
+        instance.arcs.len(),
+        instance.source,
+        instance.sink
+    );
+    for (u, v, capacity) in instance.arcs.iter() {
+        writeln!(text, "a {u} {v} {capacity}").unwrap();
+    }
+    text
+}
+
+// A formula in conjunctive normal form, for `solve_sat`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DimacsCnf {
+    pub num_variables: usize,
+    // every clause is a list of literals: m for the variable m, -m for its
+    // negation, like `solve_two_satisfiability`
+    pub clauses: Vec<Vec<i64>>,
+}
+
+// A `0` without literals before it is an empty clause, which can't be
+// satisfied
+pub fn parse_dimacs_cnf(text: &str) -> Result<DimacsCnf, ParseError> {
+    let mut problem: Option<ProblemLine> = None;
+    let mut clauses = vec![];
+    let mut clause = vec![];
+    for line in lines(text, &["c"]) {
+        let (column, first) = line.tokens[0];
+        if first.starts_with('%') {
+            break;
+        }
+        let num_variables = match (first, &problem) {
+            ("p", None) => {
+                problem = Some(parse_problem_line(&line, "cnf", ["variables", "clauses"])?);
+                continue;
+            }
+            ("p", Some(_)) => return Err(line.error(column, "a second problem line")),
+            (_, None) => return Err(line.error(column, "expected the problem line first")),
+            (_, Some(p)) => p.num_vertices,
+        };
+        for (i, &(column, _)) in line.tokens.iter().enumerate() {
+            let literal: i64 = line.parse(i, "literal")?;
+            if literal == 0 {
+                clauses.push(std::mem::take(&mut clause));
+            } else if literal.unsigned_abs() as usize > num_variables {
+                return Err(line.error(
+                    column,
+                    format!("variable {literal} is not between 1 and {num_variables}"),
+                ));
+            } else {
+                clause.push(literal);
+            }
+        }
+    }
+
+    let problem = problem.ok_or_else(|| ParseError {
+        message: "missing problem line".to_string(),
+        ..end_of_text(text)
+    })?;
+    if !clause.is_empty() {
+        return Err(ParseError {
+            message: "the last clause is not ended by `0`".to_string(),
+            ..end_of_text(text)
+        });
+    }
+    if clauses.len() != problem.num_arcs {
+        return Err(ParseError::new(
+            problem.line,
+            problem.arcs_column,
+            format!(
+                "expected {} clauses, found {}",
+                problem.num_arcs,
+                clauses.len()
+            ),
+        ));
+    }
+    Ok(DimacsCnf {
+        num_variables: problem.num_vertices,
+        clauses,
+    })
+}
+
+pub fn write_dimacs_cnf(formula: &DimacsCnf) -> String {
+    let mut text = format!(
+        "p cnf {} {}\n",
+        formula.num_variables,
+        formula.clauses.len()
+    );
+    for clause in formula.clauses.iter() {
+        for literal in clause {
+            write!(text, "{literal} ").unwrap();
+        }
+        text.push_str("0\n");
+    }
+    text
+}
+
+// The output of SAT competition solvers: `s SATISFIABLE` then the model in a
+// `v` line (the literals that are true, ended by `0`), or `s UNSATISFIABLE`
+pub fn write_dimacs_solution(result: &SatResult) -> String {
+    match result {
+        SatResult::Satisfiable(model) => {
+            let mut text = "s SATISFIABLE\nv".to_string();
+            for (v, &value) in model.iter().enumerate().skip(1) {
+                let sign = if value { "" } else { "-" };
+                write!(text, " {sign}{v}").unwrap();
+            }
+            text.push_str(" 0\n");
+            text
+        }
+        SatResult::Unsatisfiable => "s UNSATISFIABLE\n".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{dijkstra, DinicMaxFlow, PushRelabelMaxFlow};
+
+    #[test]
+    fn edge_lists() {
+        let text = "# a comment\n1 2 5\n\n2 3 -1\n% another one\n3 1 2\n";
+        let graph: AdjacencyList<u32, i64> = parse_edge_list(text, true).unwrap();
+        assert_eq!(graph[&1], vec![(2, 5)]);
+        assert_eq!(graph[&3], vec![(1, 2)]);
+        assert_eq!(write_edge_list(&graph), "1 2 5\n2 3 -1\n3 1 2\n");
+        assert_eq!(parse_edge_list(&write_edge_list(&graph), true), Ok(graph));
+
+        let undirected: AdjacencyList<u32, i64> = parse_edge_list(text, false).unwrap();
+        assert_eq!(undirected[&1], vec![(2, 5), (3, 2)]);
+
+        let unweighted: BTreeMap<char, Vec<char>> =
+            parse_unweighted_edge_list("a b\nb c", false).unwrap();
+        assert_eq!(unweighted[&'b'], vec!['a', 'c']);
+        assert_eq!(
+            write_unweighted_edge_list(&unweighted),
+            "a b\nb a\nb c\nc b\n"
+        );
+    }
+
+    #[test]
+    fn edge_list_errors() {
+        let error = parse_edge_list::<u32, i64>("1 2 3\n  4 x 1", true).unwrap_err();
+        assert_eq!(error, ParseError::new(2, 5, "invalid vertex `x`"));
+        assert_eq!(error.to_string(), "line 2, column 5: invalid vertex `x`");
+        let error = parse_edge_list::<u32, i64>("1 2", true).unwrap_err();
+        assert_eq!(error, ParseError::new(1, 4, "expected a weight"));
+        let error = parse_unweighted_edge_list::<u32>("1 2 3", true).unwrap_err();
+        assert_eq!(error, ParseError::new(1, 5, "unexpected `3`"));
+    }
+
+    #[test]
+    fn adjacency_matrices() {
+        let text = "- 4 -\n- - 1\n2 - -\n";
+        let graph: AdjacencyMap<usize, i32> = parse_adjacency_matrix(text).unwrap();
+        assert_eq!(graph.num_edges(), 3);
+        assert_eq!(graph[&1][&2], 4);
+        assert_eq!(graph[&3][&1], 2);
+        assert_eq!(write_adjacency_matrix(&graph), text);
+
+        assert_eq!(
+            parse_adjacency_matrix::<i32>("- 1\n1 - 3\n"),
+            Err(ParseError::new(2, 5, "expected 2 entries in the row"))
+        );
+        assert_eq!(
+            parse_adjacency_matrix::<i32>("- 1\n1 -\n1 1"),
+            Err(ParseError::new(3, 1, "more than 2 rows"))
+        );
+        assert_eq!(
+            parse_adjacency_matrix::<i32>("- 1\n"),
+            Err(ParseError::new(2, 1, "expected 2 rows, found 1"))
+        );
+        assert_eq!(
+            parse_adjacency_matrix::<i32>("- 1.5\n1 -"),
+            Err(ParseError::new(1, 3, "invalid weight `1.5`"))
+        );
+    }
+
+    #[test]
+    fn dimacs_shortest_path() {
+        let text = "c a small instance\n\
+                    p sp 5 6\n\
+                    a 1 2 7\n\
+                    a 1 3 2\n\
+                    a 3 2 3\n\
+                    a 2 4 1\n\
+                    a 3 4 8\n\
+                    c vertex 5 has no arcs\n\
+                    a 4 1 1\n";
+        let graph: AdjacencyList<usize, u64> = parse_dimacs_shortest_path(text).unwrap();
+        assert_eq!(graph.num_vertices(), 5);
+        let distances = dijkstra(&graph, &1);
+        assert_eq!(distances[&4], Some((2, 6)));
+        assert!(!distances.contains_key(&5));
+
+        let written = write_dimacs_shortest_path(&graph);
+        assert!(written.starts_with("p sp 5 6\na 1 2 7\n"));
+        assert_eq!(parse_dimacs_shortest_path(&written), Ok(graph));
+    }
+
+    #[test]
+    fn dimacs_errors() {
+        let parse = parse_dimacs_shortest_path::<u64>;
+        assert_eq!(
+            parse("a 1 2 3"),
+            Err(ParseError::new(1, 1, "expected the problem line first"))
+        );
+        assert_eq!(
+            parse("p max 2 1\n"),
+            Err(ParseError::new(
+                1,
+                3,
+                "expected problem kind `sp`, found `max`"
+            ))
+        );
+        assert_eq!(
+            parse("p sp 2 1\na 1  3 4"),
+            Err(ParseError::new(2, 6, "vertex 3 is not between 1 and 2"))
+        );
+        assert_eq!(
+            parse("p sp 2 2\na 1 2 4"),
+            Err(ParseError::new(1, 8, "expected 2 arcs, found 1"))
+        );
+        assert_eq!(
+            parse("c nothing"),
+            Err(ParseError::new(2, 1, "missing problem line"))
+        );
+        assert_eq!(
+            parse_dimacs_max_flow::<i64>("p max 2 0\nn 1 s\nn 2 x"),
+            Err(ParseError::new(3, 5, "expected `s` or `t`, found `x`"))
+        );
+        assert_eq!(
+            parse_dimacs_max_flow::<i64>("p max 2 0\nn 1 s\n"),
+            Err(ParseError::new(3, 1, "missing sink"))
+        );
+    }
+
+    #[test]
+    fn dimacs_max_flow() {
+        let text = "c the example of the DIMACS challenge\n\
+                    p max 6 8\n\
+                    n 1 s\n\
+                    n 6 t\n\
+                    a 1 2 5\n\
+                    a 1 3 15\n\
+                    a 2 4 5\n\
+                    a 2 5 5\n\
+                    a 3 4 5\n\
+                    a 3 5 5\n\
+                    a 4 6 15\n\
+                    a 5 6 5\n";
+        let instance: DimacsMaxFlow<i64> = parse_dimacs_max_flow(text).unwrap();
+        assert_eq!((instance.source, instance.sink), (1, 6));
+        assert_eq!(instance.arcs.len(), 8);
+
+        let mut dinic: DinicMaxFlow<i64> = instance.network();
+        assert_eq!(dinic.find_maxflow(i64::MAX), 15);
+        let mut push_relabel: PushRelabelMaxFlow<i64> = instance.network();
+        assert_eq!(push_relabel.find_maxflow(i64::MAX), 15);
+
+        let written = write_dimacs_max_flow(&instance);
+        assert_eq!(parse_dimacs_max_flow(&written), Ok(instance));
+    }
+
+    #[test]
+    fn dimacs_cnf() {
+        let text = "c (x1 or not x3) and (x2 or x3 or not x1)\n\
+                    p cnf 3 2\n\
+                    1 -3 0\n\
+                    2 3\n\
+                    -1 0\n\
+                    %\n\
+                    0\n";
+        let formula = parse_dimacs_cnf(text).unwrap();
+        assert_eq!(formula.num_variables, 3);
+        assert_eq!(formula.clauses, vec![vec![1, -3], vec![2, 3, -1]]);
+        let written = write_dimacs_cnf(&formula);
+        assert_eq!(written, "p cnf 3 2\n1 -3 0\n2 3 -1 0\n");
+        assert_eq!(parse_dimacs_cnf(&written), Ok(formula));
+
+        assert_eq!(
+            parse_dimacs_cnf("p cnf 2 1\n1 -3 0"),
+            Err(ParseError::new(2, 3, "variable -3 is not between 1 and 2"))
+        );
+        assert_eq!(
+            parse_dimacs_cnf("p cnf 2 1\n1 x 0"),
+            Err(ParseError::new(2, 3, "invalid literal `x`"))
+        );
+        assert_eq!(
+            parse_dimacs_cnf("p cnf 2 1\n1 2"),
+            Err(ParseError::new(3, 1, "the last clause is not ended by `0`"))
+        );
+        assert_eq!(
+            parse_dimacs_cnf("p cnf 2 2\n1 2 0"),
+            Err(ParseError::new(1, 9, "expected 2 clauses, found 1"))
+        );
+        assert_eq!(
+            parse_dimacs_cnf("p sp 2 2\n"),
+            Err(ParseError::new(
+                1,
+                3,
+                "expected problem kind `cnf`, found `sp`"
+            ))
+        );
+    }
+}
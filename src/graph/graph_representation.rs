@@ -0,0 +1,188 @@
+/*
+A common interface for the different ways a graph can be stored.
+
+Algorithms in this module that are generic over `Graph` work the same way
+whether the graph is kept in a `BTreeMap` of neighbours (handy to build by
+hand), in a `CompressedSparseRowGraph` (compact and fast once it is built), or
+in a `data_structures::DirectedGraph` or `UndirectedGraph` (given by
+reference, with `&str` vertices).
+
+Edges are directed: an undirected graph is expected to store every edge in
+both directions. Every vertex, even the isolated ones, should have its own
+entry in the graph (possibly with no neighbours); the algorithms that need
+every vertex up front also accept vertices that only appear as the
+destination of an edge, with `all_vertices`.
+*/
+
+use std::collections::{BTreeMap, BTreeSet};
+
+/// For each vertex, the map from its neighbours to the weight of the edge
+/// going to them
+pub type AdjacencyMap<V, E> = BTreeMap<V, BTreeMap<V, E>>;
+
+/// For each vertex, the list of its out-going edges and their weights
+pub type AdjacencyList<V, E> = BTreeMap<V, Vec<(V, E)>>;
+
+/// For each reachable vertex, its predecessor on a shortest path from the
+/// start and its distance from the start. The start itself maps to None.
+pub type PredecessorMap<V, E> = BTreeMap<V, Option<(V, E)>>;
+
+pub trait Graph {
+    type Vertex: Ord + Copy;
+    type Weight: Copy;
+
+    fn num_vertices(&self) -> usize;
+
+    fn vertices(&self) -> impl Iterator<Item = Self::Vertex> + '_;
+
+    /// Out-going edges of `vertex`, as (neighbour, weight) pairs. A vertex
+    /// that is not in the graph has no neighbours.
+    fn neighbors(
+        &self,
+        vertex: Self::Vertex,
+    ) -> impl Iterator<Item = (Self::Vertex, Self::Weight)> + '_;
+
+    fn contains_vertex(&self, vertex: Self::Vertex) -> bool;
+
+    fn num_edges(&self) -> usize {
+        self.vertices().map(|u| self.neighbors(u).count()).sum()
+    }
+
+    /// Every edge of the graph as a (source, destination, weight) triple
+    fn edges(&self) -> impl Iterator<Item = (Self::Vertex, Self::Vertex, Self::Weight)> + '_ {
+        self.vertices()
+            .flat_map(move |u| self.neighbors(u).map(move |(v, w)| (u, v, w)))
+    }
+}
+
+/// Every vertex of the graph, including the ones that only appear as the
+/// destination of an edge, without an entry of their own
+pub fn all_vertices<G: Graph>(graph: &G) -> BTreeSet<G::Vertex> {
+    graph
+        .vertices()
+        .chain(graph.edges().map(|(_, v, _)| v))
+        .collect()
+}
+
+/// The graph with the direction of every edge flipped
+pub fn reverse_graph<G: Graph>(graph: &G) -> AdjacencyList<G::Vertex, G::Weight> {
+    let mut reversed: AdjacencyList<G::Vertex, G::Weight> =
+        graph.vertices().map(|v| (v, Vec::new())).collect();
+    for (u, v, w) in graph.edges() {
+        reversed.entry(v).or_default().push((u, w));
+    }
+    reversed
+}
+
+impl<V: Ord + Copy, E: Copy> Graph for AdjacencyMap<V, E> {
+    type Vertex = V;
+    type Weight = E;
+
+    fn num_vertices(&self) -> usize {
+        self.len()
+    }
+
+    fn vertices(&self) -> impl Iterator<Item = V> + '_ {
+        self.keys().copied()
+    }
+
+    fn neighbors(&self, vertex: V) -> impl Iterator<Item = (V, E)> + '_ {
+        self.get(&vertex)
+            .into_iter()
+            .flat_map(|edges| edges.iter().map(|(v, w)| (*v, *w)))
+    }
+
+    fn contains_vertex(&self, vertex: V) -> bool {
+        self.contains_key(&vertex)
+    }
+}
+
+impl<V: Ord + Copy, E: Copy> Graph for AdjacencyList<V, E> {
+    type Vertex = V;
+    type Weight = E;
+
+    fn num_vertices(&self) -> usize {
+        self.len()
+    }
+
+    fn vertices(&self) -> impl Iterator<Item = V> + '_ {
+        self.keys().copied()
+    }
+
+    fn neighbors(&self, vertex: V) -> impl Iterator<Item = (V, E)> + '_ {
+        self.get(&vertex).into_iter().flatten().copied()
+    }
+
+    fn contains_vertex(&self, vertex: V) -> bool {
+        self.contains_key(&vertex)
+    }
+}
+
+// Unweighted graphs, as used by `enumerate_graph` and `prufer_decode`
+impl<V: Ord + Copy> Graph for BTreeMap<V, Vec<V>> {
+    type Vertex = V;
+    type Weight = ();
+
+    fn num_vertices(&self) -> usize {
+        self.len()
+    }
+
+    fn vertices(&self) -> impl Iterator<Item = V> + '_ {
+        self.keys().copied()
+    }
+
+    fn neighbors(&self, vertex: V) -> impl Iterator<Item = (V, ())> + '_ {
+        self.get(&vertex)
+            .into_iter()
+            .flat_map(|edges| edges.iter().map(|v| (*v, ())))
+    }
+
+    fn contains_vertex(&self, vertex: V) -> bool {
+        self.contains_key(&vertex)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_graph_in_every_representation() {
+        let edges = [(1, 2, 5), (1, 3, 7), (2, 3, 1), (3, 1, 2)];
+        let mut map: AdjacencyMap<u32, i32> = BTreeMap::new();
+        let mut list: AdjacencyList<u32, i32> = BTreeMap::new();
+        let mut unweighted: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
+        for &(u, v, w) in edges.iter() {
+            map.entry(u).or_default().insert(v, w);
+            list.entry(u).or_default().push((v, w));
+            unweighted.entry(u).or_default().push(v);
+        }
+
+        assert_eq!(map.num_vertices(), 3);
+        assert_eq!(list.num_vertices(), 3);
+        assert_eq!(unweighted.num_vertices(), 3);
+        assert_eq!(map.num_edges(), 4);
+        assert_eq!(map.edges().collect::<Vec<_>>(), edges.to_vec());
+        assert_eq!(list.edges().collect::<Vec<_>>(), edges.to_vec());
+        assert_eq!(
+            unweighted
+                .edges()
+                .map(|(u, v, _)| (u, v))
+                .collect::<Vec<_>>(),
+            edges.iter().map(|&(u, v, _)| (u, v)).collect::<Vec<_>>()
+        );
+        assert!(map.contains_vertex(2));
+        assert!(!map.contains_vertex(4));
+        assert_eq!(map.neighbors(4).count(), 0);
+
+        let reversed = reverse_graph(&map);
+        assert_eq!(reversed.num_vertices(), 3);
+        assert_eq!(reversed[&3], vec![(1, 7), (2, 1)]);
+        assert_eq!(reversed.num_edges(), 4);
+
+        let mut sink_only: AdjacencyList<u32, i32> = BTreeMap::new();
+        sink_only.insert(1, vec![(2, 3)]);
+        assert_eq!(sink_only.num_vertices(), 1);
+        assert_eq!(all_vertices(&sink_only), BTreeSet::from([1, 2]));
+    }
+}
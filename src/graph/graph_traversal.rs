@@ -0,0 +1,400 @@
+/*
+Depth-first and breadth-first traversals that report what they see to a
+visitor, so other algorithms can be written on top of them instead of
+re-implementing the search.
+
+Both are iterative (the DFS keeps its own stack of neighbour iterators), so a
+deep graph, like a path of a million vertices, doesn't overflow the call
+stack.
+
+The DFS classifies every edge u -> v it examines:
+- tree edge: v is discovered through it,
+- back edge: v is an ancestor of u still being explored (a cycle in a
+  directed graph),
+- forward edge: v is an already finished descendant of u,
+- cross edge: v is an already finished vertex in another branch or tree.
+In an undirected graph (edges stored in both directions), the edge back to
+the parent is also reported as a back edge.
+
+The visitor returns a `Control` to go on, skip the neighbours of the vertex
+just discovered, or stop the whole traversal.
+*/
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use super::Graph;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Control {
+    Continue,
+    // Returned on a `Discover` event: don't look at the edges of that vertex
+    // (it is still finished). Same as `Continue` for the other events.
+    Prune,
+    Stop,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DfsEvent<V, E> {
+    Discover(V),
+    // All the edges of the vertex have been examined
+    Finish(V),
+    TreeEdge(V, V, E),
+    BackEdge(V, V, E),
+    ForwardEdge(V, V, E),
+    CrossEdge(V, V, E),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BfsEvent<V, E> {
+    // The vertex is seen for the first time, and put in the queue
+    Discover(V),
+    // All the edges of the vertex have been examined
+    Finish(V),
+    TreeEdge(V, V, E),
+    // An edge to an already discovered vertex
+    NonTreeEdge(V, V, E),
+}
+
+// Runs a DFS from each of `starts` that hasn't been discovered yet (give it
+// `graph.vertices()` to go through the whole graph). Returns true if the
+// visitor stopped it.
+pub fn depth_first_traversal<G: Graph>(
+    graph: &G,
+    starts: impl IntoIterator<Item = G::Vertex>,
+    mut visitor: impl FnMut(DfsEvent<G::Vertex, G::Weight>) -> Control,
+) -> bool {
+    // The discovery time of every vertex seen
+    let mut discovered: BTreeMap<G::Vertex, usize> = BTreeMap::new();
+    let mut finished: BTreeSet<G::Vertex> = BTreeSet::new();
+
+    for start in starts {
+        if discovered.contains_key(&start) {
+            continue;
+        }
+        let mut stack = Vec::new();
+        let mut next = Some(start);
+        loop {
+            // Discover the vertex found in the last step, if any
+            if let Some(v) = next.take() {
+                discovered.insert(v, discovered.len());
+                match visitor(DfsEvent::Discover(v)) {
+                    Control::Stop => return true,
+                    pruned => stack.push((v, graph.neighbors(v), pruned == Control::Prune)),
+                }
+            }
+            let (u, neighbors, pruned) = match stack.last_mut() {
+                Some(top) => top,
+                None => break,
+            };
+            let u = *u;
+            let edge = if *pruned { None } else { neighbors.next() };
+            let event = match edge {
+                None => {
+                    stack.pop();
+                    finished.insert(u);
+                    DfsEvent::Finish(u)
+                }
+                Some((v, w)) => match discovered.get(&v) {
+                    None => {
+                        next = Some(v);
+                        DfsEvent::TreeEdge(u, v, w)
+                    }
+                    Some(_) if !finished.contains(&v) => DfsEvent::BackEdge(u, v, w),
+                    Some(&time) if discovered[&u] < time => DfsEvent::ForwardEdge(u, v, w),
+                    Some(_) => DfsEvent::CrossEdge(u, v, w),
+                },
+            };
+            if visitor(event) == Control::Stop {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+// Runs a BFS from `start`. Returns true if the visitor stopped it.
+pub fn breadth_first_traversal<G: Graph>(
+    graph: &G,
+    start: G::Vertex,
+    mut visitor: impl FnMut(BfsEvent<G::Vertex, G::Weight>) -> Control,
+) -> bool {
+    let mut discovered = BTreeSet::new();
+    // Each vertex in the queue, and whether its edges should be examined
+    let mut queue = VecDeque::new();
+
+    discovered.insert(start);
+    match visitor(BfsEvent::Discover(start)) {
+        Control::Stop => return true,
+        control => queue.push_back((start, control != Control::Prune)),
+    }
+    while let Some((u, explore)) = queue.pop_front() {
+        for (v, w) in graph.neighbors(u).filter(|_| explore) {
+            if !discovered.insert(v) {
+                if visitor(BfsEvent::NonTreeEdge(u, v, w)) == Control::Stop {
+                    return true;
+                }
+                continue;
+            }
+            if visitor(BfsEvent::TreeEdge(u, v, w)) == Control::Stop {
+                return true;
+            }
+            match visitor(BfsEvent::Discover(v)) {
+                Control::Stop => return true,
+                control => queue.push_back((v, control != Control::Prune)),
+            }
+        }
+        if visitor(BfsEvent::Finish(u)) == Control::Stop {
+            return true;
+        }
+    }
+    false
+}
+
+// A cycle of a directed graph, as its vertices in order (the edge from the
+// last one back to the first closes it), or None if the graph is acyclic
+pub fn find_cycle<G: Graph>(graph: &G) -> Option<Vec<G::Vertex>> {
+    let mut parent: BTreeMap<G::Vertex, G::Vertex> = BTreeMap::new();
+    let mut cycle = None;
+    depth_first_traversal(graph, graph.vertices(), |event| match event {
+        DfsEvent::TreeEdge(u, v, _) => {
+            parent.insert(v, u);
+            Control::Continue
+        }
+        DfsEvent::BackEdge(u, v, _) => {
+            // v is an ancestor of u: walk the tree back up from u to v
+            let mut path = vec![u];
+            let mut current = u;
+            while current != v {
+                current = parent[&current];
+                path.push(current);
+            }
+            path.reverse();
+            cycle = Some(path);
+            Control::Stop
+        }
+        _ => Control::Continue,
+    });
+    cycle
+}
+
+// The parent of every vertex reachable from root in a DFS tree (None for the
+// root)
+pub fn depth_first_tree<G: Graph>(
+    graph: &G,
+    root: G::Vertex,
+) -> BTreeMap<G::Vertex, Option<G::Vertex>> {
+    let mut parent = BTreeMap::new();
+    parent.insert(root, None);
+    depth_first_traversal(graph, [root], |event| {
+        if let DfsEvent::TreeEdge(u, v, _) = event {
+            parent.insert(v, Some(u));
+        }
+        Control::Continue
+    });
+    parent
+}
+
+// The vertices reachable from root, grouped by their number of edges from it
+pub fn breadth_first_levels<G: Graph>(graph: &G, root: G::Vertex) -> Vec<Vec<G::Vertex>> {
+    let mut level: BTreeMap<G::Vertex, usize> = BTreeMap::new();
+    let mut levels = vec![vec![root]];
+    level.insert(root, 0);
+    breadth_first_traversal(graph, root, |event| {
+        if let BfsEvent::TreeEdge(u, v, _) = event {
+            let l = level[&u] + 1;
+            level.insert(v, l);
+            if levels.len() == l {
+                levels.push(vec![]);
+            }
+            levels[l].push(v);
+        }
+        Control::Continue
+    });
+    levels
+}
+
+// A side (false or true) for every vertex of an undirected graph such that
+// every edge links the two sides, or None if the graph isn't bipartite (it
+// has an odd cycle)
+pub fn bipartition<G: Graph>(graph: &G) -> Option<BTreeMap<G::Vertex, bool>> {
+    let mut side: BTreeMap<G::Vertex, bool> = BTreeMap::new();
+    let mut bipartite = true;
+    for start in graph.vertices() {
+        if side.contains_key(&start) {
+            continue;
+        }
+        side.insert(start, false);
+        breadth_first_traversal(graph, start, |event| match event {
+            BfsEvent::TreeEdge(u, v, _) => {
+                side.insert(v, !side[&u]);
+                Control::Continue
+            }
+            BfsEvent::NonTreeEdge(u, v, _) if side[&u] == side[&v] => {
+                bipartite = false;
+                Control::Stop
+            }
+            _ => Control::Continue,
+        });
+        if !bipartite {
+            return None;
+        }
+    }
+    Some(side)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{grid_graph, random_tree, AdjacencyList};
+    use crate::math::PCG32;
+
+    fn from_edges(n: u32, edges: &[(u32, u32)]) -> BTreeMap<u32, Vec<u32>> {
+        let mut graph: BTreeMap<u32, Vec<u32>> = (1..=n).map(|v| (v, vec![])).collect();
+        for &(u, v) in edges {
+            graph.get_mut(&u).unwrap().push(v);
+        }
+        graph
+    }
+
+    #[test]
+    fn dfs_events() {
+        //  1 -> 2 -> 3 -> 1 (back)
+        //  1 -> 3 (forward), 4 -> 2 (cross)
+        let graph = from_edges(4, &[(1, 2), (1, 3), (2, 3), (3, 1), (4, 2)]);
+        let mut events = vec![];
+        let stopped = depth_first_traversal(&graph, graph.vertices(), |event| {
+            events.push(event);
+            Control::Continue
+        });
+        assert!(!stopped);
+        use DfsEvent::*;
+        assert_eq!(
+            events,
+            vec![
+                Discover(1),
+                TreeEdge(1, 2, ()),
+                Discover(2),
+                TreeEdge(2, 3, ()),
+                Discover(3),
+                BackEdge(3, 1, ()),
+                Finish(3),
+                Finish(2),
+                ForwardEdge(1, 3, ()),
+                Finish(1),
+                Discover(4),
+                CrossEdge(4, 2, ()),
+                Finish(4),
+            ]
+        );
+    }
+
+    #[test]
+    fn prune_and_stop() {
+        let graph = from_edges(5, &[(1, 2), (2, 3), (1, 4), (4, 5)]);
+        let mut discovered = vec![];
+        depth_first_traversal(&graph, [1], |event| match event {
+            DfsEvent::Discover(v) => {
+                discovered.push(v);
+                if v == 2 {
+                    Control::Prune
+                } else {
+                    Control::Continue
+                }
+            }
+            _ => Control::Continue,
+        });
+        assert_eq!(discovered, vec![1, 2, 4, 5]);
+
+        let mut seen = 0;
+        let stopped = breadth_first_traversal(&graph, 1, |event| {
+            if let BfsEvent::Discover(_) = event {
+                seen += 1;
+            }
+            if event == BfsEvent::Discover(4) {
+                Control::Stop
+            } else {
+                Control::Continue
+            }
+        });
+        assert!(stopped);
+        assert_eq!(seen, 3);
+    }
+
+    #[test]
+    fn bfs_events() {
+        let graph = from_edges(4, &[(1, 2), (1, 3), (2, 4), (3, 4), (4, 1)]);
+        let mut events = vec![];
+        breadth_first_traversal(&graph, 1, |event| {
+            events.push(event);
+            Control::Continue
+        });
+        use BfsEvent::*;
+        assert_eq!(
+            events,
+            vec![
+                Discover(1),
+                TreeEdge(1, 2, ()),
+                Discover(2),
+                TreeEdge(1, 3, ()),
+                Discover(3),
+                Finish(1),
+                TreeEdge(2, 4, ()),
+                Discover(4),
+                Finish(2),
+                NonTreeEdge(3, 4, ()),
+                Finish(3),
+                NonTreeEdge(4, 1, ()),
+                Finish(4),
+            ]
+        );
+    }
+
+    #[test]
+    fn cycles() {
+        let acyclic = from_edges(5, &[(1, 2), (1, 3), (2, 4), (3, 4), (4, 5)]);
+        assert_eq!(find_cycle(&acyclic), None);
+
+        let cyclic = from_edges(5, &[(1, 2), (2, 3), (3, 4), (4, 2), (4, 5)]);
+        assert_eq!(find_cycle(&cyclic), Some(vec![2, 3, 4]));
+
+        let mut self_loop: AdjacencyList<char, i32> = BTreeMap::new();
+        self_loop.insert('a', vec![('a', 1)]);
+        assert_eq!(find_cycle(&self_loop), Some(vec!['a']));
+    }
+
+    #[test]
+    fn trees_levels_and_bipartition() {
+        let grid = grid_graph(3, 4);
+        let levels = breadth_first_levels(&grid, 1);
+        assert_eq!(levels.len(), 6);
+        assert_eq!(levels[1], vec![2, 5]);
+        assert_eq!(levels[5], vec![12]);
+
+        let parent = depth_first_tree(&grid, 1);
+        assert_eq!(parent.len(), 12);
+        assert_eq!(parent[&1], None);
+
+        let sides = bipartition(&grid).unwrap();
+        for (u, neighbors) in grid.iter() {
+            assert!(neighbors.iter().all(|v| sides[u] != sides[v]));
+        }
+
+        let mut rng = PCG32::new_default(4);
+        assert!(bipartition(&random_tree(50, &mut rng)).is_some());
+        let triangle = from_edges(3, &[(1, 2), (2, 1), (2, 3), (3, 2), (3, 1), (1, 3)]);
+        assert_eq!(bipartition(&triangle), None);
+    }
+
+    #[test]
+    fn deep_graph() {
+        // a recursive DFS would overflow the stack on such a long path
+        let n = 200_000;
+        let graph: BTreeMap<u32, Vec<u32>> = (1..=n)
+            .map(|v| (v, if v < n { vec![v + 1] } else { vec![] }))
+            .collect();
+        let parent = depth_first_tree(&graph, 1);
+        assert_eq!(parent[&n], Some(n - 1));
+        assert_eq!(find_cycle(&graph), None);
+        assert_eq!(breadth_first_levels(&graph, 1).len(), n as usize);
+    }
+}
@@ -31,6 +31,13 @@ pub struct HeavyLightDecomposition {
     // the other ones. If `v` is a leaf, big_child[v] = 0
     pub big_child: Vec<usize>,
 
+    // The parent of each vertex (0 for the root), its depth (the root has
+    // depth 0) and the number of vertices in its subtree. The subtree of `v`
+    // takes the positions from position[v] to position[v] + subtree_size[v] - 1
+    pub parent: Vec<usize>,
+    pub depth: Vec<usize>,
+    pub subtree_size: Vec<usize>,
+
     // Used internally to fill `position` Vec
     current_position: usize,
 }
@@ -42,10 +49,19 @@ impl HeavyLightDecomposition {
             position: vec![0; num_vertices],
             head: vec![0; num_vertices],
             big_child: vec![0; num_vertices],
+            parent: vec![0; num_vertices],
+            depth: vec![0; num_vertices],
+            subtree_size: vec![0; num_vertices],
             current_position: 1,
         }
     }
     fn dfs(&mut self, v: usize, parent: usize, adj: &Adj) -> usize {
+        self.parent[v] = parent;
+        self.depth[v] = if parent == 0 {
+            0
+        } else {
+            self.depth[parent] + 1
+        };
         let mut big_child = 0usize;
         let mut bc_size = 0usize; // big child size
         let mut subtree_size = 1usize; // size of this subtree
@@ -61,6 +77,7 @@ impl HeavyLightDecomposition {
             }
         }
         self.big_child[v] = big_child;
+        self.subtree_size[v] = subtree_size;
         subtree_size
     }
     pub fn decompose(&mut self, root: usize, adj: &Adj) {
@@ -85,6 +102,38 @@ impl HeavyLightDecomposition {
             self.decompose_path(u, v, u, adj);
         }
     }
+
+    // Splits the path between u and v into O(lg(n)) ranges of consecutive
+    // positions, given as (first, last) with first <= last
+    pub fn path_ranges(&self, mut u: usize, mut v: usize) -> Vec<(usize, usize)> {
+        let mut ranges = vec![];
+        while self.head[u] != self.head[v] {
+            // Move up from the one whose path starts deeper
+            if self.depth[self.head[u]] < self.depth[self.head[v]] {
+                std::mem::swap(&mut u, &mut v);
+            }
+            ranges.push((self.position[self.head[u]], self.position[u]));
+            u = self.parent[self.head[u]];
+        }
+        let (a, b) = (self.position[u], self.position[v]);
+        ranges.push((a.min(b), a.max(b)));
+        ranges
+    }
+
+    // The lowest common ancestor of u and v
+    pub fn lowest_common_ancestor(&self, mut u: usize, mut v: usize) -> usize {
+        while self.head[u] != self.head[v] {
+            if self.depth[self.head[u]] < self.depth[self.head[v]] {
+                std::mem::swap(&mut u, &mut v);
+            }
+            u = self.parent[self.head[u]];
+        }
+        if self.depth[u] < self.depth[v] {
+            u
+        } else {
+            v
+        }
+    }
 }
 
 #[cfg(test)]
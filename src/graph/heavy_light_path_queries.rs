@@ -0,0 +1,334 @@
+/*
+Path and subtree queries on a tree, with `HeavyLightDecomposition` and a
+`LazySegmentTree` over the positions it gives to the vertices.
+
+Every vertex holds a value. The path between two vertices is split into
+O(lg(n)) ranges of positions, so aggregating or updating the values along a
+path takes O(lg(n)^2); a subtree is always a single range, so it takes
+O(lg(n)).
+
+What is aggregated and how values are updated is defined by the `LazyMonoid`
+(for example `RangeSum`, `RangeMin` or `RangeMax`, or a custom one). Path
+queries combine the values in the order of the path, from u to v: the ranges
+on the way up from u are aggregated from the top down, like everything in the
+segment tree, and turned around with `LazyMonoid::reverse`, so a monoid whose
+`combine` isn't commutative should implement it.
+
+The tree vertices are numbered from 1 to n, like for
+`HeavyLightDecomposition`.
+*/
+
+use super::HeavyLightDecomposition;
+use crate::data_structures::{LazyMonoid, LazySegmentTree};
+
+pub struct HeavyLightPathQueries<M: LazyMonoid> {
+    pub hld: HeavyLightDecomposition,
+    // The value of vertex v is at index position[v] - 1
+    tree: LazySegmentTree<M>,
+}
+
+impl<M: LazyMonoid> HeavyLightPathQueries<M> {
+    // `values[v]` is the initial value of vertex v (values[0] is ignored)
+    pub fn new(root: usize, adj: &[Vec<usize>], values: &[M::Value]) -> Self {
+        let num_vertices = adj.len() - 1;
+        let mut hld = HeavyLightDecomposition::new(num_vertices);
+        hld.decompose(root, adj);
+        let mut by_position = vec![M::identity(); num_vertices];
+        for v in 1..=num_vertices {
+            by_position[hld.position[v] - 1] = values[v];
+        }
+        HeavyLightPathQueries {
+            hld,
+            tree: LazySegmentTree::from_vec(&by_position),
+        }
+    }
+
+    // The aggregate of the values on the path from u to v (both included)
+    pub fn path_query(&mut self, mut u: usize, mut v: usize) -> M::Value {
+        let hld = &self.hld;
+        // The part of the path from the lowest common ancestor down to u, and
+        // the one down to v, both aggregated from the top down. Like in
+        // `path_ranges`, the one whose path starts deeper moves up.
+        let mut u_side = M::identity();
+        let mut v_side = M::identity();
+        while hld.head[u] != hld.head[v] {
+            if hld.depth[hld.head[u]] >= hld.depth[hld.head[v]] {
+                let range = self
+                    .tree
+                    .query(hld.position[hld.head[u]] - 1, hld.position[u] - 1);
+                u_side = M::combine(range, u_side);
+                u = hld.parent[hld.head[u]];
+            } else {
+                let range = self
+                    .tree
+                    .query(hld.position[hld.head[v]] - 1, hld.position[v] - 1);
+                v_side = M::combine(range, v_side);
+                v = hld.parent[hld.head[v]];
+            }
+        }
+        // The lowest common ancestor is the higher of u and v
+        if hld.depth[u] >= hld.depth[v] {
+            let range = self.tree.query(hld.position[v] - 1, hld.position[u] - 1);
+            u_side = M::combine(range, u_side);
+        } else {
+            let range = self.tree.query(hld.position[u] - 1, hld.position[v] - 1);
+            v_side = M::combine(range, v_side);
+        }
+        M::combine(M::reverse(u_side), v_side)
+    }
+
+    // Applies `update` to the value of every vertex on the path from u to v
+    pub fn path_update(&mut self, u: usize, v: usize, update: M::Update) {
+        for (first, last) in self.hld.path_ranges(u, v) {
+            self.tree.update(first - 1, last - 1, update);
+        }
+    }
+
+    // The aggregate of the values in the subtree of v (v included)
+    pub fn subtree_query(&mut self, v: usize) -> M::Value {
+        let first = self.hld.position[v] - 1;
+        self.tree.query(first, first + self.hld.subtree_size[v] - 1)
+    }
+
+    // Applies `update` to the value of every vertex in the subtree of v
+    pub fn subtree_update(&mut self, v: usize, update: M::Update) {
+        let first = self.hld.position[v] - 1;
+        self.tree
+            .update(first, first + self.hld.subtree_size[v] - 1, update);
+    }
+
+    // The value of vertex v
+    pub fn value(&mut self, v: usize) -> M::Value {
+        let position = self.hld.position[v] - 1;
+        self.tree.query(position, position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_structures::{RangeMax, RangeSum};
+    use crate::math::PCG32;
+
+    // The vertices on the path from u to v, by walking up from the deeper one
+    fn naive_path(parent: &[usize], depth: &[usize], mut u: usize, mut v: usize) -> Vec<usize> {
+        let mut path = vec![];
+        while u != v {
+            if depth[u] >= depth[v] {
+                path.push(u);
+                u = parent[u];
+            } else {
+                path.push(v);
+                v = parent[v];
+            }
+        }
+        path.push(u);
+        path
+    }
+
+    // The vertices on the path from u to v, in order
+    fn ordered_path(parent: &[usize], depth: &[usize], mut u: usize, mut v: usize) -> Vec<usize> {
+        let mut from_u = vec![];
+        let mut from_v = vec![];
+        while u != v {
+            if depth[u] >= depth[v] {
+                from_u.push(u);
+                u = parent[u];
+            } else {
+                from_v.push(v);
+                v = parent[v];
+            }
+        }
+        from_u.push(u);
+        from_u.extend(from_v.into_iter().rev());
+        from_u
+    }
+
+    // A polynomial hash of the sequence of values, forward and backward, and
+    // its length. Updates add to every value of a range, which changes the
+    // hashes by that much times the sum of the powers of the base.
+    struct PathHash;
+
+    const BASE: u64 = 1_000_003;
+
+    fn hash_of_ones(len: u64) -> u64 {
+        (0..len).fold(0, |hash: u64, _| hash.wrapping_mul(BASE).wrapping_add(1))
+    }
+
+    impl LazyMonoid for PathHash {
+        // (forward hash, backward hash, length)
+        type Value = (u64, u64, u64);
+        type Update = u64;
+
+        fn identity() -> (u64, u64, u64) {
+            (0, 0, 0)
+        }
+        fn combine(left: (u64, u64, u64), right: (u64, u64, u64)) -> (u64, u64, u64) {
+            (
+                left.0
+                    .wrapping_mul(BASE.wrapping_pow(right.2 as u32))
+                    .wrapping_add(right.0),
+                right
+                    .1
+                    .wrapping_mul(BASE.wrapping_pow(left.2 as u32))
+                    .wrapping_add(left.1),
+                left.2 + right.2,
+            )
+        }
+        fn reverse(value: (u64, u64, u64)) -> (u64, u64, u64) {
+            (value.1, value.0, value.2)
+        }
+        fn no_update() -> u64 {
+            0
+        }
+        fn compose(newer: u64, older: u64) -> u64 {
+            newer.wrapping_add(older)
+        }
+        fn apply(value: (u64, u64, u64), update: u64, _len: usize) -> (u64, u64, u64) {
+            let added = update.wrapping_mul(hash_of_ones(value.2));
+            (
+                value.0.wrapping_add(added),
+                value.1.wrapping_add(added),
+                value.2,
+            )
+        }
+    }
+
+    fn hash_sequence(values: impl Iterator<Item = u64>) -> u64 {
+        values.fold(0, |hash, x| hash.wrapping_mul(BASE).wrapping_add(x))
+    }
+
+    fn random_tree(rng: &mut PCG32, n: usize) -> (Vec<Vec<usize>>, Vec<usize>, Vec<usize>) {
+        let mut adj = vec![vec![]; n + 1];
+        let mut parent = vec![0; n + 1];
+        let mut depth = vec![0; n + 1];
+        for v in 2..=n {
+            let p = rng.get_u32() as usize % (v - 1) + 1;
+            adj[p].push(v);
+            adj[v].push(p);
+            parent[v] = p;
+            depth[v] = depth[p] + 1;
+        }
+        (adj, parent, depth)
+    }
+
+    #[test]
+    fn small_tree() {
+        //       1
+        //     /   \
+        //    2     3
+        //   / \     \
+        //  4   5     6
+        let adj = vec![
+            vec![],
+            vec![2, 3],
+            vec![1, 4, 5],
+            vec![1, 6],
+            vec![2],
+            vec![2],
+            vec![3],
+        ];
+        let values = vec![0, 1, 2, 3, 4, 5, 6];
+        let mut sums: HeavyLightPathQueries<RangeSum<i32>> =
+            HeavyLightPathQueries::new(1, &adj, &values);
+        assert_eq!(sums.path_query(4, 6), 4 + 2 + 1 + 3 + 6);
+        assert_eq!(sums.path_query(4, 5), 4 + 2 + 5);
+        assert_eq!(sums.path_query(3, 3), 3);
+        assert_eq!(sums.subtree_query(2), 2 + 4 + 5);
+        assert_eq!(sums.hld.lowest_common_ancestor(5, 6), 1);
+
+        sums.path_update(5, 3, 10);
+        assert_eq!(sums.subtree_query(1), 21 + 40);
+        sums.subtree_update(3, -1);
+        assert_eq!(sums.value(6), 5);
+        assert_eq!(sums.value(3), 12);
+        assert_eq!(sums.path_query(4, 6), 4 + 12 + 11 + 12 + 5);
+    }
+
+    #[test]
+    fn same_as_naive() {
+        let mut rng = PCG32::new_default(2024);
+        for _ in 0..10 {
+            let n = 1 + rng.get_u32() as usize % 80;
+            let (adj, parent, depth) = random_tree(&mut rng, n);
+            let mut naive: Vec<i64> = (0..=n).map(|_| (rng.get_u32() % 100) as i64).collect();
+            let initial: Vec<Option<i64>> = naive.iter().map(|&x| Some(x)).collect();
+            let mut sums: HeavyLightPathQueries<RangeSum<i64>> =
+                HeavyLightPathQueries::new(1, &adj, &naive);
+            let mut maxs: HeavyLightPathQueries<RangeMax<i64>> =
+                HeavyLightPathQueries::new(1, &adj, &initial);
+
+            for _ in 0..200 {
+                let u = rng.get_u32() as usize % n + 1;
+                let v = rng.get_u32() as usize % n + 1;
+                let path = naive_path(&parent, &depth, u, v);
+                let subtree: Vec<usize> = (1..=n)
+                    .filter(|&w| naive_path(&parent, &depth, w, 1).contains(&u))
+                    .collect();
+                let x = (rng.get_u32() % 21) as i64 - 10;
+                match rng.get_u32() % 4 {
+                    0 => {
+                        sums.path_update(u, v, x);
+                        maxs.path_update(u, v, x);
+                        path.iter().for_each(|&w| naive[w] += x);
+                    }
+                    1 => {
+                        sums.subtree_update(u, x);
+                        maxs.subtree_update(u, x);
+                        subtree.iter().for_each(|&w| naive[w] += x);
+                    }
+                    2 => {
+                        assert_eq!(
+                            sums.path_query(u, v),
+                            path.iter().map(|&w| naive[w]).sum::<i64>()
+                        );
+                        assert_eq!(maxs.path_query(u, v), path.iter().map(|&w| naive[w]).max());
+                    }
+                    _ => {
+                        assert_eq!(
+                            sums.subtree_query(u),
+                            subtree.iter().map(|&w| naive[w]).sum::<i64>()
+                        );
+                        assert_eq!(
+                            maxs.subtree_query(u),
+                            subtree.iter().map(|&w| naive[w]).max()
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn path_order() {
+        let mut rng = PCG32::new_default(77);
+        for _ in 0..10 {
+            let n = 1 + rng.get_u32() as usize % 60;
+            let (adj, parent, depth) = random_tree(&mut rng, n);
+            let mut naive: Vec<u64> = (0..=n).map(|_| rng.get_u32() as u64).collect();
+            let initial: Vec<(u64, u64, u64)> = naive.iter().map(|&x| (x, x, 1)).collect();
+            let mut hashes: HeavyLightPathQueries<PathHash> =
+                HeavyLightPathQueries::new(1, &adj, &initial);
+
+            for _ in 0..200 {
+                let u = rng.get_u32() as usize % n + 1;
+                let v = rng.get_u32() as usize % n + 1;
+                let path = ordered_path(&parent, &depth, u, v);
+                if rng.get_u32().is_multiple_of(3) {
+                    let x = rng.get_u32() as u64;
+                    hashes.path_update(u, v, x);
+                    path.iter()
+                        .for_each(|&w| naive[w] = naive[w].wrapping_add(x));
+                } else {
+                    let (forward, backward, len) = hashes.path_query(u, v);
+                    assert_eq!(forward, hash_sequence(path.iter().map(|&w| naive[w])));
+                    assert_eq!(
+                        backward,
+                        hash_sequence(path.iter().rev().map(|&w| naive[w]))
+                    );
+                    assert_eq!(len, path.len() as u64);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,278 @@
+/*
+The Hungarian (Kuhn-Munkres) algorithm for the assignment problem.
+
+Given a cost for every (row, column) pair of a bipartite graph, it finds a
+matching of all the rows (or all the columns, whichever there are fewer of)
+whose total cost is as small as possible, in O(n^2.m).
+
+It keeps a potential for every row and column, such that no cost is below the
+sum of the potentials of its row and column, and only uses pairs where the
+cost is equal to that sum. Rows are added one by one: a shortest augmenting
+path from the new row is found dijkstra-style, and the potentials are shifted
+so that it only uses such tight pairs.
+
+Costs can be integers or floats; they don't need to be positive.
+*/
+
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+
+/// The pairs of a matching, and the sum of their costs (or weights)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assignment<T> {
+    /// (row, column) pairs, sorted by row
+    pub pairs: Vec<(usize, usize)>,
+    pub total: T,
+}
+
+// Rows and columns are numbered from 1 here, 0 is a fake column used as the
+// start of every augmenting path. Needs rows <= columns.
+fn solve<T>(costs: &[Vec<T>], rows: usize, columns: usize) -> Vec<usize>
+where
+    T: Copy + PartialOrd + Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign + Default,
+{
+    let mut row_potential = vec![T::default(); rows + 1];
+    let mut column_potential = vec![T::default(); columns + 1];
+    // matched_row[j] is the row matched with column j (0 if it is free)
+    let mut matched_row = vec![0; columns + 1];
+    // the previous column on the augmenting path
+    let mut way = vec![0; columns + 1];
+
+    for row in 1..=rows {
+        matched_row[0] = row;
+        let mut j0 = 0;
+        // min_slack[j]: the smallest reduced cost of an edge from the rows
+        // of the path to column j
+        let mut min_slack: Vec<Option<T>> = vec![None; columns + 1];
+        let mut used = vec![false; columns + 1];
+        loop {
+            used[j0] = true;
+            let i0 = matched_row[j0];
+            let mut delta: Option<T> = None;
+            let mut j1 = 0;
+            for j in 1..=columns {
+                if used[j] {
+                    continue;
+                }
+                let reduced = costs[i0 - 1][j - 1] - row_potential[i0] - column_potential[j];
+                if min_slack[j].is_none_or(|slack| reduced < slack) {
+                    min_slack[j] = Some(reduced);
+                    way[j] = j0;
+                }
+                if delta.is_none_or(|d| min_slack[j].unwrap() < d) {
+                    delta = min_slack[j];
+                    j1 = j;
+                }
+            }
+            // there are at least as many columns as rows, so one is unused
+            let delta = delta.unwrap();
+            for j in 0..=columns {
+                if used[j] {
+                    row_potential[matched_row[j]] += delta;
+                    column_potential[j] -= delta;
+                } else if let Some(slack) = min_slack[j].as_mut() {
+                    *slack -= delta;
+                }
+            }
+            j0 = j1;
+            if matched_row[j0] == 0 {
+                break;
+            }
+        }
+        // Flip the augmenting path
+        while j0 != 0 {
+            let j1 = way[j0];
+            matched_row[j0] = matched_row[j1];
+            j0 = j1;
+        }
+    }
+    matched_row
+}
+
+/// Matches every row to a different column (or every column to a different
+/// row if there are fewer columns) so that the total cost is minimal.
+/// `costs[i][j]` is the cost of matching row i with column j; all rows must
+/// have the same length.
+pub fn hungarian_min_cost<T>(costs: &[Vec<T>]) -> Assignment<T>
+where
+    T: Copy + PartialOrd + Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign + Default,
+{
+    let rows = costs.len();
+    let columns = costs.first().map_or(0, |row| row.len());
+    let mut pairs = Vec::new();
+    if rows <= columns {
+        let matched_row = solve(costs, rows, columns);
+        for (j, &i) in matched_row.iter().enumerate().skip(1) {
+            if i != 0 {
+                pairs.push((i - 1, j - 1));
+            }
+        }
+    } else {
+        let transposed: Vec<Vec<T>> = (0..columns)
+            .map(|j| costs.iter().map(|row| row[j]).collect())
+            .collect();
+        let matched_column = solve(&transposed, columns, rows);
+        for (i, &j) in matched_column.iter().enumerate().skip(1) {
+            if j != 0 {
+                pairs.push((i - 1, j - 1));
+            }
+        }
+    }
+    pairs.sort_unstable();
+
+    let mut total = T::default();
+    for &(i, j) in pairs.iter() {
+        total += costs[i][j];
+    }
+    Assignment { pairs, total }
+}
+
+/// Same as `hungarian_min_cost`, but the total weight of the matching is made
+/// as large as possible
+pub fn hungarian_max_weight<T>(weights: &[Vec<T>]) -> Assignment<T>
+where
+    T: Copy
+        + PartialOrd
+        + Add<Output = T>
+        + Sub<Output = T>
+        + AddAssign
+        + SubAssign
+        + Neg<Output = T>
+        + Default,
+{
+    let costs: Vec<Vec<T>> = weights
+        .iter()
+        .map(|row| row.iter().map(|&w| -w).collect())
+        .collect();
+    let Assignment { pairs, total } = hungarian_min_cost(&costs);
+    Assignment {
+        pairs,
+        total: -total,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::PCG32;
+
+    // The cheapest assignment of the rows, trying every permutation of the
+    // columns (needs rows <= columns)
+    fn brute_force(costs: &[Vec<i64>]) -> i64 {
+        fn go(costs: &[Vec<i64>], row: usize, used: &mut Vec<bool>) -> i64 {
+            if row == costs.len() {
+                return 0;
+            }
+            let mut best = i64::MAX;
+            for j in 0..used.len() {
+                if !used[j] {
+                    used[j] = true;
+                    best = best.min(costs[row][j] + go(costs, row + 1, used));
+                    used[j] = false;
+                }
+            }
+            best
+        }
+        go(costs, 0, &mut vec![false; costs[0].len()])
+    }
+
+    fn check_pairs<T>(assignment: &Assignment<T>, rows: usize, columns: usize) {
+        assert_eq!(assignment.pairs.len(), rows.min(columns));
+        let mut used_columns = vec![false; columns];
+        for &(i, j) in assignment.pairs.iter() {
+            assert!(i < rows);
+            assert!(!used_columns[j]);
+            used_columns[j] = true;
+        }
+    }
+
+    #[test]
+    fn square_matrix() {
+        let costs = vec![
+            vec![9, 2, 7, 8],
+            vec![6, 4, 3, 7],
+            vec![5, 8, 1, 8],
+            vec![7, 6, 9, 4],
+        ];
+        assert_eq!(
+            hungarian_min_cost(&costs),
+            Assignment {
+                pairs: vec![(0, 1), (1, 0), (2, 2), (3, 3)],
+                total: 13,
+            }
+        );
+    }
+
+    #[test]
+    fn rectangular_matrices() {
+        // more columns than rows: the cheapest columns are picked
+        let costs = vec![vec![4, 1, 4, 9], vec![2, 0, 5, 9]];
+        let assignment = hungarian_min_cost(&costs);
+        assert_eq!(assignment.pairs, vec![(0, 1), (1, 0)]);
+        assert_eq!(assignment.total, 3);
+
+        // more rows than columns: some rows are left out
+        let costs = vec![vec![4, 3], vec![1, 0], vec![3, 5], vec![9, 9]];
+        let assignment = hungarian_min_cost(&costs);
+        assert_eq!(assignment.pairs, vec![(1, 1), (2, 0)]);
+        assert_eq!(assignment.total, 3);
+
+        let empty: Vec<Vec<i32>> = vec![];
+        assert_eq!(hungarian_min_cost(&empty).pairs, vec![]);
+    }
+
+    #[test]
+    fn negative_costs_and_max_weight() {
+        let weights = vec![vec![7, -3, 1], vec![2, 5, -8], vec![0, 4, 6]];
+        // 7 + 5 + 6
+        let best = hungarian_max_weight(&weights);
+        assert_eq!(best.pairs, vec![(0, 0), (1, 1), (2, 2)]);
+        assert_eq!(best.total, 18);
+        // -3 - 8 + 0
+        let cheapest = hungarian_min_cost(&weights);
+        assert_eq!(cheapest.pairs, vec![(0, 1), (1, 2), (2, 0)]);
+        assert_eq!(cheapest.total, -11);
+    }
+
+    #[test]
+    fn float_costs() {
+        let costs: Vec<Vec<f64>> = vec![
+            vec![0.5, 2.25, 1.0],
+            vec![1.5, 0.25, 3.0],
+            vec![2.0, 1.0, 0.75],
+        ];
+        let assignment = hungarian_min_cost(&costs);
+        assert_eq!(assignment.pairs, vec![(0, 0), (1, 1), (2, 2)]);
+        assert!((assignment.total - 1.5).abs() < 1e-9);
+
+        // 2.25 + 3.0 + 2.0
+        let assignment = hungarian_max_weight(&costs);
+        assert_eq!(assignment.pairs, vec![(0, 1), (1, 2), (2, 0)]);
+        assert!((assignment.total - 7.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn same_as_brute_force() {
+        let mut rng = PCG32::new_default(5150);
+        for round in 0..100 {
+            let rows = 1 + round % 6;
+            let columns = rows + (round / 6) % 3;
+            let costs: Vec<Vec<i64>> = (0..rows)
+                .map(|_| {
+                    (0..columns)
+                        .map(|_| (rng.get_u32() % 50) as i64 - 10)
+                        .collect()
+                })
+                .collect();
+            let assignment = hungarian_min_cost(&costs);
+            check_pairs(&assignment, rows, columns);
+            assert_eq!(assignment.total, brute_force(&costs));
+
+            let transposed: Vec<Vec<i64>> = (0..columns)
+                .map(|j| costs.iter().map(|row| row[j]).collect())
+                .collect();
+            let assignment = hungarian_min_cost(&transposed);
+            check_pairs(&assignment, columns, rows);
+            assert_eq!(assignment.total, brute_force(&costs));
+        }
+    }
+}
@@ -0,0 +1,128 @@
+/*
+Johnson's algorithm for the shortest paths between all pairs of vertices of a
+directed graph that may have negative weights (but no negative cycle).
+
+Bellman-Ford gives a potential h for every vertex with h(u) + w >= h(v) for
+every edge (u, v, w) (see `shortest_path_potentials`). Every weight w can then
+be replaced by w + h(u) - h(v), which is not negative, so Dijkstra's algorithm
+can be run from every vertex. Along a path from s to t the potentials cancel
+out: its new weight is its old one plus h(s) - h(t), so the shortest paths are
+the same.
+
+It takes O(n * m * lg(n)), which is better than the O(n^3) of
+`floyd_warshall` on sparse graphs.
+*/
+
+use std::collections::BTreeMap;
+use std::ops::{Add, Sub};
+
+use super::{all_vertices, dijkstra, shortest_path_potentials, AdjacencyList, Graph};
+
+// the distances between all pairs of vertices, or a negative cycle
+type AllPairs<V, E> = Result<BTreeMap<V, BTreeMap<V, E>>, Vec<V>>;
+
+// returns the same map as `floyd_warshall`: map[u][v] is the distance from u
+// to v, for every v reachable from u (u included, at distance zero), or the
+// vertices of a negative cycle (the edge from the last one back to the first
+// closes it). E::default() should be zero.
+pub fn johnson<G: Graph>(graph: &G) -> AllPairs<G::Vertex, G::Weight>
+where
+    G::Weight: Ord + Add<Output = G::Weight> + Sub<Output = G::Weight> + Default,
+{
+    let h = shortest_path_potentials(graph)?;
+    let vertices = all_vertices(graph);
+
+    let mut reweighted: AdjacencyList<G::Vertex, G::Weight> = BTreeMap::new();
+    for &u in &vertices {
+        reweighted.insert(
+            u,
+            graph
+                .neighbors(u)
+                .map(|(v, w)| (v, w + h[&u] - h[&v]))
+                .collect(),
+        );
+    }
+
+    Ok(vertices
+        .into_iter()
+        .map(|u| {
+            let distances = dijkstra(&reweighted, &u)
+                .into_iter()
+                .map(|(v, entry)| match entry {
+                    Some((_, dist)) => (v, dist + h[&v] - h[&u]),
+                    None => (v, G::Weight::default()),
+                })
+                .collect();
+            (u, distances)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{floyd_warshall, AdjacencyMap};
+    use crate::math::PCG32;
+
+    fn add_edge(graph: &mut AdjacencyMap<u32, i64>, u: u32, v: u32, w: i64) {
+        graph.entry(u).or_default().insert(v, w);
+        graph.entry(v).or_default();
+    }
+
+    #[test]
+    fn small_graph() {
+        let mut graph = BTreeMap::new();
+        add_edge(&mut graph, 0, 1, 3);
+        add_edge(&mut graph, 1, 2, -2);
+        add_edge(&mut graph, 0, 2, 2);
+        add_edge(&mut graph, 2, 3, 1);
+        add_edge(&mut graph, 3, 1, 4);
+        graph.insert(4, BTreeMap::new());
+
+        let distances = johnson(&graph).unwrap();
+        assert_eq!(
+            distances[&0],
+            BTreeMap::from([(0, 0), (1, 3), (2, 1), (3, 2)])
+        );
+        assert_eq!(distances[&3], BTreeMap::from([(1, 4), (2, 2), (3, 0)]));
+        assert_eq!(distances[&4], BTreeMap::from([(4, 0)]));
+
+        add_edge(&mut graph, 3, 1, 0);
+        assert_eq!(johnson(&graph).unwrap_err().len(), 3);
+    }
+
+    #[test]
+    fn vertex_without_entry() {
+        // 2 is only the destination of an edge
+        let graph = BTreeMap::from([(1, vec![(2, -3)])]);
+        let distances = johnson(&graph).unwrap();
+        assert_eq!(distances, floyd_warshall(&graph));
+        assert_eq!(distances[&1], BTreeMap::from([(1, 0), (2, -3)]));
+    }
+
+    #[test]
+    fn same_as_floyd_warshall() {
+        let mut rng = PCG32::new_default(1618);
+        let mut checked = 0;
+        for _ in 0..60 {
+            let n = 1 + rng.get_u32() % 25;
+            let mut graph = BTreeMap::new();
+            for v in 0..n {
+                graph.insert(v, BTreeMap::new());
+            }
+            for _ in 0..rng.get_u32() % (3 * n) {
+                let u = rng.get_u32() % n;
+                let v = rng.get_u32() % n;
+                if u != v {
+                    add_edge(&mut graph, u, v, (rng.get_u32() % 40) as i64 - 3);
+                }
+            }
+
+            if let Ok(distances) = johnson(&graph) {
+                assert_eq!(distances, floyd_warshall(&graph));
+                checked += 1;
+            }
+        }
+        assert!(checked > 30);
+    }
+}
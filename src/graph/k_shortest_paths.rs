@@ -0,0 +1,232 @@
+/*
+Yen's algorithm for the k shortest loopless paths between two vertices.
+
+The first path is found with `shortest_path`. Each next one is a "deviation"
+of a path found before: for every vertex `spur` of the last path, the part
+of the path before `spur` (the root) is kept, and a shortest path from `spur`
+to the target is searched in the graph without the root's other vertices and
+without the edges that the known paths sharing this root take out of `spur`.
+The cheapest of all the deviations found so far is the next path.
+
+It makes O(k.n) calls to dijkstra, n being the number of vertices.
+*/
+
+use std::collections::BTreeSet;
+use std::ops::Add;
+
+use super::{shortest_path, Graph};
+
+// The graph without some of its vertices and edges
+struct Restricted<'a, G: Graph> {
+    graph: &'a G,
+    vertices: BTreeSet<G::Vertex>,
+    edges: BTreeSet<(G::Vertex, G::Vertex)>,
+}
+
+impl<'a, G: Graph> Graph for Restricted<'a, G> {
+    type Vertex = G::Vertex;
+    type Weight = G::Weight;
+
+    fn num_vertices(&self) -> usize {
+        self.graph.num_vertices() - self.vertices.len()
+    }
+
+    fn vertices(&self) -> impl Iterator<Item = G::Vertex> + '_ {
+        self.graph.vertices().filter(|v| !self.vertices.contains(v))
+    }
+
+    fn neighbors(&self, vertex: G::Vertex) -> impl Iterator<Item = (G::Vertex, G::Weight)> + '_ {
+        let removed = self.vertices.contains(&vertex);
+        self.graph.neighbors(vertex).filter(move |(v, _)| {
+            !removed && !self.vertices.contains(v) && !self.edges.contains(&(vertex, *v))
+        })
+    }
+
+    fn contains_vertex(&self, vertex: G::Vertex) -> bool {
+        self.graph.contains_vertex(vertex) && !self.vertices.contains(&vertex)
+    }
+}
+
+// the weight of the cheapest edge from u to v
+fn edge_weight<G: Graph>(graph: &G, u: G::Vertex, v: G::Vertex) -> G::Weight
+where
+    G::Weight: Ord,
+{
+    graph
+        .neighbors(u)
+        .filter(|(w, _)| *w == v)
+        .map(|(_, weight)| weight)
+        .min()
+        .unwrap()
+}
+
+// finds the k shortest loopless paths from start to target with Yen's algorithm
+// the graph is a positively-weighted directed graph
+//
+// returns up to k paths (fewer if there aren't that many) with their total
+// weight, in increasing order of weight. Paths of the same weight are sorted
+// by their vertices, but when more than k paths tie, which of the last ones
+// are returned is not specified. E::default() should be zero.
+pub fn k_shortest_paths<G: Graph>(
+    graph: &G,
+    start: &G::Vertex,
+    target: &G::Vertex,
+    k: usize,
+) -> Vec<(Vec<G::Vertex>, G::Weight)>
+where
+    G::Weight: Ord + Add<Output = G::Weight> + Default,
+{
+    let mut found: Vec<(Vec<G::Vertex>, G::Weight)> = Vec::new();
+    if k == 0 {
+        return found;
+    }
+    match shortest_path(graph, start, target) {
+        Some(path) => found.push(path),
+        None => return found,
+    }
+    // the deviations that haven't been picked yet, ordered by weight
+    let mut candidates: BTreeSet<(G::Weight, Vec<G::Vertex>)> = BTreeSet::new();
+
+    while found.len() < k {
+        let (last, _) = &found[found.len() - 1];
+        let mut root_weight = G::Weight::default();
+        for j in 0..last.len() - 1 {
+            let spur = last[j];
+            let root = &last[..=j];
+            let mut restricted = Restricted {
+                graph,
+                vertices: root[..j].iter().copied().collect(),
+                edges: BTreeSet::new(),
+            };
+            for (path, _) in found.iter() {
+                if path.len() > j + 1 && path[..=j] == *root {
+                    restricted.edges.insert((path[j], path[j + 1]));
+                }
+            }
+            if let Some((spur_path, spur_weight)) = shortest_path(&restricted, &spur, target) {
+                let mut path = root[..j].to_vec();
+                path.extend(spur_path);
+                if !found.iter().any(|(p, _)| *p == path) {
+                    candidates.insert((root_weight + spur_weight, path));
+                }
+            }
+            root_weight = root_weight + edge_weight(graph, spur, last[j + 1]);
+        }
+
+        match candidates.pop_first() {
+            Some((weight, path)) => found.push((path, weight)),
+            None => break,
+        }
+    }
+
+    // the first path and the deviations of the same weight may come in any
+    // order
+    found.sort_by(|(p1, w1), (p2, w2)| w1.cmp(w2).then_with(|| p1.cmp(p2)));
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::k_shortest_paths;
+    use crate::graph::AdjacencyMap;
+
+    fn add_edge<V: Ord + Copy, E: Ord>(graph: &mut AdjacencyMap<V, E>, v1: V, v2: V, c: E) {
+        graph.entry(v1).or_default().insert(v2, c);
+        graph.entry(v2).or_default();
+    }
+
+    #[test]
+    fn wikipedia_example() {
+        // https://en.wikipedia.org/wiki/Yen%27s_algorithm#Example
+        let mut graph = AdjacencyMap::new();
+        add_edge(&mut graph, 'C', 'D', 3);
+        add_edge(&mut graph, 'C', 'E', 2);
+        add_edge(&mut graph, 'D', 'F', 4);
+        add_edge(&mut graph, 'E', 'D', 1);
+        add_edge(&mut graph, 'E', 'F', 2);
+        add_edge(&mut graph, 'E', 'G', 3);
+        add_edge(&mut graph, 'F', 'G', 2);
+        add_edge(&mut graph, 'F', 'H', 1);
+        add_edge(&mut graph, 'G', 'H', 2);
+
+        assert_eq!(
+            k_shortest_paths(&graph, &'C', &'H', 3),
+            vec![
+                (vec!['C', 'E', 'F', 'H'], 5),
+                (vec!['C', 'E', 'G', 'H'], 7),
+                (vec!['C', 'D', 'F', 'H'], 8),
+            ]
+        );
+        // there are only 7 loopless paths from C to H
+        let all = k_shortest_paths(&graph, &'C', &'H', 100);
+        assert_eq!(all.len(), 7);
+        for pair in all.windows(2) {
+            assert!(pair[0].1 <= pair[1].1);
+        }
+    }
+
+    #[test]
+    fn ties() {
+        // four paths of weight 2 from 0 to 5, and one of weight 3
+        let mut graph = AdjacencyMap::new();
+        for middle in 1..=4 {
+            add_edge(&mut graph, 0, middle, 1);
+            add_edge(&mut graph, middle, 5, 1);
+        }
+        add_edge(&mut graph, 0, 5, 3);
+
+        let paths = k_shortest_paths(&graph, &0, &5, 5);
+        assert_eq!(
+            paths,
+            vec![
+                (vec![0, 1, 5], 2),
+                (vec![0, 2, 5], 2),
+                (vec![0, 3, 5], 2),
+                (vec![0, 4, 5], 2),
+                (vec![0, 5], 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn ties_with_the_first_path() {
+        // 0 -> 2 -> 3 is found first, 0 -> 1 -> 3 has the same weight
+        let mut graph = AdjacencyMap::new();
+        add_edge(&mut graph, 0, 1, 2);
+        add_edge(&mut graph, 1, 3, 1);
+        add_edge(&mut graph, 0, 2, 1);
+        add_edge(&mut graph, 2, 3, 2);
+        add_edge(&mut graph, 0, 3, 5);
+        assert_eq!(
+            k_shortest_paths(&graph, &0, &3, 3),
+            vec![(vec![0, 1, 3], 3), (vec![0, 2, 3], 3), (vec![0, 3], 5)]
+        );
+    }
+
+    #[test]
+    fn unreachable_target() {
+        let mut graph = AdjacencyMap::new();
+        add_edge(&mut graph, 0, 1, 1);
+        add_edge(&mut graph, 2, 1, 1);
+        assert_eq!(k_shortest_paths(&graph, &0, &2, 3), vec![]);
+        assert_eq!(k_shortest_paths(&graph, &0, &7, 3), vec![]);
+        assert_eq!(k_shortest_paths(&graph, &0, &1, 3), vec![(vec![0, 1], 1)]);
+        assert_eq!(k_shortest_paths(&graph, &0, &1, 0), vec![]);
+    }
+
+    #[test]
+    fn loops_are_skipped() {
+        // 0 -> 1 -> 2 -> 3 with a cycle 1 -> 2 -> 1 that must not be used
+        let mut graph = AdjacencyMap::new();
+        add_edge(&mut graph, 0, 1, 1);
+        add_edge(&mut graph, 1, 2, 1);
+        add_edge(&mut graph, 2, 1, 1);
+        add_edge(&mut graph, 2, 3, 1);
+        add_edge(&mut graph, 1, 3, 5);
+
+        assert_eq!(
+            k_shortest_paths(&graph, &0, &3, 4),
+            vec![(vec![0, 1, 2, 3], 3), (vec![0, 1, 3], 6)]
+        );
+    }
+}
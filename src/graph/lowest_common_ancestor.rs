@@ -13,8 +13,16 @@ Depth First Search order and queries are answered using Disjoint
 Set Union algorithm. The time complexity is O(n.alpha(n) + q) and
 memory usage is O(n + q), but time complexity can be considered to be O(n + q),
 because alpha(n) < 5 for n < 10 ^ 600
+- The Euler tour one lists the vertices in the order a walk around the tree
+meets them (2n - 1 of them). The lowest common ancestor of u and v is the
+vertex of smallest depth between their first occurrences, which is a range
+minimum query answered in O(1) with a sparse table built in O(n.lg(n)). It
+also gives distances, weighted path lengths and k-th ancestors (by binary
+search among the vertices of the same depth, in O(lg(n))).
  */
 
+use std::ops::{Add, Sub};
+
 use super::DisjointSetUnion;
 pub struct LowestCommonAncestorOnline {
     // Make members public to allow the user to fill them themself.
@@ -84,8 +92,12 @@ impl LowestCommonAncestorOnline {
         if u == v {
             return u;
         }
-        // `self.num_parents` of u and v should be equal
+        // `self.num_parents` of u and v should be equal, but it decreases
+        // after a jump: near the root, the next levels may be missing
         for i in (0..self.num_parents(v)).rev() {
+            if i >= self.num_parents(v) {
+                continue;
+            }
             let nv = self.get_parent(v, i);
             let nu = self.get_parent(u, i);
             if nv != nu {
@@ -97,6 +109,164 @@ impl LowestCommonAncestorOnline {
     }
 }
 
+pub struct LowestCommonAncestorEulerTour<W> {
+    // The parent of each vertex (0 for the root), its depth (the root has
+    // depth 0) and the total weight of the path from the root to it
+    pub parent: Vec<usize>,
+    pub depth: Vec<usize>,
+    pub root_distance: Vec<W>,
+    // first_visit[v] is the index of the first occurrence of v in the tour
+    first_visit: Vec<usize>,
+    // sparse_table[j][i] is the vertex of smallest depth in the part of the
+    // tour starting at i of length 2^j
+    sparse_table: Vec<Vec<usize>>,
+    // by_depth[d] are the vertices of depth d in the order they are visited
+    by_depth: Vec<Vec<usize>>,
+}
+
+impl LowestCommonAncestorEulerTour<usize> {
+    // the tree has the vertices 1..=num_vertices and the given edges, every
+    // edge has length 1
+    pub fn from_edges(num_vertices: usize, root: usize, edges: &[(usize, usize)]) -> Self {
+        let weighted: Vec<(usize, usize, usize)> = edges.iter().map(|&(u, v)| (u, v, 1)).collect();
+        Self::from_weighted_edges(num_vertices, root, &weighted)
+    }
+}
+
+impl<W: Copy + Default + Add<Output = W> + Sub<Output = W>> LowestCommonAncestorEulerTour<W> {
+    // the tree has the vertices 1..=num_vertices and the given edges, with
+    // their lengths. W::default() should be zero.
+    pub fn from_weighted_edges(
+        num_vertices: usize,
+        root: usize,
+        edges: &[(usize, usize, W)],
+    ) -> Self {
+        let mut adj = vec![vec![]; num_vertices + 1];
+        for &(u, v, w) in edges {
+            adj[u].push((v, w));
+            adj[v].push((u, w));
+        }
+
+        let mut parent = vec![0; num_vertices + 1];
+        let mut depth = vec![0; num_vertices + 1];
+        let mut root_distance = vec![W::default(); num_vertices + 1];
+        let mut first_visit = vec![0; num_vertices + 1];
+        let mut by_depth: Vec<Vec<usize>> = vec![];
+        let mut tour = Vec::with_capacity(2 * num_vertices);
+        // the walk around the tree, without recursion: each vertex on the
+        // stack keeps the index of its next neighbour to look at
+        let mut stack = vec![(root, 0)];
+        tour.push(root);
+        by_depth.push(vec![root]);
+        while let Some((v, next)) = stack.last_mut() {
+            let v = *v;
+            match adj[v].get(*next) {
+                Some(&(u, w)) => {
+                    *next += 1;
+                    if u == parent[v] {
+                        continue;
+                    }
+                    parent[u] = v;
+                    depth[u] = depth[v] + 1;
+                    root_distance[u] = root_distance[v] + w;
+                    first_visit[u] = tour.len();
+                    tour.push(u);
+                    if by_depth.len() == depth[u] {
+                        by_depth.push(vec![]);
+                    }
+                    by_depth[depth[u]].push(u);
+                    stack.push((u, 0));
+                }
+                None => {
+                    stack.pop();
+                    if let Some(&(p, _)) = stack.last() {
+                        tour.push(p);
+                    }
+                }
+            }
+        }
+
+        let mut sparse_table = vec![tour];
+        let mut length = 1;
+        while 2 * length <= sparse_table[0].len() {
+            let previous = sparse_table.last().unwrap();
+            let level = (0..previous.len() - length)
+                .map(|i| {
+                    let (a, b) = (previous[i], previous[i + length]);
+                    if depth[a] <= depth[b] {
+                        a
+                    } else {
+                        b
+                    }
+                })
+                .collect();
+            sparse_table.push(level);
+            length *= 2;
+        }
+
+        LowestCommonAncestorEulerTour {
+            parent,
+            depth,
+            root_distance,
+            first_visit,
+            sparse_table,
+            by_depth,
+        }
+    }
+
+    pub fn get_ancestor(&self, u: usize, v: usize) -> usize {
+        let (mut left, mut right) = (self.first_visit[u], self.first_visit[v]);
+        if left > right {
+            std::mem::swap(&mut left, &mut right);
+        }
+        // two overlapping ranges of length 2^level cover the range
+        let level = (right - left + 1).ilog2() as usize;
+        let a = self.sparse_table[level][left];
+        let b = self.sparse_table[level][right + 1 - (1 << level)];
+        if self.depth[a] <= self.depth[b] {
+            a
+        } else {
+            b
+        }
+    }
+
+    // the number of edges between u and v
+    pub fn distance(&self, u: usize, v: usize) -> usize {
+        self.depth[u] + self.depth[v] - 2 * self.depth[self.get_ancestor(u, v)]
+    }
+
+    // the total length of the edges between u and v
+    pub fn path_length(&self, u: usize, v: usize) -> W {
+        let ancestor = self.root_distance[self.get_ancestor(u, v)];
+        self.root_distance[u] - ancestor + self.root_distance[v] - ancestor
+    }
+
+    // the ancestor of v k levels above it (v itself for k = 0), None if v
+    // has depth less than k
+    pub fn kth_ancestor(&self, v: usize, k: usize) -> Option<usize> {
+        let target_depth = self.depth[v].checked_sub(k)?;
+        // the ancestor is the last vertex of that depth visited before v
+        let level = &self.by_depth[target_depth];
+        let index = level.partition_point(|&u| self.first_visit[u] <= self.first_visit[v]);
+        Some(level[index - 1])
+    }
+
+    // the k-th vertex of the path from u to v (u for k = 0), None if the path
+    // has less than k edges
+    pub fn kth_vertex_on_path(&self, u: usize, v: usize, k: usize) -> Option<usize> {
+        let ancestor = self.get_ancestor(u, v);
+        let up = self.depth[u] - self.depth[ancestor];
+        let down = self.depth[v] - self.depth[ancestor];
+        if k <= up {
+            self.kth_ancestor(u, k)
+        } else if k <= up + down {
+            self.kth_ancestor(v, up + down - k)
+        } else {
+            None
+        }
+    }
+}
+
 #[derive(Clone, Copy)]
 pub struct LCAQuery {
     other: usize,
@@ -176,6 +346,9 @@ impl LowestCommonAncestorOffline {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::graph::random_tree;
+    use crate::math::PCG32;
+
     #[test]
     fn small_binary_tree() {
         let num_verts = 127;
@@ -208,4 +381,127 @@ mod tests {
         offline_answers.sort_unstable_by(|a1, a2| a1.query_id.cmp(&a2.query_id));
         assert_eq!(offline_answers, online_answers);
     }
+
+    #[test]
+    fn jump_below_the_last_level() {
+        // Two paths of 9 vertices hanging from the root: from 10 and 19, the
+        // jump of 8 lands on 2 and 11, whose tables are too short for the
+        // next jumps
+        let num_verts = 19;
+        let mut tree: Vec<Vec<usize>> = vec![vec![]; num_verts + 1];
+        for (u, v) in (1..10)
+            .zip(2..=10)
+            .chain([(1, 11)])
+            .chain((11..19).zip(12..=19))
+        {
+            tree[u].push(v);
+            tree[v].push(u);
+        }
+        let mut online = LowestCommonAncestorOnline::new(num_verts);
+        online.fill_sparse_table(1, 0, 0, &tree);
+        assert_eq!(online.get_ancestor(10, 19), 1);
+        assert_eq!(online.get_ancestor(19, 10), 1);
+        assert_eq!(online.get_ancestor(10, 15), 1);
+        assert_eq!(online.get_ancestor(10, 6), 6);
+    }
+
+    fn random_edges(n: usize, rng: &mut PCG32) -> Vec<(usize, usize)> {
+        random_tree(n, rng)
+            .into_iter()
+            .flat_map(|(u, neighbors)| neighbors.into_iter().map(move |v| (u, v)))
+            .filter(|&(u, v)| u < v)
+            .collect()
+    }
+
+    #[test]
+    fn euler_tour_small_tree() {
+        //       1
+        //      / \
+        //     2   3
+        //    / \   \
+        //   4   5   6
+        //           |
+        //           7
+        let edges = [(1, 2), (1, 3), (2, 4), (2, 5), (3, 6), (6, 7)];
+        let lca = LowestCommonAncestorEulerTour::from_edges(7, 1, &edges);
+        assert_eq!(lca.get_ancestor(4, 5), 2);
+        assert_eq!(lca.get_ancestor(4, 7), 1);
+        assert_eq!(lca.get_ancestor(6, 7), 6);
+        assert_eq!(lca.get_ancestor(3, 3), 3);
+        assert_eq!(lca.distance(4, 7), 5);
+        assert_eq!(lca.kth_ancestor(7, 2), Some(3));
+        assert_eq!(lca.kth_ancestor(7, 3), Some(1));
+        assert_eq!(lca.kth_ancestor(7, 4), None);
+        let path: Vec<Option<usize>> = (0..=6).map(|k| lca.kth_vertex_on_path(5, 7, k)).collect();
+        assert_eq!(
+            path,
+            vec![Some(5), Some(2), Some(1), Some(3), Some(6), Some(7), None]
+        );
+
+        let weighted: Vec<(usize, usize, i64)> = edges
+            .iter()
+            .map(|&(u, v)| (u, v, 10 * u as i64 + v as i64))
+            .collect();
+        let lca = LowestCommonAncestorEulerTour::from_weighted_edges(7, 1, &weighted);
+        assert_eq!(lca.path_length(5, 7), 25 + 12 + 13 + 36 + 67);
+        assert_eq!(lca.path_length(6, 6), 0);
+
+        let single = LowestCommonAncestorEulerTour::from_edges(1, 1, &[]);
+        assert_eq!(single.get_ancestor(1, 1), 1);
+        assert_eq!(single.kth_vertex_on_path(1, 1, 0), Some(1));
+    }
+
+    #[test]
+    fn euler_tour_against_binary_lifting() {
+        let mut rng = PCG32::new_default(1729);
+        for _ in 0..20 {
+            let n = 1 + rng.get_u32() as usize % 80;
+            let edges = random_edges(n, &mut rng);
+            let root = 1 + rng.get_u32() as usize % n;
+            let mut tree = vec![vec![]; n + 1];
+            for &(u, v) in &edges {
+                tree[u].push(v);
+                tree[v].push(u);
+            }
+            let mut online = LowestCommonAncestorOnline::new(n);
+            online.fill_sparse_table(root, 0, 0, &tree);
+            let euler = LowestCommonAncestorEulerTour::from_edges(n, root, &edges);
+
+            for u in 1..=n {
+                for v in 1..=n {
+                    let ancestor = online.get_ancestor(u, v);
+                    assert_eq!(euler.get_ancestor(u, v), ancestor);
+                    assert_eq!(euler.path_length(u, v), euler.distance(u, v));
+
+                    // the path by walking up from both ends
+                    let mut path = vec![u];
+                    while *path.last().unwrap() != ancestor {
+                        path.push(euler.parent[*path.last().unwrap()]);
+                    }
+                    let mut down = vec![v];
+                    while *down.last().unwrap() != ancestor {
+                        down.push(euler.parent[*down.last().unwrap()]);
+                    }
+                    down.pop();
+                    path.extend(down.into_iter().rev());
+                    assert_eq!(euler.distance(u, v), path.len() - 1);
+                    for (k, &w) in path.iter().enumerate() {
+                        assert_eq!(euler.kth_vertex_on_path(u, v, k), Some(w));
+                    }
+                    assert_eq!(euler.kth_vertex_on_path(u, v, path.len()), None);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn euler_tour_deep_path() {
+        let n = 200_000;
+        let edges: Vec<(usize, usize)> = (1..n).map(|v| (v, v + 1)).collect();
+        let lca = LowestCommonAncestorEulerTour::from_edges(n, 1, &edges);
+        assert_eq!(lca.get_ancestor(n, 7), 7);
+        assert_eq!(lca.kth_ancestor(n, n - 1), Some(1));
+        assert_eq!(lca.kth_vertex_on_path(3, n, 10), Some(13));
+        assert_eq!(lca.distance(n, 1), n - 1);
+    }
 }
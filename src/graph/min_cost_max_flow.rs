@@ -0,0 +1,276 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+use super::dinic_maxflow::{FlowEdge, FlowResultEdge};
+
+// We assume that graph vertices are numbered from 1 to n.
+
+/// Minimum cost maximum flow, with successive shortest paths.
+///
+/// The flow is increased along a cheapest path of the residual network until
+/// the sink can't be reached anymore. Cheapest paths are found with dijkstra,
+/// on costs made non-negative with vertex potentials (Johnson's reweighting),
+/// so costs can be negative as long as the network has no negative cycle.
+pub struct MinCostMaxFlow<T> {
+    /// Holds wether the solution has already been calculated
+    network_solved: bool,
+
+    pub source: usize,
+    pub sink: usize,
+
+    /// Number of edges added to the residual network
+    pub num_edges: usize,
+    pub num_vertices: usize,
+
+    pub adj: Vec<Vec<usize>>,
+
+    /// The list of flow edges, each followed by its reverse edge
+    pub edges: Vec<FlowEdge<T>>,
+}
+
+impl<
+        T: Clone
+            + Copy
+            + Add<Output = T>
+            + AddAssign
+            + Sub<Output = T>
+            + SubAssign
+            + Mul<Output = T>
+            + Neg<Output = T>
+            + Ord
+            + Default,
+    > MinCostMaxFlow<T>
+{
+    pub fn new(source: usize, sink: usize, num_vertices: usize) -> Self {
+        MinCostMaxFlow {
+            network_solved: false,
+            source,
+            sink,
+            num_edges: 0,
+            num_vertices,
+            adj: vec![vec![]; num_vertices + 1],
+            edges: vec![],
+        }
+    }
+
+    #[inline]
+    pub fn add_edge(&mut self, source: usize, sink: usize, capacity: T, cost: T) {
+        self.edges.push(FlowEdge::with_cost(sink, capacity, cost));
+        // The reverse edge gives back the cost when flow is cancelled
+        self.edges
+            .push(FlowEdge::with_cost(source, T::default(), -cost));
+        self.adj[source].push(self.num_edges);
+        self.adj[sink].push(self.num_edges + 1);
+        self.num_edges += 2;
+    }
+
+    fn has_residual(&self, e: usize) -> bool {
+        self.edges[e].capacity > self.edges[e].flow
+    }
+
+    /// Bellman-Ford distances from the source, used as the first potentials
+    /// (the costs may be negative). Unreachable vertices get None.
+    fn initial_potentials(&self) -> Vec<Option<T>> {
+        let mut dist = vec![None; self.adj.len()];
+        dist[self.source] = Some(T::default());
+        for _ in 0..self.adj.len() {
+            let mut changed = false;
+            for v in 1..self.adj.len() {
+                let Some(dist_v) = dist[v] else { continue };
+                for &e in self.adj[v].iter() {
+                    if !self.has_residual(e) {
+                        continue;
+                    }
+                    let u = self.edges[e].sink;
+                    let dist_u = dist_v + self.edges[e].cost;
+                    if dist[u].is_none_or(|d| dist_u < d) {
+                        dist[u] = Some(dist_u);
+                        changed = true;
+                    }
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        dist
+    }
+
+    /// Dijkstra on the reduced costs `cost + potential[v] - potential[u]`,
+    /// which are never negative. Returns the reduced distance of every vertex
+    /// and the edge used to reach it.
+    fn dijkstra(&self, potential: &[T]) -> (Vec<Option<T>>, Vec<Option<usize>>) {
+        let mut dist = vec![None; self.adj.len()];
+        let mut parent_edge = vec![None; self.adj.len()];
+        let mut prio = BinaryHeap::new();
+        dist[self.source] = Some(T::default());
+        prio.push(Reverse((T::default(), self.source)));
+        while let Some(Reverse((dist_v, v))) = prio.pop() {
+            if dist[v].is_some_and(|d| dist_v > d) {
+                continue;
+            }
+            for &e in self.adj[v].iter() {
+                if !self.has_residual(e) {
+                    continue;
+                }
+                let u = self.edges[e].sink;
+                let dist_u = dist_v + self.edges[e].cost + potential[v] - potential[u];
+                if dist[u].is_none_or(|d| dist_u < d) {
+                    dist[u] = Some(dist_u);
+                    parent_edge[u] = Some(e);
+                    prio.push(Reverse((dist_u, u)));
+                }
+            }
+        }
+        (dist, parent_edge)
+    }
+
+    /// Returns the max flow from source to sink, and the minimum total cost
+    /// of sending it. `infinite_flow` should be greater than any capacity.
+    pub fn find_mincost_maxflow(&mut self, infinite_flow: T) -> (T, T) {
+        self.network_solved = true;
+        let mut total_flow = T::default();
+        let mut total_cost = T::default();
+        // Vertices that can't be reached from the source never will be, so
+        // their potential doesn't matter
+        let mut potential: Vec<T> = self
+            .initial_potentials()
+            .into_iter()
+            .map(|p| p.unwrap_or_default())
+            .collect();
+
+        loop {
+            let (dist, parent_edge) = self.dijkstra(&potential);
+            if dist[self.sink].is_none() {
+                break;
+            }
+            for v in 1..self.adj.len() {
+                if let Some(d) = dist[v] {
+                    potential[v] += d;
+                }
+            }
+
+            // The bottleneck of the cheapest path, found by walking it back
+            let mut pushed = infinite_flow;
+            let mut v = self.sink;
+            while let Some(e) = parent_edge[v] {
+                pushed = std::cmp::min(pushed, self.edges[e].capacity - self.edges[e].flow);
+                v = self.edges[e ^ 1].sink;
+            }
+            let mut v = self.sink;
+            while let Some(e) = parent_edge[v] {
+                self.edges[e].flow += pushed;
+                self.edges[e ^ 1].flow -= pushed;
+                total_cost += pushed * self.edges[e].cost;
+                v = self.edges[e ^ 1].sink;
+            }
+            total_flow += pushed;
+        }
+        (total_flow, total_cost)
+    }
+
+    pub fn get_flow_edges(&mut self, infinite_flow: T) -> Vec<FlowResultEdge<T>> {
+        if !self.network_solved {
+            self.find_mincost_maxflow(infinite_flow);
+        }
+        let mut result = Vec::new();
+        for v in 1..self.adj.len() {
+            for &e_ind in self.adj[v].iter() {
+                let e = &self.edges[e_ind];
+                // Make sure that reverse edges from residual network are not
+                // included
+                if e.flow > T::default() {
+                    result.push(FlowResultEdge {
+                        source: v,
+                        sink: e.sink,
+                        flow: e.flow,
+                    });
+                }
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::DinicMaxFlow;
+    use crate::math::PCG32;
+
+    #[test]
+    fn small_graph() {
+        // two paths from 1 to 4: 1-2-4 costs 2 per unit, 1-3-4 costs 5
+        let mut flow: MinCostMaxFlow<i32> = MinCostMaxFlow::new(1, 4, 4);
+        flow.add_edge(1, 2, 3, 1);
+        flow.add_edge(2, 4, 2, 1);
+        flow.add_edge(1, 3, 2, 2);
+        flow.add_edge(3, 4, 4, 3);
+        flow.add_edge(2, 3, 5, 1);
+
+        // 2 units through 1-2-4 (cost 2 each), 1 through 1-2-3-4 (cost 5)
+        // and 2 through 1-3-4 (cost 5 each)
+        assert_eq!(flow.find_mincost_maxflow(i32::MAX), (5, 19));
+
+        let flow_edges = flow.get_flow_edges(i32::MAX);
+        assert_eq!(flow_edges.len(), 5);
+        // the reverse edges are at the odd indices
+        let cost: i32 = flow.edges.iter().step_by(2).map(|e| e.flow * e.cost).sum();
+        assert_eq!(cost, 19);
+    }
+
+    #[test]
+    fn assignment() {
+        // 3 workers (2..=4) and 3 jobs (5..=7), the source is 1 and the sink 8
+        let costs = [[9, 2, 7], [6, 4, 3], [5, 8, 1]];
+        let mut flow: MinCostMaxFlow<i64> = MinCostMaxFlow::new(1, 8, 8);
+        for (worker, row) in costs.iter().enumerate() {
+            flow.add_edge(1, worker + 2, 1, 0);
+            flow.add_edge(worker + 5, 8, 1, 0);
+            for (job, &cost) in row.iter().enumerate() {
+                flow.add_edge(worker + 2, job + 5, 1, cost);
+            }
+        }
+        // 0 -> 1, 1 -> 0, 2 -> 2
+        assert_eq!(flow.find_mincost_maxflow(i64::MAX), (3, 9));
+    }
+
+    #[test]
+    fn negative_costs() {
+        let mut flow: MinCostMaxFlow<i32> = MinCostMaxFlow::new(1, 3, 3);
+        flow.add_edge(1, 2, 1, -5);
+        flow.add_edge(2, 3, 1, 2);
+        flow.add_edge(1, 3, 1, 1);
+        assert_eq!(flow.find_mincost_maxflow(i32::MAX), (2, -2));
+    }
+
+    #[test]
+    fn unreachable_sink() {
+        let mut flow: MinCostMaxFlow<i32> = MinCostMaxFlow::new(1, 4, 4);
+        flow.add_edge(1, 2, 1, 1);
+        flow.add_edge(3, 4, 1, 1);
+        assert_eq!(flow.find_mincost_maxflow(i32::MAX), (0, 0));
+        assert!(flow.get_flow_edges(i32::MAX).is_empty());
+    }
+
+    #[test]
+    fn same_flow_as_dinic() {
+        let mut rng = PCG32::new_default(1618);
+        for _ in 0..30 {
+            let n = 12;
+            let mut min_cost: MinCostMaxFlow<i64> = MinCostMaxFlow::new(1, n, n);
+            let mut dinic: DinicMaxFlow<i64> = DinicMaxFlow::new(1, n, n);
+            for _ in 0..40 {
+                let u = (rng.get_u32() as usize % n) + 1;
+                let v = (rng.get_u32() as usize % n) + 1;
+                let capacity = (rng.get_u32() % 20) as i64;
+                let cost = (rng.get_u32() % 10) as i64;
+                min_cost.add_edge(u, v, capacity, cost);
+                dinic.add_edge(u, v, capacity);
+            }
+            let (max_flow, _) = min_cost.find_mincost_maxflow(i64::MAX);
+            assert_eq!(max_flow, dinic.find_maxflow(i64::MAX));
+        }
+    }
+}
@@ -1,24 +1,20 @@
-use super::DisjointSetUnion;
+use std::collections::BTreeMap;
+use std::ops::Add;
 
-#[derive(Debug)]
-pub struct Edge {
-    source: i64,
-    destination: i64,
-    cost: i64,
-}
+use super::{all_vertices, DisjointSetUnion, Graph};
 
-impl PartialEq for Edge {
-    fn eq(&self, other: &Self) -> bool {
-        self.source == other.source
-            && self.destination == other.destination
-            && self.cost == other.cost
-    }
-}
+// The total cost of a minimum spanning tree (or forest) and its edges
+pub type SpanningTree<V, E> = (E, Vec<Edge<V, E>>);
 
-impl Eq for Edge {}
+#[derive(Debug, PartialEq, Eq)]
+pub struct Edge<V, E> {
+    pub source: V,
+    pub destination: V,
+    pub cost: E,
+}
 
-impl Edge {
-    fn new(source: i64, destination: i64, cost: i64) -> Self {
+impl<V, E> Edge<V, E> {
+    pub fn new(source: V, destination: V, cost: E) -> Self {
         Self {
             source,
             destination,
@@ -27,26 +23,41 @@ impl Edge {
     }
 }
 
-pub fn kruskal(mut edges: Vec<Edge>, number_of_vertices: i64) -> (i64, Vec<Edge>) {
-    let mut dsu = DisjointSetUnion::new(number_of_vertices as usize);
-
-    edges.sort_unstable_by(|a, b| a.cost.cmp(&b.cost));
-    let mut total_cost: i64 = 0;
-    let mut final_edges: Vec<Edge> = Vec::new();
-    let mut merge_count: i64 = 0;
-    for edge in edges.iter() {
-        if merge_count >= number_of_vertices - 1 {
+// the graph is an undirected graph, it doesn't matter whether its edges are
+// stored in one or both directions
+//
+// returns the total cost of the minimum spanning tree and its edges, sorted by cost
+// if the graph is not connected, it is a minimum spanning forest: a tree for
+// each connected component
+pub fn kruskal<G: Graph>(graph: &G) -> SpanningTree<G::Vertex, G::Weight>
+where
+    G::Weight: Ord + Add<Output = G::Weight> + Default,
+{
+    let index: BTreeMap<G::Vertex, usize> = all_vertices(graph)
+        .into_iter()
+        .enumerate()
+        .map(|(i, v)| (v, i))
+        .collect();
+    let number_of_vertices = index.len();
+    let mut dsu = DisjointSetUnion::new(number_of_vertices);
+
+    let mut edges: Vec<Edge<G::Vertex, G::Weight>> = graph
+        .edges()
+        .map(|(source, destination, cost)| Edge::new(source, destination, cost))
+        .collect();
+    edges.sort_by_key(|edge| edge.cost);
+    let mut total_cost = G::Weight::default();
+    let mut final_edges = Vec::new();
+    let mut merge_count: usize = 0;
+    for edge in edges {
+        if merge_count + 1 >= number_of_vertices {
             break;
         }
 
-        let source: i64 = edge.source;
-        let destination: i64 = edge.destination;
-        if dsu.merge(source as usize, destination as usize) < std::usize::MAX {
+        if dsu.merge(index[&edge.source], index[&edge.destination]) < usize::MAX {
             merge_count += 1;
-            let cost: i64 = edge.cost;
-            total_cost += cost;
-            let final_edge: Edge = Edge::new(source, destination, cost);
-            final_edges.push(final_edge);
+            total_cost = total_cost + edge.cost;
+            final_edges.push(edge);
         }
     }
     (total_cost, final_edges)
@@ -55,34 +66,39 @@ pub fn kruskal(mut edges: Vec<Edge>, number_of_vertices: i64) -> (i64, Vec<Edge>
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::graph::AdjacencyMap;
+
+    fn add_edge(graph: &mut AdjacencyMap<i64, i64>, source: i64, destination: i64, cost: i64) {
+        graph.entry(source).or_default().insert(destination, cost);
+        graph.entry(destination).or_default();
+    }
 
     #[test]
     fn test_seven_vertices_eleven_edges() {
-        let mut edges: Vec<Edge> = Vec::new();
-        edges.push(Edge::new(0, 1, 7));
-        edges.push(Edge::new(0, 3, 5));
-        edges.push(Edge::new(1, 2, 8));
-        edges.push(Edge::new(1, 3, 9));
-        edges.push(Edge::new(1, 4, 7));
-        edges.push(Edge::new(2, 4, 5));
-        edges.push(Edge::new(3, 4, 15));
-        edges.push(Edge::new(3, 5, 6));
-        edges.push(Edge::new(4, 5, 8));
-        edges.push(Edge::new(4, 6, 9));
-        edges.push(Edge::new(5, 6, 11));
-
-        let number_of_vertices: i64 = 7;
+        let mut graph = AdjacencyMap::new();
+        add_edge(&mut graph, 0, 1, 7);
+        add_edge(&mut graph, 0, 3, 5);
+        add_edge(&mut graph, 1, 2, 8);
+        add_edge(&mut graph, 1, 3, 9);
+        add_edge(&mut graph, 1, 4, 7);
+        add_edge(&mut graph, 2, 4, 5);
+        add_edge(&mut graph, 3, 4, 15);
+        add_edge(&mut graph, 3, 5, 6);
+        add_edge(&mut graph, 4, 5, 8);
+        add_edge(&mut graph, 4, 6, 9);
+        add_edge(&mut graph, 5, 6, 11);
 
         let expected_total_cost = 39;
-        let mut expected_used_edges: Vec<Edge> = Vec::new();
-        expected_used_edges.push(Edge::new(0, 3, 5));
-        expected_used_edges.push(Edge::new(2, 4, 5));
-        expected_used_edges.push(Edge::new(3, 5, 6));
-        expected_used_edges.push(Edge::new(0, 1, 7));
-        expected_used_edges.push(Edge::new(1, 4, 7));
-        expected_used_edges.push(Edge::new(4, 6, 9));
+        let expected_used_edges = vec![
+            Edge::new(0, 3, 5),
+            Edge::new(2, 4, 5),
+            Edge::new(3, 5, 6),
+            Edge::new(0, 1, 7),
+            Edge::new(1, 4, 7),
+            Edge::new(4, 6, 9),
+        ];
 
-        let (actual_total_cost, actual_final_edges) = kruskal(edges, number_of_vertices);
+        let (actual_total_cost, actual_final_edges) = kruskal(&graph);
 
         assert_eq!(actual_total_cost, expected_total_cost);
         assert_eq!(actual_final_edges, expected_used_edges);
@@ -90,45 +106,80 @@ mod tests {
 
     #[test]
     fn test_ten_vertices_twenty_edges() {
-        let mut edges: Vec<Edge> = Vec::new();
-        edges.push(Edge::new(0, 1, 3));
-        edges.push(Edge::new(0, 3, 6));
-        edges.push(Edge::new(0, 4, 9));
-        edges.push(Edge::new(1, 2, 2));
-        edges.push(Edge::new(1, 3, 4));
-        edges.push(Edge::new(1, 4, 9));
-        edges.push(Edge::new(2, 3, 2));
-        edges.push(Edge::new(2, 5, 8));
-        edges.push(Edge::new(2, 6, 9));
-        edges.push(Edge::new(3, 6, 9));
-        edges.push(Edge::new(4, 5, 8));
-        edges.push(Edge::new(4, 9, 18));
-        edges.push(Edge::new(5, 6, 7));
-        edges.push(Edge::new(5, 8, 9));
-        edges.push(Edge::new(5, 9, 10));
-        edges.push(Edge::new(6, 7, 4));
-        edges.push(Edge::new(6, 8, 5));
-        edges.push(Edge::new(7, 8, 1));
-        edges.push(Edge::new(7, 9, 4));
-        edges.push(Edge::new(8, 9, 3));
-
-        let number_of_vertices: i64 = 10;
+        let mut graph = AdjacencyMap::new();
+        add_edge(&mut graph, 0, 1, 3);
+        add_edge(&mut graph, 0, 3, 6);
+        add_edge(&mut graph, 0, 4, 9);
+        add_edge(&mut graph, 1, 2, 2);
+        add_edge(&mut graph, 1, 3, 4);
+        add_edge(&mut graph, 1, 4, 9);
+        add_edge(&mut graph, 2, 3, 2);
+        add_edge(&mut graph, 2, 5, 8);
+        add_edge(&mut graph, 2, 6, 9);
+        add_edge(&mut graph, 3, 6, 9);
+        add_edge(&mut graph, 4, 5, 8);
+        add_edge(&mut graph, 4, 9, 18);
+        add_edge(&mut graph, 5, 6, 7);
+        add_edge(&mut graph, 5, 8, 9);
+        add_edge(&mut graph, 5, 9, 10);
+        add_edge(&mut graph, 6, 7, 4);
+        add_edge(&mut graph, 6, 8, 5);
+        add_edge(&mut graph, 7, 8, 1);
+        add_edge(&mut graph, 7, 9, 4);
+        add_edge(&mut graph, 8, 9, 3);
 
         let expected_total_cost = 38;
-        let mut expected_used_edges = Vec::new();
-        expected_used_edges.push(Edge::new(7, 8, 1));
-        expected_used_edges.push(Edge::new(1, 2, 2));
-        expected_used_edges.push(Edge::new(2, 3, 2));
-        expected_used_edges.push(Edge::new(0, 1, 3));
-        expected_used_edges.push(Edge::new(8, 9, 3));
-        expected_used_edges.push(Edge::new(6, 7, 4));
-        expected_used_edges.push(Edge::new(5, 6, 7));
-        expected_used_edges.push(Edge::new(2, 5, 8));
-        expected_used_edges.push(Edge::new(4, 5, 8));
-
-        let (actual_total_cost, actual_final_edges) = kruskal(edges, number_of_vertices);
+        let expected_used_edges = vec![
+            Edge::new(7, 8, 1),
+            Edge::new(1, 2, 2),
+            Edge::new(2, 3, 2),
+            Edge::new(0, 1, 3),
+            Edge::new(8, 9, 3),
+            Edge::new(6, 7, 4),
+            Edge::new(5, 6, 7),
+            Edge::new(2, 5, 8),
+            Edge::new(4, 5, 8),
+        ];
+
+        let (actual_total_cost, actual_final_edges) = kruskal(&graph);
 
         assert_eq!(actual_total_cost, expected_total_cost);
         assert_eq!(actual_final_edges, expected_used_edges);
     }
+
+    #[test]
+    fn test_disconnected_graph() {
+        let mut graph = AdjacencyMap::new();
+        add_edge(&mut graph, 1, 2, 4);
+        add_edge(&mut graph, 2, 3, 2);
+        add_edge(&mut graph, 1, 3, 3);
+        add_edge(&mut graph, 10, 11, -1);
+        graph.insert(12, Default::default());
+
+        let (total_cost, edges) = kruskal(&graph);
+
+        assert_eq!(total_cost, 4);
+        assert_eq!(
+            edges,
+            vec![
+                Edge::new(10, 11, -1),
+                Edge::new(2, 3, 2),
+                Edge::new(1, 3, 3)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_edges_in_one_direction() {
+        // 2 and 4 only appear as destinations
+        let graph: AdjacencyMap<i64, i64> =
+            AdjacencyMap::from([(1, BTreeMap::from([(2, 3)])), (3, BTreeMap::from([(4, 1)]))]);
+        assert_eq!(
+            kruskal(&graph),
+            (4, vec![Edge::new(3, 4, 1), Edge::new(1, 2, 3)])
+        );
+
+        let graph: AdjacencyMap<i64, i64> = AdjacencyMap::from([(1, BTreeMap::from([(2, 3)]))]);
+        assert_eq!(kruskal(&graph), (3, vec![Edge::new(1, 2, 3)]));
+    }
 }
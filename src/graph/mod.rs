@@ -1,38 +1,114 @@
+mod a_star;
 mod bellman_ford;
+mod biconnected_components;
+mod bidirectional_dijkstra;
 mod bipartite_matching;
+mod blossom_matching;
+mod boruvka;
 mod breadth_first_search;
 mod centroid_decomposition;
+mod centroid_queries;
+mod compressed_sparse_row;
 mod depth_first_search;
 mod depth_first_search_tic_tac_toe;
 mod dijkstra;
 mod dinic_maxflow;
 mod disjoint_set_union;
+mod dot_format;
+mod dynamic_connectivity;
+mod flow_with_lower_bounds;
 mod floyd_warshall;
+mod game_tree_search;
 mod graph_enumeration;
+mod graph_io;
+mod graph_representation;
+mod graph_traversal;
 mod heavy_light_decomposition;
+mod heavy_light_path_queries;
+mod hungarian;
+mod johnson;
+mod k_shortest_paths;
 mod lowest_common_ancestor;
+mod min_cost_max_flow;
 mod minimum_spanning_tree;
+mod persistent_disjoint_set_union;
 mod prim;
 mod prufer_code;
+mod push_relabel;
+mod random_graphs;
+mod rooted_tree;
+mod sat_solver;
+mod spfa;
 mod strongly_connected_components;
 mod topological_sort;
 mod two_satisfiability;
-pub use self::bellman_ford::bellman_ford;
+pub use self::a_star::a_star;
+pub use self::bellman_ford::{
+    bellman_ford, find_negative_cycle, negative_cycle_from, shortest_path_potentials,
+};
+pub use self::biconnected_components::BiconnectedComponents;
+pub use self::bidirectional_dijkstra::bidirectional_dijkstra;
 pub use self::bipartite_matching::BipartiteMatching;
+pub use self::blossom_matching::BlossomMatching;
+pub use self::boruvka::{boruvka, boruvka_parallel};
 pub use self::breadth_first_search::breadth_first_search;
 pub use self::centroid_decomposition::CentroidDecomposition;
+pub use self::centroid_queries::{count_pairs_within_distance, CentroidQueries, NearestPainted};
+pub use self::compressed_sparse_row::{CompressedSparseRowGraph, VertexIndex};
 pub use self::depth_first_search::depth_first_search;
 pub use self::depth_first_search_tic_tac_toe::minimax;
-pub use self::dijkstra::dijkstra;
-pub use self::dinic_maxflow::DinicMaxFlow;
+pub use self::dijkstra::{dijkstra, reconstruct_path, shortest_path};
+pub use self::dinic_maxflow::{DinicMaxFlow, FlowEdge, FlowResultEdge, MaxFlow};
 pub use self::disjoint_set_union::DisjointSetUnion;
-pub use self::floyd_warshall::floyd_warshall;
+pub use self::dot_format::{parse_dot, write_dot, DotGraph};
+pub use self::dynamic_connectivity::{
+    offline_dynamic_connectivity, ConnectivityAnswer, ConnectivityEvent,
+};
+pub use self::flow_with_lower_bounds::FlowWithLowerBounds;
+pub use self::floyd_warshall::{
+    floyd_warshall, floyd_warshall_dense, floyd_warshall_paths, DenseShortestPaths, ShortestPaths,
+};
+pub use self::game_tree_search::{
+    Game, GameSearch, Outcome, SearchResult, Side, ZobristTable, WIN_SCORE,
+};
 pub use self::graph_enumeration::enumerate_graph;
+pub use self::graph_io::{
+    parse_adjacency_matrix, parse_dimacs_cnf, parse_dimacs_max_flow, parse_dimacs_shortest_path,
+    parse_edge_list, parse_unweighted_edge_list, write_adjacency_matrix, write_dimacs_cnf,
+    write_dimacs_max_flow, write_dimacs_shortest_path, write_dimacs_solution, write_edge_list,
+    write_unweighted_edge_list, DimacsCnf, DimacsMaxFlow, ParseError,
+};
+pub use self::graph_representation::{
+    all_vertices, reverse_graph, AdjacencyList, AdjacencyMap, Graph, PredecessorMap,
+};
+pub use self::graph_traversal::{
+    bipartition, breadth_first_levels, breadth_first_traversal, depth_first_traversal,
+    depth_first_tree, find_cycle, BfsEvent, Control, DfsEvent,
+};
 pub use self::heavy_light_decomposition::HeavyLightDecomposition;
-pub use self::lowest_common_ancestor::{LowestCommonAncestorOffline, LowestCommonAncestorOnline};
-pub use self::minimum_spanning_tree::kruskal;
+pub use self::heavy_light_path_queries::HeavyLightPathQueries;
+pub use self::hungarian::{hungarian_max_weight, hungarian_min_cost, Assignment};
+pub use self::johnson::johnson;
+pub use self::k_shortest_paths::k_shortest_paths;
+pub use self::lowest_common_ancestor::{
+    LowestCommonAncestorEulerTour, LowestCommonAncestorOffline, LowestCommonAncestorOnline,
+};
+pub use self::min_cost_max_flow::MinCostMaxFlow;
+pub use self::minimum_spanning_tree::{kruskal, Edge, SpanningTree};
+pub use self::persistent_disjoint_set_union::PersistentDisjointSetUnion;
 pub use self::prim::{prim, prim_with_start};
 pub use self::prufer_code::{prufer_decode, prufer_encode};
-pub use self::strongly_connected_components::StronglyConnectedComponents;
-pub use self::topological_sort::topological_sort;
-pub use self::two_satisfiability::solve_two_satisfiability;
+pub use self::push_relabel::PushRelabelMaxFlow;
+pub use self::random_graphs::{
+    adjacency_lists, barabasi_albert, erdos_renyi_gnm, erdos_renyi_gnp, grid_graph,
+    random_bipartite, random_dag, random_tree, with_random_weights,
+};
+pub use self::rooted_tree::RootedTree;
+pub use self::sat_solver::{solve_sat, SatResult};
+pub use self::spfa::spfa;
+pub use self::strongly_connected_components::{Reachability, StronglyConnectedComponents};
+pub use self::topological_sort::{
+    all_topological_sorts, dag_longest_path, lexicographic_topological_sort, topological_sort,
+    CycleError,
+};
+pub use self::two_satisfiability::{solve_two_satisfiability, TwoSatConflict};
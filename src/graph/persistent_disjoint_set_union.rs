@@ -0,0 +1,205 @@
+/*
+Fully persistent disjoint set union: every merge creates a new version, and
+any version (not only the latest) can be queried or merged into again.
+
+The parents and ranks are kept in a persistent array, a segment tree where an
+update copies only the O(lg(n)) nodes on the path to the changed leaf and
+shares the rest with the previous version. With union by rank (no path
+compression, it would need too many copies), `find_set` reads O(lg(n))
+parents, so it takes O(lg(n)^2), and so does `merge`.
+
+Version 0 has every element in its own set.
+*/
+
+#[derive(Clone, Copy)]
+struct Node {
+    left: usize,
+    right: usize,
+    // Only meaningful for the leaves
+    parent: usize,
+    rank: usize,
+}
+
+pub struct PersistentDisjointSetUnion {
+    len: usize,
+    // The nodes of every version of the array
+    nodes: Vec<Node>,
+    // The root node of each version
+    versions: Vec<usize>,
+}
+
+impl PersistentDisjointSetUnion {
+    // Create n+1 sets [0, n]
+    pub fn new(n: usize) -> Self {
+        let mut dsu = PersistentDisjointSetUnion {
+            len: n + 1,
+            nodes: vec![],
+            versions: vec![],
+        };
+        let root = dsu.build(0, n);
+        dsu.versions.push(root);
+        dsu
+    }
+
+    fn build(&mut self, start: usize, end: usize) -> usize {
+        let mut node = Node {
+            left: 0,
+            right: 0,
+            parent: start,
+            rank: 0,
+        };
+        if start != end {
+            let mid = (start + end) / 2;
+            node.left = self.build(start, mid);
+            node.right = self.build(mid + 1, end);
+        }
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    // The leaf of element i in the array rooted at `root`
+    fn leaf(&self, mut root: usize, i: usize) -> Node {
+        let (mut start, mut end) = (0, self.len - 1);
+        while start != end {
+            let mid = (start + end) / 2;
+            if i <= mid {
+                root = self.nodes[root].left;
+                end = mid;
+            } else {
+                root = self.nodes[root].right;
+                start = mid + 1;
+            }
+        }
+        self.nodes[root]
+    }
+
+    // A copy of the array rooted at `root` where element i is changed
+    fn set(
+        &mut self,
+        root: usize,
+        start: usize,
+        end: usize,
+        i: usize,
+        parent: usize,
+        rank: usize,
+    ) -> usize {
+        let mut node = self.nodes[root];
+        if start == end {
+            node.parent = parent;
+            node.rank = rank;
+        } else {
+            let mid = (start + end) / 2;
+            if i <= mid {
+                node.left = self.set(node.left, start, mid, i, parent, rank);
+            } else {
+                node.right = self.set(node.right, mid + 1, end, i, parent, rank);
+            }
+        }
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    // The number of versions, the last one being the newest
+    pub fn num_versions(&self) -> usize {
+        self.versions.len()
+    }
+
+    pub fn find_set(&self, version: usize, mut v: usize) -> usize {
+        let root = self.versions[version];
+        loop {
+            let parent = self.leaf(root, v).parent;
+            if parent == v {
+                return v;
+            }
+            v = parent;
+        }
+    }
+
+    pub fn same_set(&self, version: usize, u: usize, v: usize) -> bool {
+        self.find_set(version, u) == self.find_set(version, v)
+    }
+
+    // Merges the sets of u and v in `version`, and returns the number of the
+    // new version. If they were already in the same set, the new version is
+    // the same as `version`.
+    pub fn merge(&mut self, version: usize, u: usize, v: usize) -> usize {
+        let mut root = self.versions[version];
+        let mut a = self.find_set(version, u);
+        let mut b = self.find_set(version, v);
+        if a != b {
+            let (mut rank_a, mut rank_b) = (self.leaf(root, a).rank, self.leaf(root, b).rank);
+            if rank_a < rank_b {
+                std::mem::swap(&mut a, &mut b);
+                std::mem::swap(&mut rank_a, &mut rank_b);
+            }
+            root = self.set(root, 0, self.len - 1, b, a, rank_b);
+            if rank_a == rank_b {
+                root = self.set(root, 0, self.len - 1, a, a, rank_a + 1);
+            }
+        }
+        self.versions.push(root);
+        self.versions.len() - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::DisjointSetUnion;
+    use crate::math::PCG32;
+
+    #[test]
+    fn branching_versions() {
+        let mut dsu = PersistentDisjointSetUnion::new(5);
+        let v1 = dsu.merge(0, 1, 2);
+        let v2 = dsu.merge(v1, 3, 4);
+        let v3 = dsu.merge(v2, 2, 3);
+        // a different history from v1
+        let v4 = dsu.merge(v1, 1, 5);
+        assert_eq!(dsu.num_versions(), 5);
+
+        assert!(!dsu.same_set(0, 1, 2));
+        assert!(dsu.same_set(v1, 1, 2));
+        assert!(!dsu.same_set(v2, 1, 4));
+        assert!(dsu.same_set(v3, 1, 4));
+        assert!(dsu.same_set(v4, 2, 5));
+        assert!(!dsu.same_set(v4, 3, 4));
+        assert!(!dsu.same_set(v3, 1, 5));
+
+        // merging two elements of the same set still makes a version
+        let v5 = dsu.merge(v3, 1, 4);
+        assert_eq!(v5, 5);
+        assert!(dsu.same_set(v5, 1, 4));
+    }
+
+    #[test]
+    fn same_as_replaying_history() {
+        // every version is built from a random older one; it should have the
+        // same sets as a DSU replaying the merges that led to it
+        let mut rng = PCG32::new_default(99);
+        let n = 20;
+        let mut dsu = PersistentDisjointSetUnion::new(n);
+        let mut history: Vec<Vec<(usize, usize)>> = vec![vec![]];
+        for _ in 0..200 {
+            let from = rng.get_u32() as usize % dsu.num_versions();
+            let u = rng.get_u32() as usize % (n + 1);
+            let v = rng.get_u32() as usize % (n + 1);
+            let version = dsu.merge(from, u, v);
+            let mut merges = history[from].clone();
+            merges.push((u, v));
+            history.push(merges);
+            assert_eq!(version, history.len() - 1);
+        }
+        for (version, merges) in history.iter().enumerate() {
+            let mut expected = DisjointSetUnion::new(n);
+            for &(u, v) in merges {
+                expected.merge(u, v);
+            }
+            for u in 0..=n {
+                for v in 0..=n {
+                    assert_eq!(dsu.same_set(version, u, v), expected.same_set(u, v));
+                }
+            }
+        }
+    }
+}
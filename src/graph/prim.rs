@@ -2,74 +2,96 @@ use std::cmp::Reverse;
 use std::collections::{BTreeMap, BinaryHeap};
 use std::ops::Add;
 
-type Graph<V, E> = BTreeMap<V, BTreeMap<V, E>>;
-
-fn add_edge<V: Ord + Copy, E: Ord + Add + Copy>(graph: &mut Graph<V, E>, v1: V, v2: V, c: E) {
-    graph.entry(v1).or_insert_with(BTreeMap::new).insert(v2, c);
-    graph.entry(v2).or_insert_with(BTreeMap::new).insert(v1, c);
+use super::{AdjacencyMap, Graph};
+
+fn add_edge<V: Ord + Copy, E: Ord + Add + Copy>(
+    graph: &mut AdjacencyMap<V, E>,
+    v1: V,
+    v2: V,
+    c: E,
+) {
+    graph.entry(v1).or_default().insert(v2, c);
+    graph.entry(v2).or_default().insert(v1, c);
 }
 
-// selects a start and run the algorithm from it
-pub fn prim<V: Ord + Copy + std::fmt::Debug, E: Ord + Add + Copy + std::fmt::Debug>(
-    graph: &Graph<V, E>,
-) -> Graph<V, E> {
-    match graph.keys().next() {
-        Some(v) => prim_with_start(graph, *v),
-        None => BTreeMap::new(),
+// runs the algorithm from every vertex not reached yet, so a disconnected
+// graph gives a minimum spanning forest (a tree for each connected component)
+pub fn prim<G: Graph>(graph: &G) -> AdjacencyMap<G::Vertex, G::Weight>
+where
+    G::Weight: Ord + Add,
+{
+    let mut mst: AdjacencyMap<G::Vertex, G::Weight> = BTreeMap::new();
+    for v in graph.vertices() {
+        if !mst.contains_key(&v) {
+            grow_tree(graph, v, &mut mst);
+        }
     }
+    mst
 }
 
 // only works for a connected graph
 // if the given graph is not connected it will return the MST of the connected subgraph
-pub fn prim_with_start<V: Ord + Copy, E: Ord + Add + Copy>(
-    graph: &Graph<V, E>,
-    start: V,
-) -> Graph<V, E> {
+pub fn prim_with_start<G: Graph>(graph: &G, start: G::Vertex) -> AdjacencyMap<G::Vertex, G::Weight>
+where
+    G::Weight: Ord + Add,
+{
     // will contain the MST
-    let mut mst: Graph<V, E> = Graph::new();
+    let mut mst: AdjacencyMap<G::Vertex, G::Weight> = BTreeMap::new();
+    grow_tree(graph, start, &mut mst);
+    mst
+}
+
+// adds to `mst` the minimum spanning tree of the component of `start`, which
+// must not be in `mst` yet
+fn grow_tree<G: Graph>(graph: &G, start: G::Vertex, mst: &mut AdjacencyMap<G::Vertex, G::Weight>)
+where
+    G::Weight: Ord + Add,
+{
     // a priority queue based on a binary heap, used to get the cheapest edge
     // the elements are an edge: the cost, destination and source
     let mut prio = BinaryHeap::new();
 
     mst.insert(start, BTreeMap::new());
 
-    for (v, c) in &graph[&start] {
+    for (v, c) in graph.neighbors(start) {
         // the heap is a max heap, we have to use Reverse when adding to simulate a min heap
-        prio.push(Reverse((*c, v, start)));
+        prio.push(Reverse((c, v, start)));
     }
 
     while let Some(Reverse((dist, t, prev))) = prio.pop() {
         // the destination of the edge has already been seen
-        if mst.contains_key(t) {
+        if mst.contains_key(&t) {
             continue;
         }
 
         // the destination is a new vertex
-        add_edge(&mut mst, prev, *t, dist);
+        add_edge(mst, prev, t, dist);
 
-        for (v, c) in &graph[t] {
-            if !mst.contains_key(v) {
-                prio.push(Reverse((*c, v, *t)));
+        for (v, c) in graph.neighbors(t) {
+            if !mst.contains_key(&v) {
+                prio.push(Reverse((c, v, t)));
             }
         }
     }
-
-    mst
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{add_edge, prim, Graph};
+    use super::{add_edge, prim, prim_with_start};
+    use crate::graph::AdjacencyMap;
     use std::collections::BTreeMap;
 
     #[test]
     fn empty() {
-        assert_eq!(prim::<usize, usize>(&BTreeMap::new()), BTreeMap::new());
+        assert_eq!(
+            prim::<AdjacencyMap<usize, usize>>(&BTreeMap::new()),
+            BTreeMap::new()
+        );
     }
 
     #[test]
     fn single_vertex() {
-        let mut graph: Graph<usize, usize> = BTreeMap::new();
+        let mut graph: AdjacencyMap<usize, usize> = BTreeMap::new();
         graph.insert(42, BTreeMap::new());
 
         assert_eq!(prim(&graph), graph);
@@ -196,4 +218,23 @@ mod tests {
 
         assert_eq!(prim(&graph), ans);
     }
+
+    #[test]
+    fn disconnected_graph() {
+        let mut graph = BTreeMap::new();
+        add_edge(&mut graph, 1, 2, 3);
+        add_edge(&mut graph, 2, 3, 1);
+        add_edge(&mut graph, 1, 3, 2);
+        add_edge(&mut graph, 4, 5, 7);
+        graph.insert(6, BTreeMap::new());
+
+        let mut ans = BTreeMap::new();
+        add_edge(&mut ans, 2, 3, 1);
+        add_edge(&mut ans, 1, 3, 2);
+        add_edge(&mut ans, 4, 5, 7);
+        ans.insert(6, BTreeMap::new());
+
+        assert_eq!(prim(&graph), ans);
+        assert_eq!(prim_with_start(&graph, 5).len(), 2);
+    }
 }
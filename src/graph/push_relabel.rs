@@ -0,0 +1,297 @@
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+
+use super::dinic_maxflow::{FlowEdge, FlowResultEdge, MaxFlow};
+
+// We assume that graph vertices are numbered from 1 to n.
+
+/// Highest-label push-relabel max flow, with the gap heuristic.
+///
+/// Instead of looking for augmenting paths, every vertex may hold more flow
+/// than it sends (its excess). An active vertex, one with some excess, pushes
+/// it to the neighbours that are one level lower, and is lifted (relabelled)
+/// when it can't. Always discharging the highest active vertex gives
+/// O(V^2.sqrt(E)), against O(V^2.E) for Dinic.
+pub struct PushRelabelMaxFlow<T> {
+    /// The label of each vertex. The flow only goes one label down.
+    height: Vec<usize>,
+
+    /// How much more flow went into each vertex than out of it
+    excess: Vec<T>,
+
+    /// The index of the next edge to try for each vertex
+    current_edge: Vec<usize>,
+
+    /// Holds wether the solution has already been calculated
+    network_solved: bool,
+
+    pub source: usize,
+    pub sink: usize,
+
+    /// Number of edges added to the residual network
+    pub num_edges: usize,
+    pub num_vertices: usize,
+
+    pub adj: Vec<Vec<usize>>,
+
+    /// The list of flow edges
+    pub edges: Vec<FlowEdge<T>>,
+}
+
+impl<T: Clone + Copy + Add + AddAssign + Sub<Output = T> + SubAssign + Neg + Ord + Default>
+    PushRelabelMaxFlow<T>
+{
+    pub fn new(source: usize, sink: usize, num_vertices: usize) -> Self {
+        PushRelabelMaxFlow {
+            height: vec![0; num_vertices + 1],
+            excess: vec![T::default(); num_vertices + 1],
+            current_edge: vec![0; num_vertices + 1],
+            network_solved: false,
+            source,
+            sink,
+            num_edges: 0,
+            num_vertices,
+            adj: vec![vec![]; num_vertices + 1],
+            edges: vec![],
+        }
+    }
+
+    #[inline]
+    pub fn add_edge(&mut self, source: usize, sink: usize, capacity: T) {
+        self.edges.push(FlowEdge::new(sink, capacity));
+        // Add the reverse edge with zero capacity
+        self.edges.push(FlowEdge::new(source, T::default()));
+        self.adj[source].push(self.num_edges);
+        self.adj[sink].push(self.num_edges + 1);
+        self.num_edges += 2;
+    }
+
+    // Sends as much as possible of the excess of v through edge e
+    fn push(&mut self, v: usize, e: usize) -> usize {
+        let u = self.edges[e].sink;
+        let pushed = std::cmp::min(self.excess[v], self.edges[e].capacity - self.edges[e].flow);
+        self.edges[e].flow += pushed;
+        self.edges[e ^ 1].flow -= pushed;
+        self.excess[v] -= pushed;
+        self.excess[u] += pushed;
+        u
+    }
+
+    pub fn find_maxflow(&mut self, _infinite_flow: T) -> T {
+        self.network_solved = true;
+        let n = self.num_vertices;
+        // Labels never go above 2n - 1 (n + 1 is used by the gap heuristic,
+        // so leave room for it when n is tiny)
+        let mut active: Vec<Vec<usize>> = vec![vec![]; 2 * n + 1];
+        // The number of vertices with each label, for the gap heuristic
+        let mut count = vec![0; 2 * n + 1];
+        self.height.fill(0);
+        self.excess.fill(T::default());
+        self.current_edge.fill(0);
+        self.height[self.source] = n;
+        count[0] = n - 1;
+        count[n] = 1;
+
+        // Saturate every edge out of the source
+        for i in 0..self.adj[self.source].len() {
+            let e = self.adj[self.source][i];
+            self.excess[self.source] = self.edges[e].capacity - self.edges[e].flow;
+            let u = self.push(self.source, e);
+            if u != self.source && u != self.sink && self.excess[u] > T::default() {
+                active[0].push(u);
+            }
+        }
+        self.excess[self.source] = T::default();
+        // A vertex can be added twice above, keep it only once
+        active[0].sort_unstable();
+        active[0].dedup();
+
+        let mut highest = 0;
+        loop {
+            while highest > 0 && active[highest].is_empty() {
+                highest -= 1;
+            }
+            let Some(v) = active[highest].pop() else {
+                break;
+            };
+            // Its label was raised by the gap heuristic, it will be found in
+            // its new bucket
+            if self.height[v] != highest {
+                continue;
+            }
+
+            // Discharge v
+            while self.excess[v] > T::default() {
+                if self.current_edge[v] == self.adj[v].len() {
+                    // Relabel: just above the lowest neighbour it can push to
+                    let old = self.height[v];
+                    let mut lowest = 2 * n - 1;
+                    for &e in self.adj[v].iter() {
+                        if self.edges[e].capacity > self.edges[e].flow {
+                            lowest = lowest.min(self.height[self.edges[e].sink] + 1);
+                        }
+                    }
+                    count[old] -= 1;
+                    self.height[v] = lowest;
+                    count[lowest] += 1;
+                    self.current_edge[v] = 0;
+
+                    // Nothing has label `old` anymore: the vertices above
+                    // it can't reach the sink, lift them above the source
+                    if count[old] == 0 && old < n {
+                        for w in 1..=n {
+                            if self.height[w] > old && self.height[w] < n {
+                                count[self.height[w]] -= 1;
+                                self.height[w] = n + 1;
+                                count[n + 1] += 1;
+                                if self.excess[w] > T::default() && w != v {
+                                    active[n + 1].push(w);
+                                    highest = highest.max(n + 1);
+                                }
+                            }
+                        }
+                    }
+                    continue;
+                }
+
+                let e = self.adj[v][self.current_edge[v]];
+                let u = self.edges[e].sink;
+                if self.edges[e].capacity > self.edges[e].flow
+                    && self.height[v] == self.height[u] + 1
+                {
+                    let was_active = self.excess[u] > T::default();
+                    self.push(v, e);
+                    if !was_active && u != self.source && u != self.sink {
+                        active[self.height[u]].push(u);
+                        highest = highest.max(self.height[u]);
+                    }
+                } else {
+                    self.current_edge[v] += 1;
+                }
+            }
+        }
+
+        self.excess[self.sink]
+    }
+
+    pub fn get_flow_edges(&mut self, infinite_flow: T) -> Vec<FlowResultEdge<T>> {
+        if !self.network_solved {
+            self.find_maxflow(infinite_flow);
+        }
+        let mut result = Vec::new();
+        for v in 1..self.adj.len() {
+            for &e_ind in self.adj[v].iter() {
+                let e = &self.edges[e_ind];
+                // Make sure that reverse edges from residual network are not
+                // included
+                if e.flow > T::default() {
+                    result.push(FlowResultEdge {
+                        source: v,
+                        sink: e.sink,
+                        flow: e.flow,
+                    });
+                }
+            }
+        }
+        result
+    }
+}
+
+impl<T: Clone + Copy + Add + AddAssign + Sub<Output = T> + SubAssign + Neg + Ord + Default>
+    MaxFlow<T> for PushRelabelMaxFlow<T>
+{
+    fn new(source: usize, sink: usize, num_vertices: usize) -> Self {
+        PushRelabelMaxFlow::new(source, sink, num_vertices)
+    }
+
+    fn add_edge(&mut self, source: usize, sink: usize, capacity: T) {
+        PushRelabelMaxFlow::add_edge(self, source, sink, capacity)
+    }
+
+    fn find_maxflow(&mut self, infinite_flow: T) -> T {
+        PushRelabelMaxFlow::find_maxflow(self, infinite_flow)
+    }
+
+    fn get_flow_edges(&mut self, infinite_flow: T) -> Vec<FlowResultEdge<T>> {
+        PushRelabelMaxFlow::get_flow_edges(self, infinite_flow)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::DinicMaxFlow;
+    use crate::math::PCG32;
+
+    fn random_network<M: MaxFlow<i64>>(
+        rng: &mut PCG32,
+        num_vertices: usize,
+        num_edges: usize,
+        max_capacity: u32,
+    ) -> M {
+        let mut flow = M::new(1, num_vertices, num_vertices);
+        for _ in 0..num_edges {
+            let u = (rng.get_u32() as usize % num_vertices) + 1;
+            let v = (rng.get_u32() as usize % num_vertices) + 1;
+            flow.add_edge(u, v, (rng.get_u32() % max_capacity) as i64);
+        }
+        flow
+    }
+
+    // Checks that the flow is conserved and returns how much leaves vertex 1
+    fn check_flow<M: MaxFlow<i64>>(flow: &mut M, num_vertices: usize) -> i64 {
+        let mut balance = vec![0; num_vertices + 1];
+        for e in flow.get_flow_edges(i64::MAX) {
+            balance[e.source] -= e.flow;
+            balance[e.sink] += e.flow;
+        }
+        for b in balance.iter().take(num_vertices).skip(2) {
+            assert_eq!(*b, 0);
+        }
+        assert_eq!(balance[1], -balance[num_vertices]);
+        balance[num_vertices]
+    }
+
+    #[test]
+    fn small_graph() {
+        let mut flow: PushRelabelMaxFlow<i32> = PushRelabelMaxFlow::new(1, 6, 6);
+        flow.add_edge(1, 2, 16);
+        flow.add_edge(1, 4, 13);
+        flow.add_edge(2, 3, 12);
+        flow.add_edge(3, 4, 9);
+        flow.add_edge(3, 6, 20);
+        flow.add_edge(4, 2, 4);
+        flow.add_edge(4, 5, 14);
+        flow.add_edge(5, 3, 7);
+        flow.add_edge(5, 6, 4);
+
+        assert_eq!(flow.find_maxflow(i32::MAX), 23);
+        let mut sm_out = [0; 7];
+        let mut sm_in = [0; 7];
+        for e in flow.get_flow_edges(i32::MAX) {
+            sm_out[e.source] += e.flow;
+            sm_in[e.sink] += e.flow;
+        }
+        for i in 2..=5 {
+            assert_eq!(sm_in[i], sm_out[i]);
+        }
+        assert_eq!(sm_out[1], 23);
+        assert_eq!(sm_in[6], 23);
+    }
+
+    #[test]
+    fn same_as_dinic() {
+        let mut rng = PCG32::new_default(1234);
+        for round in 0..200 {
+            let num_vertices = 2 + round % 15;
+            let num_edges = round % 60;
+            let seed = rng.get_u32() as u64;
+            let mut dinic: DinicMaxFlow<i64> =
+                random_network(&mut PCG32::new_default(seed), num_vertices, num_edges, 30);
+            let mut push_relabel: PushRelabelMaxFlow<i64> =
+                random_network(&mut PCG32::new_default(seed), num_vertices, num_edges, 30);
+            let expected = MaxFlow::find_maxflow(&mut dinic, i64::MAX);
+            assert_eq!(MaxFlow::find_maxflow(&mut push_relabel, i64::MAX), expected);
+            assert_eq!(check_flow(&mut push_relabel, num_vertices), expected);
+        }
+    }
+}
@@ -0,0 +1,403 @@
+/*
+Seeded random graph generators, to test graph algorithms on many inputs and
+benchmark them on large ones. Every generator takes a `PCG32`, so the same
+seed always gives the same graph.
+
+The graphs have their vertices numbered from 1 to n, and every vertex has an
+entry (possibly without neighbours). Undirected graphs have their edges in
+both directions. They are unweighted `Graph`s; `with_random_weights` turns
+them into weighted ones, and `adjacency_lists` into the `Vec<Vec<usize>>`
+taken by `StronglyConnectedComponents`, `HeavyLightDecomposition`...
+*/
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::{prufer_decode, AdjacencyList};
+use crate::math::PCG32;
+
+type UnweightedGraph = BTreeMap<usize, Vec<usize>>;
+
+// A uniform integer in [0, k)
+fn below(rng: &mut PCG32, k: usize) -> usize {
+    (rng.get_u64() % k as u64) as usize
+}
+
+// A uniform float in [0, 1)
+fn uniform(rng: &mut PCG32) -> f64 {
+    rng.get_u32() as f64 / (1u64 << 32) as f64
+}
+
+fn shuffle<T>(values: &mut [T], rng: &mut PCG32) {
+    for i in (1..values.len()).rev() {
+        values.swap(i, below(rng, i + 1));
+    }
+}
+
+fn empty_graph(n: usize) -> UnweightedGraph {
+    (1..=n).map(|v| (v, vec![])).collect()
+}
+
+fn add_edge(graph: &mut UnweightedGraph, u: usize, v: usize, directed: bool) {
+    graph.get_mut(&u).unwrap().push(v);
+    if !directed {
+        graph.get_mut(&v).unwrap().push(u);
+    }
+}
+
+// The pair of vertices number `k` among the n.(n - 1) ordered pairs (or the
+// n.(n - 1) / 2 unordered ones, as (u, v) with u < v) without self loops
+fn pair(n: usize, k: usize, directed: bool) -> (usize, usize) {
+    if directed {
+        let (u, j) = (k / (n - 1), k % (n - 1));
+        (u + 1, if j < u { j + 1 } else { j + 2 })
+    } else {
+        // the pairs are in increasing order of v: the w.(w - 1) / 2 pairs with
+        // v <= w come before the ones with v = w + 1
+        let mut w = ((((8 * k + 1) as f64).sqrt() + 1.0) / 2.0) as usize;
+        while w * (w - 1) / 2 > k {
+            w -= 1;
+        }
+        while (w + 1) * w / 2 <= k {
+            w += 1;
+        }
+        (k - w * (w - 1) / 2 + 1, w + 1)
+    }
+}
+
+fn num_pairs(n: usize, directed: bool) -> usize {
+    let ordered = n * n.saturating_sub(1);
+    if directed {
+        ordered
+    } else {
+        ordered / 2
+    }
+}
+
+// Erdős–Rényi G(n, p): every edge (without self loops) is there with
+// probability p, independently of the others. Instead of a coin flip for
+// each of the O(n^2) possible edges, the gaps between the edges that are
+// there are drawn directly, so it takes O(n + m).
+pub fn erdos_renyi_gnp(n: usize, p: f64, directed: bool, rng: &mut PCG32) -> UnweightedGraph {
+    let mut graph = empty_graph(n);
+    let total = num_pairs(n, directed);
+    if p <= 0.0 {
+        return graph;
+    }
+    let mut k = 0;
+    while k < total {
+        if p < 1.0 {
+            // the number of edges skipped follows a geometric distribution
+            let skip = ((1.0 - uniform(rng)).ln() / (1.0 - p).ln()).floor();
+            if skip >= (total - k) as f64 {
+                break;
+            }
+            k += skip as usize;
+        }
+        let (u, v) = pair(n, k, directed);
+        add_edge(&mut graph, u, v, directed);
+        k += 1;
+    }
+    graph
+}
+
+// Erdős–Rényi G(n, m): m different edges (without self loops) chosen
+// uniformly. Panics if there are fewer than m possible edges.
+pub fn erdos_renyi_gnm(n: usize, m: usize, directed: bool, rng: &mut PCG32) -> UnweightedGraph {
+    let total = num_pairs(n, directed);
+    assert!(m <= total, "a graph with {n} vertices can't have {m} edges");
+    // draw whichever of the edges or the missing edges are fewer
+    let dense = 2 * m > total;
+    let wanted = if dense { total - m } else { m };
+    let mut drawn = BTreeSet::new();
+    while drawn.len() < wanted {
+        drawn.insert(below(rng, total));
+    }
+    let mut graph = empty_graph(n);
+    let chosen: Vec<usize> = if dense {
+        (0..total).filter(|k| !drawn.contains(k)).collect()
+    } else {
+        drawn.into_iter().collect()
+    };
+    for k in chosen {
+        let (u, v) = pair(n, k, directed);
+        add_edge(&mut graph, u, v, directed);
+    }
+    graph
+}
+
+// A uniformly random labelled tree, decoded from a random Prüfer code
+pub fn random_tree(n: usize, rng: &mut PCG32) -> UnweightedGraph {
+    if n <= 1 {
+        return empty_graph(n);
+    }
+    let code: Vec<usize> = (2..n).map(|_| below(rng, n) + 1).collect();
+    let vertices: Vec<usize> = (1..=n).collect();
+    prufer_decode(&code, &vertices)
+}
+
+// A random directed acyclic graph: the vertices are put in a random order,
+// and every edge going forward in that order is there with probability p
+pub fn random_dag(n: usize, p: f64, rng: &mut PCG32) -> UnweightedGraph {
+    let mut order: Vec<usize> = (1..=n).collect();
+    shuffle(&mut order, rng);
+    let forward = erdos_renyi_gnp(n, p, false, rng);
+    let mut graph = empty_graph(n);
+    for (&u, neighbors) in forward.iter() {
+        for &v in neighbors.iter().filter(|&&v| u < v) {
+            add_edge(&mut graph, order[u - 1], order[v - 1], true);
+        }
+    }
+    graph
+}
+
+// The rows x columns grid, where each vertex is linked to the ones above,
+// below, left and right of it. The vertex in row r and column c (from 0) is
+// r * columns + c + 1.
+pub fn grid_graph(rows: usize, columns: usize) -> UnweightedGraph {
+    let mut graph = empty_graph(rows * columns);
+    for r in 0..rows {
+        for c in 0..columns {
+            let v = r * columns + c + 1;
+            if c + 1 < columns {
+                add_edge(&mut graph, v, v + 1, false);
+            }
+            if r + 1 < rows {
+                add_edge(&mut graph, v, v + columns, false);
+            }
+        }
+    }
+    graph
+}
+
+// Barabási–Albert preferential attachment: starting from a clique of m + 1
+// vertices, each new vertex is linked to m different older vertices, chosen
+// with a probability proportional to their degree. This gives the
+// heavy-tailed degrees of many real networks.
+pub fn barabasi_albert(n: usize, m: usize, rng: &mut PCG32) -> UnweightedGraph {
+    assert!(m >= 1 && n > m, "barabasi_albert needs 1 <= m < n");
+    let mut graph = empty_graph(n);
+    // every vertex appears once per edge it has, so drawing from it is
+    // drawing proportionally to the degrees
+    let mut endpoints = vec![];
+    for u in 1..=m + 1 {
+        for v in u + 1..=m + 1 {
+            add_edge(&mut graph, u, v, false);
+            endpoints.extend([u, v]);
+        }
+    }
+    for u in m + 2..=n {
+        let mut targets = BTreeSet::new();
+        while targets.len() < m {
+            targets.insert(endpoints[below(rng, endpoints.len())]);
+        }
+        for v in targets {
+            add_edge(&mut graph, u, v, false);
+            endpoints.extend([u, v]);
+        }
+    }
+    graph
+}
+
+// A random bipartite graph between the vertices 1..=left and
+// left + 1..=left + right, where each of the left x right edges is there
+// with probability p
+pub fn random_bipartite(left: usize, right: usize, p: f64, rng: &mut PCG32) -> UnweightedGraph {
+    let mut graph = empty_graph(left + right);
+    for u in 1..=left {
+        for v in left + 1..=left + right {
+            if uniform(rng) < p {
+                add_edge(&mut graph, u, v, false);
+            }
+        }
+    }
+    graph
+}
+
+// The same graph with a random weight between 1 and max_weight on each edge.
+// An edge and its reverse get the same weight, so undirected graphs stay
+// undirected.
+pub fn with_random_weights(
+    graph: &UnweightedGraph,
+    max_weight: u64,
+    rng: &mut PCG32,
+) -> AdjacencyList<usize, u64> {
+    let mut weights: BTreeMap<(usize, usize), u64> = BTreeMap::new();
+    let mut weighted: AdjacencyList<usize, u64> = BTreeMap::new();
+    for (&u, neighbors) in graph.iter() {
+        let edges = weighted.entry(u).or_default();
+        for &v in neighbors.iter() {
+            let w = *weights
+                .entry((u.min(v), u.max(v)))
+                .or_insert_with(|| rng.get_u64() % max_weight + 1);
+            edges.push((v, w));
+        }
+    }
+    weighted
+}
+
+// The neighbours of each vertex from 1 to n, indexed by vertex (index 0 is
+// left empty)
+pub fn adjacency_lists(graph: &UnweightedGraph) -> Vec<Vec<usize>> {
+    let n = graph.keys().next_back().copied().unwrap_or(0);
+    let mut adj = vec![vec![]; n + 1];
+    for (&u, neighbors) in graph.iter() {
+        adj[u] = neighbors.clone();
+    }
+    adj
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{
+        kruskal, prim, prufer_encode, topological_sort, Graph, StronglyConnectedComponents,
+    };
+
+    fn is_connected(graph: &UnweightedGraph) -> bool {
+        let adj = adjacency_lists(graph);
+        let mut seen = vec![false; adj.len()];
+        let mut stack = vec![1];
+        seen[1] = true;
+        while let Some(u) = stack.pop() {
+            for &v in adj[u].iter() {
+                if !seen[v] {
+                    seen[v] = true;
+                    stack.push(v);
+                }
+            }
+        }
+        seen[1..].iter().all(|&s| s)
+    }
+
+    fn no_self_loops_or_parallel_edges(graph: &UnweightedGraph) -> bool {
+        graph.iter().all(|(u, neighbors)| {
+            let distinct: BTreeSet<&usize> = neighbors.iter().collect();
+            distinct.len() == neighbors.len() && !distinct.contains(u)
+        })
+    }
+
+    #[test]
+    fn pairs_are_enumerated_once() {
+        for directed in [false, true] {
+            let n = 7;
+            let pairs: BTreeSet<(usize, usize)> = (0..num_pairs(n, directed))
+                .map(|k| pair(n, k, directed))
+                .collect();
+            assert_eq!(pairs.len(), num_pairs(n, directed));
+            assert!(pairs
+                .iter()
+                .all(|&(u, v)| u != v && 1 <= u.min(v) && u.max(v) <= n && (directed || u < v)));
+        }
+    }
+
+    #[test]
+    fn same_seed_same_graph() {
+        let a = erdos_renyi_gnp(50, 0.1, false, &mut PCG32::new_default(7));
+        let b = erdos_renyi_gnp(50, 0.1, false, &mut PCG32::new_default(7));
+        let c = erdos_renyi_gnp(50, 0.1, false, &mut PCG32::new_default(8));
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(
+            barabasi_albert(100, 3, &mut PCG32::new_default(1)),
+            barabasi_albert(100, 3, &mut PCG32::new_default(1))
+        );
+    }
+
+    #[test]
+    fn erdos_renyi() {
+        let mut rng = PCG32::new_default(3);
+        let sparse = erdos_renyi_gnp(400, 0.05, false, &mut rng);
+        let edges = sparse.num_edges() / 2;
+        // 3990 edges are expected, with a standard deviation of about 61
+        assert!((3700..4300).contains(&edges), "{edges} edges");
+        assert!(no_self_loops_or_parallel_edges(&sparse));
+
+        assert_eq!(erdos_renyi_gnp(10, 0.0, true, &mut rng).num_edges(), 0);
+        assert_eq!(erdos_renyi_gnp(10, 1.0, true, &mut rng).num_edges(), 90);
+        assert_eq!(erdos_renyi_gnp(10, 1.0, false, &mut rng).num_edges(), 90);
+
+        for (m, directed) in [
+            (0, false),
+            (30, false),
+            (44, false),
+            (45, false),
+            (70, true),
+        ] {
+            let graph = erdos_renyi_gnm(10, m, directed, &mut rng);
+            assert_eq!(graph.num_vertices(), 10);
+            assert_eq!(graph.num_edges(), if directed { m } else { 2 * m });
+            assert!(no_self_loops_or_parallel_edges(&graph));
+        }
+    }
+
+    #[test]
+    fn trees_and_dags() {
+        let mut rng = PCG32::new_default(11);
+        assert_eq!(random_tree(1, &mut rng).num_edges(), 0);
+        for n in 2..40 {
+            let tree = random_tree(n, &mut rng);
+            assert_eq!(tree.num_vertices(), n);
+            assert_eq!(tree.num_edges(), 2 * (n - 1));
+            assert!(is_connected(&tree));
+            if n > 2 {
+                assert_eq!(prufer_encode(&tree).len(), n - 2);
+            }
+
+            let dag = random_dag(n, 0.3, &mut rng);
+            assert_eq!(topological_sort(&dag).unwrap().len(), n);
+            let mut scc = StronglyConnectedComponents::new(n);
+            scc.find_components(&adjacency_lists(&dag));
+            assert_eq!(scc.num_components, n);
+        }
+    }
+
+    #[test]
+    fn grids_and_preferential_attachment() {
+        let grid = grid_graph(4, 6);
+        assert_eq!(grid.num_vertices(), 24);
+        assert_eq!(grid.num_edges(), 2 * (4 * 5 + 3 * 6));
+        assert_eq!(grid[&1], vec![2, 7]);
+        assert!(is_connected(&grid));
+
+        let mut rng = PCG32::new_default(5);
+        let (n, m) = (500, 3);
+        let graph = barabasi_albert(n, m, &mut rng);
+        assert_eq!(graph.num_edges(), 2 * (m * (m + 1) / 2 + (n - m - 1) * m));
+        assert!(no_self_loops_or_parallel_edges(&graph));
+        assert!(is_connected(&graph));
+        // the oldest vertices collect many more edges than the average
+        let max_degree = graph
+            .values()
+            .map(|neighbors| neighbors.len())
+            .max()
+            .unwrap();
+        assert!(max_degree > 5 * 2 * m, "max degree {max_degree}");
+    }
+
+    #[test]
+    fn bipartite() {
+        let mut rng = PCG32::new_default(9);
+        let graph = random_bipartite(20, 30, 0.2, &mut rng);
+        assert_eq!(graph.num_vertices(), 50);
+        assert!(graph.num_edges() > 0);
+        for (&u, neighbors) in graph.iter() {
+            assert!(neighbors.iter().all(|&v| (u <= 20) != (v <= 20)));
+        }
+    }
+
+    #[test]
+    fn minimum_spanning_trees_of_random_graphs() {
+        // a property test made possible by the generators
+        let mut rng = PCG32::new_default(2);
+        for _ in 0..20 {
+            let graph = erdos_renyi_gnm(60, 150, false, &mut rng);
+            let weighted = with_random_weights(&graph, 1000, &mut rng);
+            let prim_cost: u64 = prim(&weighted)
+                .values()
+                .flat_map(|neighbors| neighbors.values())
+                .sum::<u64>()
+                / 2;
+            assert_eq!(kruskal(&weighted).0, prim_cost);
+        }
+    }
+}
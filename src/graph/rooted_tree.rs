@@ -0,0 +1,548 @@
+/*
+A rooted tree whose vertices can have any (ordered) labels.
+
+The vertices are numbered from 1 to n by `enumerate_graph` (in the order of
+their labels), so `adjacency` can be given directly to the structures that
+expect vertices numbered this way (`LowestCommonAncestorOnline`,
+`CentroidDecomposition`, `HeavyLightDecomposition`...), and the labels are
+kept to translate the results back.
+
+Everything is computed without recursion, so deep trees (paths) are fine.
+
+On top of it:
+- the diameter (a longest path), the centers (the middle of the diameter) and
+  the centroids (the vertices whose removal leaves components of at most n/2
+  vertices), in O(n);
+- the Euler tour, and the range of each subtree in the preorder, so that
+  subtree queries become range queries;
+- rerooting DP: a value computed for every vertex as if it were the root, in
+  O(n) calls to the given functions instead of O(n^2);
+- AHU (Aho, Hopcroft, Ullman) isomorphism: two rooted trees are isomorphic if
+  the sorted lists of the canonical names of the children of their roots are
+  the same, recursively. For unrooted trees, the trees are rooted at their
+  centers, since an isomorphism maps centers to centers.
+*/
+
+use std::collections::{BTreeMap, VecDeque};
+use std::ops::Range;
+
+use super::enumerate_graph;
+
+type Graph<Vertex> = BTreeMap<Vertex, Vec<Vertex>>;
+
+pub struct RootedTree<V> {
+    // sorted, vertex v has the label labels[v - 1]
+    labels: Vec<V>,
+    // The tree as numbered by `enumerate_graph`, adjacency[0] is empty
+    pub adjacency: Vec<Vec<usize>>,
+    pub root: usize,
+    // The parent of each vertex (0 for the root), its depth (the root has
+    // depth 0) and the number of vertices in its subtree
+    pub parent: Vec<usize>,
+    pub depth: Vec<usize>,
+    pub subtree_size: Vec<usize>,
+    // The vertices in preorder, and the position of each vertex in it. The
+    // subtree of v takes the positions from position[v] to
+    // position[v] + subtree_size[v] - 1
+    pub order: Vec<usize>,
+    pub position: Vec<usize>,
+}
+
+impl<V: Ord + Clone> RootedTree<V> {
+    // the tree is given as an undirected graph: every edge is stored in both
+    // directions, and every vertex has an entry
+    //
+    // returns None if the graph is not a tree (including when an edge is
+    // missing its other direction, or goes to a vertex without an entry) or
+    // doesn't contain root
+    pub fn new(tree: &Graph<V>, root: &V) -> Option<Self> {
+        let labels: Vec<V> = tree.keys().cloned().collect();
+        let root = labels.binary_search(root).ok()? + 1;
+        if tree.values().flatten().any(|v| !tree.contains_key(v)) {
+            return None;
+        }
+        Self::from_adjacency(labels, enumerate_graph(tree), root)
+    }
+
+    fn from_adjacency(labels: Vec<V>, adjacency: Vec<Vec<usize>>, root: usize) -> Option<Self> {
+        let n = labels.len();
+        let mut edges: Vec<(usize, usize)> = adjacency
+            .iter()
+            .enumerate()
+            .flat_map(|(v, neighbors)| neighbors.iter().map(move |&u| (v, u)))
+            .collect();
+        if n == 0 || edges.len() != 2 * (n - 1) {
+            return None;
+        }
+        // every edge must be stored in both directions (as many times)
+        let mut reversed: Vec<(usize, usize)> = edges.iter().map(|&(v, u)| (u, v)).collect();
+        edges.sort_unstable();
+        reversed.sort_unstable();
+        if edges != reversed {
+            return None;
+        }
+
+        let mut parent = vec![0; n + 1];
+        let mut depth = vec![0; n + 1];
+        let mut visited = vec![false; n + 1];
+        let mut order = Vec::with_capacity(n);
+        let mut stack = vec![root];
+        visited[root] = true;
+        // a stack gives a preorder: a subtree is finished before the vertices
+        // under it in the stack are popped
+        while let Some(v) = stack.pop() {
+            order.push(v);
+            for &u in adjacency[v].iter().rev() {
+                if !visited[u] {
+                    visited[u] = true;
+                    parent[u] = v;
+                    depth[u] = depth[v] + 1;
+                    stack.push(u);
+                }
+            }
+        }
+        // with n - 1 edges, it is a tree if and only if it is connected
+        if order.len() != n {
+            return None;
+        }
+
+        let mut position = vec![0; n + 1];
+        for (i, &v) in order.iter().enumerate() {
+            position[v] = i;
+        }
+        let mut subtree_size = vec![1; n + 1];
+        subtree_size[0] = 0;
+        for &v in order.iter().rev().filter(|&&v| v != root) {
+            subtree_size[parent[v]] += subtree_size[v];
+        }
+
+        Some(RootedTree {
+            labels,
+            adjacency,
+            root,
+            parent,
+            depth,
+            subtree_size,
+            order,
+            position,
+        })
+    }
+
+    // the same tree, rooted at another vertex
+    pub fn rerooted(&self, root: usize) -> Self {
+        Self::from_adjacency(self.labels.clone(), self.adjacency.clone(), root).unwrap()
+    }
+
+    pub fn num_vertices(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn label(&self, v: usize) -> &V {
+        &self.labels[v - 1]
+    }
+
+    // the number of the vertex with this label
+    pub fn vertex(&self, label: &V) -> Option<usize> {
+        self.labels.binary_search(label).ok().map(|i| i + 1)
+    }
+
+    pub fn children(&self, v: usize) -> impl Iterator<Item = usize> + '_ {
+        self.adjacency[v]
+            .iter()
+            .copied()
+            .filter(move |&u| u != self.parent[v])
+    }
+
+    // the positions of the subtree of v in `order`
+    pub fn subtree_range(&self, v: usize) -> Range<usize> {
+        self.position[v]..self.position[v] + self.subtree_size[v]
+    }
+
+    pub fn is_ancestor(&self, ancestor: usize, v: usize) -> bool {
+        self.subtree_range(ancestor).contains(&self.position[v])
+    }
+
+    // the vertices in the order they are met by a walk around the tree, which
+    // goes back to a vertex after each of its children (2n - 1 vertices)
+    pub fn euler_tour(&self) -> Vec<usize> {
+        let mut tour = vec![self.root];
+        let mut stack = vec![(self.root, 0)];
+        while let Some((v, next_child)) = stack.last_mut() {
+            let v = *v;
+            match self.adjacency[v][*next_child..]
+                .iter()
+                .position(|&u| u != self.parent[v])
+            {
+                Some(i) => {
+                    let u = self.adjacency[v][*next_child + i];
+                    *next_child += i + 1;
+                    tour.push(u);
+                    stack.push((u, 0));
+                }
+                None => {
+                    stack.pop();
+                    if let Some(&(p, _)) = stack.last() {
+                        tour.push(p);
+                    }
+                }
+            }
+        }
+        tour
+    }
+
+    // the distance from start to every vertex, and the previous vertex on the
+    // path from start
+    fn distances_from(&self, start: usize) -> (Vec<usize>, Vec<usize>) {
+        let n = self.num_vertices();
+        let mut distance = vec![usize::MAX; n + 1];
+        let mut previous = vec![0; n + 1];
+        let mut queue = VecDeque::from([start]);
+        distance[start] = 0;
+        while let Some(v) = queue.pop_front() {
+            for &u in &self.adjacency[v] {
+                if distance[u] == usize::MAX {
+                    distance[u] = distance[v] + 1;
+                    previous[u] = v;
+                    queue.push_back(u);
+                }
+            }
+        }
+        (distance, previous)
+    }
+
+    // a longest path of the tree, as its vertices in order
+    pub fn diameter(&self) -> Vec<usize> {
+        // the farthest vertex from any vertex is an end of a diameter
+        let first_end = *self.order.iter().max_by_key(|&&v| self.depth[v]).unwrap();
+        let (distance, previous) = self.distances_from(first_end);
+        let mut v = (1..=self.num_vertices())
+            .max_by_key(|&v| distance[v])
+            .unwrap();
+        let mut path = vec![v];
+        while v != first_end {
+            v = previous[v];
+            path.push(v);
+        }
+        path
+    }
+
+    // the vertices whose greatest distance to the other vertices is the
+    // smallest: the one or two vertices in the middle of a diameter
+    pub fn centers(&self) -> Vec<usize> {
+        let path = self.diameter();
+        let middle = path.len() / 2;
+        let mut centers = if path.len() % 2 == 1 {
+            vec![path[middle]]
+        } else {
+            vec![path[middle - 1], path[middle]]
+        };
+        centers.sort_unstable();
+        centers
+    }
+
+    // the one or two vertices whose removal leaves components of at most n/2
+    // vertices
+    pub fn centroids(&self) -> Vec<usize> {
+        let n = self.num_vertices();
+        (1..=n)
+            .filter(|&v| {
+                let largest_child = self
+                    .children(v)
+                    .map(|u| self.subtree_size[u])
+                    .max()
+                    .unwrap_or(0);
+                largest_child.max(n - self.subtree_size[v]) <= n / 2
+            })
+            .collect()
+    }
+
+    // rerooting DP: for every vertex v, the value of the whole tree rooted at
+    // v, where the value of a tree is `finish(children, v)` with `children`
+    // the `merge` of the values of the subtrees of the children of v (or
+    // `identity` if there are none). `merge` must be associative and
+    // commutative, with `identity` as neutral element.
+    //
+    // returns the values indexed by vertex (the value at index 0 is
+    // `identity`)
+    pub fn reroot<T, M, F>(&self, identity: T, merge: M, finish: F) -> Vec<T>
+    where
+        T: Clone,
+        M: Fn(&T, &T) -> T,
+        F: Fn(&T, usize) -> T,
+    {
+        let n = self.num_vertices();
+        // down[v]: the value of the subtree of v, for the current root
+        let mut down = vec![identity.clone(); n + 1];
+        for &v in self.order.iter().rev() {
+            let children = self
+                .children(v)
+                .fold(identity.clone(), |acc, u| merge(&acc, &down[u]));
+            down[v] = finish(&children, v);
+        }
+
+        // up[v]: the value of the tree hanging from the parent of v when v is
+        // taken out, rooted at the parent of v
+        let mut up = vec![identity.clone(); n + 1];
+        let mut answer = vec![identity.clone(); n + 1];
+        for &v in &self.order {
+            // the values around v: the parent side first, then the children
+            let mut around = Vec::with_capacity(self.adjacency[v].len());
+            if v != self.root {
+                around.push((self.parent[v], up[v].clone()));
+            }
+            around.extend(self.children(v).map(|u| (u, down[u].clone())));
+
+            // prefix[i] merges the values before i, suffix[i] those from i
+            let mut prefix = vec![identity.clone()];
+            for (_, value) in &around {
+                prefix.push(merge(prefix.last().unwrap(), value));
+            }
+            let mut suffix = vec![identity.clone(); around.len() + 1];
+            for i in (0..around.len()).rev() {
+                suffix[i] = merge(&around[i].1, &suffix[i + 1]);
+            }
+
+            answer[v] = finish(&prefix[around.len()], v);
+            for (i, &(u, _)) in around.iter().enumerate() {
+                if u != self.parent[v] {
+                    up[u] = finish(&merge(&prefix[i], &suffix[i + 1]), v);
+                }
+            }
+        }
+        answer
+    }
+
+    // the AHU name of every subtree: two subtrees have the same name if and
+    // only if they are isomorphic. `names` is shared between the trees that
+    // are compared.
+    fn subtree_names(&self, names: &mut BTreeMap<Vec<usize>, usize>) -> Vec<usize> {
+        let mut name = vec![0; self.num_vertices() + 1];
+        for &v in self.order.iter().rev() {
+            let mut children: Vec<usize> = self.children(v).map(|u| name[u]).collect();
+            children.sort_unstable();
+            let next = names.len();
+            name[v] = *names.entry(children).or_insert(next);
+        }
+        name
+    }
+
+    // whether there is a bijection between the vertices of the two trees that
+    // keeps the edges and maps the root to the root
+    pub fn is_isomorphic_rooted<W: Ord + Clone>(&self, other: &RootedTree<W>) -> bool {
+        if self.num_vertices() != other.num_vertices() {
+            return false;
+        }
+        let mut names = BTreeMap::new();
+        let mine = self.subtree_names(&mut names);
+        let theirs = other.subtree_names(&mut names);
+        mine[self.root] == theirs[other.root]
+    }
+
+    // same as `is_isomorphic_rooted`, but the roots don't matter
+    pub fn is_isomorphic<W: Ord + Clone>(&self, other: &RootedTree<W>) -> bool {
+        let theirs = other.centers();
+        self.num_vertices() == other.num_vertices()
+            && self.centers().len() == theirs.len()
+            && self.centers().into_iter().any(|center| {
+                self.rerooted(center)
+                    .is_isomorphic_rooted(&other.rerooted(theirs[0]))
+            })
+    }
+
+    // a hash of the rooted tree that only depends on its shape: isomorphic
+    // trees have the same hash, and different ones almost never do
+    pub fn canonical_hash(&self) -> u64 {
+        let mut hash = vec![0u64; self.num_vertices() + 1];
+        for &v in self.order.iter().rev() {
+            let mut children: Vec<u64> = self.children(v).map(|u| hash[u]).collect();
+            children.sort_unstable();
+            hash[v] = children.into_iter().fold(0x9e37_79b9_7f4a_7c15, |acc, h| {
+                mix(acc.wrapping_mul(0x0100_0000_01b3) ^ h)
+            });
+        }
+        hash[self.root]
+    }
+
+    // same as `canonical_hash`, but the root doesn't matter
+    pub fn unrooted_canonical_hash(&self) -> u64 {
+        self.centers()
+            .into_iter()
+            .map(|center| self.rerooted(center).canonical_hash())
+            .min()
+            .unwrap()
+    }
+}
+
+// the finalizer of SplitMix64, so that close values give unrelated hashes
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{prufer_decode, random_tree};
+    use crate::math::PCG32;
+
+    fn add_edge<V: Ord + Clone>(graph: &mut Graph<V>, a: V, b: V) {
+        graph.entry(a.clone()).or_default().push(b.clone());
+        graph.entry(b).or_default().push(a);
+    }
+
+    fn labels<V: Ord + Clone>(tree: &RootedTree<V>, vertices: &[usize]) -> Vec<V> {
+        vertices.iter().map(|&v| tree.label(v).clone()).collect()
+    }
+
+    //        a
+    //      / | \
+    //     b  c  d
+    //    / \     \
+    //   e   f     g
+    //             |
+    //             h
+    fn example() -> RootedTree<char> {
+        let mut graph = Graph::new();
+        for (u, v) in [
+            ('a', 'b'),
+            ('a', 'c'),
+            ('a', 'd'),
+            ('b', 'e'),
+            ('b', 'f'),
+            ('d', 'g'),
+            ('g', 'h'),
+        ] {
+            add_edge(&mut graph, u, v);
+        }
+        RootedTree::new(&graph, &'a').unwrap()
+    }
+
+    #[test]
+    fn structure() {
+        let tree = example();
+        assert_eq!(tree.num_vertices(), 8);
+        assert_eq!(tree.vertex(&'d'), Some(4));
+        assert_eq!(tree.vertex(&'z'), None);
+        assert_eq!(*tree.label(tree.root), 'a');
+        assert_eq!(
+            labels(&tree, &tree.order),
+            "abefcdgh".chars().collect::<Vec<_>>()
+        );
+
+        let b = tree.vertex(&'b').unwrap();
+        assert_eq!(tree.subtree_size[b], 3);
+        assert_eq!(tree.depth[tree.vertex(&'h').unwrap()], 3);
+        let subtree: Vec<usize> = tree.subtree_range(b).map(|i| tree.order[i]).collect();
+        assert_eq!(labels(&tree, &subtree), vec!['b', 'e', 'f']);
+        assert!(tree.is_ancestor(b, tree.vertex(&'f').unwrap()));
+        assert!(!tree.is_ancestor(b, tree.vertex(&'c').unwrap()));
+
+        assert_eq!(
+            labels(&tree, &tree.euler_tour()),
+            "abebfbacadghgda".chars().collect::<Vec<_>>()
+        );
+
+        // not trees
+        let mut graph = Graph::new();
+        add_edge(&mut graph, 1, 2);
+        add_edge(&mut graph, 2, 3);
+        add_edge(&mut graph, 3, 1);
+        graph.insert(4, vec![]);
+        assert!(RootedTree::new(&graph, &1).is_none());
+        assert!(RootedTree::new(&Graph::<u32>::new(), &1).is_none());
+        // 2(n - 1) edges, all reachable from 1, but only in one direction
+        let graph = Graph::from([(1, vec![2, 3]), (2, vec![3]), (3, vec![1])]);
+        assert!(RootedTree::new(&graph, &1).is_none());
+        // 2 is a neighbour of 1 without an entry
+        let graph = Graph::from([(1, vec![2])]);
+        assert!(RootedTree::new(&graph, &1).is_none());
+        let graph = Graph::from([(1, vec![2]), (3, vec![1])]);
+        assert!(RootedTree::new(&graph, &1).is_none());
+    }
+
+    #[test]
+    fn diameter_centers_and_centroids() {
+        let tree = example();
+        let diameter = labels(&tree, &tree.diameter());
+        assert_eq!(diameter.len(), 6);
+        assert!(
+            diameter == vec!['e', 'b', 'a', 'd', 'g', 'h']
+                || diameter == vec!['f', 'b', 'a', 'd', 'g', 'h']
+        );
+        assert_eq!(labels(&tree, &tree.centers()), vec!['a', 'd']);
+        assert_eq!(labels(&tree, &tree.centroids()), vec!['a']);
+
+        let mut graph = Graph::new();
+        add_edge(&mut graph, 1, 2);
+        let tree = RootedTree::new(&graph, &1).unwrap();
+        assert_eq!(tree.centroids(), vec![1, 2]);
+        assert_eq!(tree.centers(), vec![1, 2]);
+        let tree = RootedTree::new(&Graph::from([(7, vec![])]), &7).unwrap();
+        assert_eq!(tree.diameter(), vec![1]);
+        assert_eq!(tree.centers(), vec![1]);
+    }
+
+    #[test]
+    fn sum_of_distances_by_rerooting() {
+        let mut rng = PCG32::new_default(4242);
+        for n in [1, 2, 3, 10, 57] {
+            let tree = RootedTree::new(&random_tree(n, &mut rng), &1).unwrap();
+            // (number of vertices, sum of their distances to the root)
+            let answer = tree.reroot(
+                (0, 0),
+                |a: &(usize, usize), b: &(usize, usize)| (a.0 + b.0, a.1 + b.1),
+                |children, _| (children.0 + 1, children.1 + children.0),
+            );
+            for (v, &value) in answer.iter().enumerate().skip(1) {
+                let (distance, _) = tree.distances_from(v);
+                assert_eq!(value, (n, distance[1..].iter().sum()));
+            }
+        }
+    }
+
+    #[test]
+    fn isomorphism() {
+        let mut rng = PCG32::new_default(17);
+        for _ in 0..30 {
+            let n = 1 + rng.get_u32() as usize % 30;
+            let graph = random_tree(n, &mut rng);
+            // the same tree with shuffled labels
+            let mut shuffled: Vec<usize> = (1..=n).collect();
+            for i in (1..n).rev() {
+                shuffled.swap(i, rng.get_u32() as usize % (i + 1));
+            }
+            let relabelled: Graph<String> = graph
+                .iter()
+                .map(|(v, neighbors)| {
+                    let name = |v: &usize| format!("v{}", shuffled[v - 1]);
+                    (name(v), neighbors.iter().map(name).collect())
+                })
+                .collect();
+
+            let a = RootedTree::new(&graph, &1).unwrap();
+            let b = RootedTree::new(&relabelled, &format!("v{}", shuffled[0])).unwrap();
+            assert!(a.is_isomorphic_rooted(&b));
+            assert_eq!(a.canonical_hash(), b.canonical_hash());
+            let other_root = b.rerooted(b.vertex(&format!("v{}", shuffled[n - 1])).unwrap());
+            assert!(a.is_isomorphic(&other_root));
+            assert_eq!(
+                a.unrooted_canonical_hash(),
+                other_root.unrooted_canonical_hash()
+            );
+        }
+
+        // a path and a star of 4 vertices
+        let path = RootedTree::new(&prufer_decode(&[2, 3], &[1, 2, 3, 4]), &1).unwrap();
+        let star = RootedTree::new(&prufer_decode(&[1, 1], &[1, 2, 3, 4]), &1).unwrap();
+        assert!(!path.is_isomorphic(&star));
+        assert_ne!(
+            path.unrooted_canonical_hash(),
+            star.unrooted_canonical_hash()
+        );
+        // the same path rooted at an end or in the middle
+        assert!(path.is_isomorphic(&path.rerooted(2)));
+        assert!(!path.is_isomorphic_rooted(&path.rerooted(2)));
+        assert_ne!(path.canonical_hash(), path.rerooted(2).canonical_hash());
+    }
+}
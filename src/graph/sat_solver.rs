@@ -0,0 +1,563 @@
+/*
+A conflict-driven clause learning (CDCL) solver for the boolean satisfiability
+problem: given clauses (disjunctions of literals), find values for the
+variables that satisfy all of them, or prove that there are none. Variables
+are numbered from 1 to n, and the literal -m is the negation of the variable m,
+like in `solve_two_satisfiability` (which is faster on clauses of two
+literals).
+
+The solver assigns variables one by one (decisions), and after each one
+deduces the values forced by clauses that have a single literal left that is
+not false (unit propagation). When a clause becomes false (a conflict), it
+walks back the implications to the first unique implication point: the only
+literal of the last decision level on a cut of the implication graph. The
+negation of this cut is a new clause implied by the formula, that is learnt,
+and the solver backtracks to the second highest level in it, where it forces
+a new value (the clause is unit there). A conflict at level 0 proves that
+the formula is unsatisfiable.
+
+It uses the usual techniques of modern solvers:
+- two watched literals: a clause is only looked at when one of two of its
+  literals that are not false becomes false, and nothing is done on
+  backtracking;
+- VSIDS: the next decision is the variable with the highest activity, which
+  grows each time the variable takes part in a conflict and decays over time,
+  with the last value of the variable (phase saving);
+- restarts: after a number of conflicts following the Luby sequence, all the
+  decisions are undone (the learnt clauses are kept).
+
+Learnt clauses are never deleted, which is fine up to a few hundred thousand
+conflicts. `parse_dimacs_cnf` and `write_dimacs_solution` read and write the
+formats used by SAT competitions.
+*/
+
+use std::collections::BinaryHeap;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SatResult {
+    // model[v] is the value of the variable v (model[0] is unused)
+    Satisfiable(Vec<bool>),
+    Unsatisfiable,
+}
+
+// Each clause is a list of literals, between -n and n (but not 0). An empty
+// clause can't be satisfied.
+pub fn solve_sat(clauses: &[Vec<i64>], num_variables: usize) -> SatResult {
+    let mut solver = Solver::new(num_variables);
+    for clause in clauses {
+        if !solver.add_clause(clause) {
+            return SatResult::Unsatisfiable;
+        }
+    }
+    solver.solve()
+}
+
+// The values of literals
+const TRUE: i8 = 1;
+const FALSE: i8 = -1;
+const UNASSIGNED: i8 = 0;
+
+// How many conflicts are allowed before the first restart, the next ones
+// being multiplied by the Luby sequence
+const RESTART_BASE: u64 = 100;
+const ACTIVITY_DECAY: f64 = 0.95;
+
+// Literals are numbered like the vertices of the implication graph of
+// `solve_two_satisfiability`: 2m for the variable m, 2m + 1 for its
+// negation, so the negation of l is l ^ 1 and its variable l >> 1
+fn literal(x: i64, num_variables: usize) -> usize {
+    let var = x.unsigned_abs() as usize;
+    assert!(
+        var >= 1 && var <= num_variables,
+        "literal {x} is not between 1 and {num_variables} (or their negations)"
+    );
+    if x < 0 {
+        (var << 1) + 1
+    } else {
+        var << 1
+    }
+}
+
+struct Solver {
+    num_variables: usize,
+    clauses: Vec<Vec<usize>>,
+    // watches[l]: the clauses that have l as one of their first two literals,
+    // which are the watched ones
+    watches: Vec<Vec<usize>>,
+    // The value of each literal
+    value: Vec<i8>,
+    // For each variable, the decision level where it was assigned, and the
+    // clause that forced its value (None for decisions)
+    level: Vec<usize>,
+    reason: Vec<Option<usize>>,
+    // The true literals in the order they were assigned, and where each
+    // decision level starts in it
+    trail: Vec<usize>,
+    trail_limits: Vec<usize>,
+    // The literals of the trail before this index were propagated
+    propagated: usize,
+    activity: Vec<f64>,
+    increment: f64,
+    // (activity, variable) for every unassigned variable, along with stale
+    // entries that are skipped when popped (an activity that changed, or a
+    // variable that was assigned since)
+    heap: BinaryHeap<(u64, usize)>,
+    // The last value of each variable, that is tried first
+    phase: Vec<bool>,
+    seen: Vec<bool>,
+}
+
+impl Solver {
+    fn new(num_variables: usize) -> Self {
+        let n = num_variables + 1;
+        Solver {
+            num_variables,
+            clauses: vec![],
+            watches: vec![vec![]; 2 * n],
+            value: vec![UNASSIGNED; 2 * n],
+            level: vec![0; n],
+            reason: vec![None; n],
+            trail: vec![],
+            trail_limits: vec![],
+            propagated: 0,
+            activity: vec![0.0; n],
+            increment: 1.0,
+            heap: (1..n).map(|v| (0f64.to_bits(), v)).collect(),
+            phase: vec![false; n],
+            seen: vec![false; n],
+        }
+    }
+
+    fn decision_level(&self) -> usize {
+        self.trail_limits.len()
+    }
+
+    fn assign(&mut self, lit: usize, reason: Option<usize>) {
+        self.value[lit] = TRUE;
+        self.value[lit ^ 1] = FALSE;
+        self.level[lit >> 1] = self.decision_level();
+        self.reason[lit >> 1] = reason;
+        self.trail.push(lit);
+    }
+
+    // Adds a clause before solving, simplified with the values of level 0.
+    // Returns false if the formula is unsatisfiable.
+    fn add_clause(&mut self, clause: &[i64]) -> bool {
+        let mut lits: Vec<usize> = clause
+            .iter()
+            .map(|&x| literal(x, self.num_variables))
+            .collect();
+        lits.sort_unstable();
+        lits.dedup();
+        // l and its negation are next to each other once sorted
+        if lits.windows(2).any(|pair| pair[0] ^ 1 == pair[1]) {
+            return true;
+        }
+        if lits.iter().any(|&l| self.value[l] == TRUE) {
+            return true;
+        }
+        lits.retain(|&l| self.value[l] == UNASSIGNED);
+        match lits.len() {
+            0 => false,
+            1 => {
+                self.assign(lits[0], None);
+                self.propagate().is_none()
+            }
+            _ => {
+                self.attach(lits);
+                true
+            }
+        }
+    }
+
+    // Adds a clause watching its first two literals, and returns its index
+    fn attach(&mut self, lits: Vec<usize>) -> usize {
+        let index = self.clauses.len();
+        self.watches[lits[0]].push(index);
+        self.watches[lits[1]].push(index);
+        self.clauses.push(lits);
+        index
+    }
+
+    // Unit propagation, returns a clause with all its literals false if
+    // there is one
+    fn propagate(&mut self) -> Option<usize> {
+        while self.propagated < self.trail.len() {
+            let false_lit = self.trail[self.propagated] ^ 1;
+            self.propagated += 1;
+            let mut watchers = std::mem::take(&mut self.watches[false_lit]);
+            let mut conflict = None;
+            let mut i = 0;
+            while i < watchers.len() {
+                let c = watchers[i];
+                let clause = &mut self.clauses[c];
+                // the false literal is put second, so that the first one is
+                // the implied literal if the clause is unit
+                if clause[0] == false_lit {
+                    clause.swap(0, 1);
+                }
+                let first = clause[0];
+                if self.value[first] == TRUE {
+                    i += 1;
+                    continue;
+                }
+                if let Some(k) = (2..clause.len()).find(|&k| self.value[clause[k]] != FALSE) {
+                    clause.swap(1, k);
+                    self.watches[clause[1]].push(c);
+                    watchers.swap_remove(i);
+                    continue;
+                }
+                if self.value[first] == FALSE {
+                    conflict = Some(c);
+                    break;
+                }
+                self.assign(first, Some(c));
+                i += 1;
+            }
+            // no clause started watching the false literal meanwhile, but
+            // this keeps them if it ever happens
+            watchers.append(&mut self.watches[false_lit]);
+            self.watches[false_lit] = watchers;
+            if conflict.is_some() {
+                return conflict;
+            }
+        }
+        None
+    }
+
+    fn bump(&mut self, var: usize) {
+        self.activity[var] += self.increment;
+        if self.activity[var] > 1e100 {
+            for activity in self.activity.iter_mut() {
+                *activity *= 1e-100;
+            }
+            self.increment *= 1e-100;
+            // all the entries of the heap are stale now
+            self.heap = (1..=self.num_variables)
+                .filter(|&v| self.value[v << 1] == UNASSIGNED)
+                .map(|v| (self.activity[v].to_bits(), v))
+                .collect();
+        } else {
+            self.heap.push((self.activity[var].to_bits(), var));
+        }
+    }
+
+    // Returns the learnt clause, with the negation of the first unique
+    // implication point first and a literal of the level to backtrack to
+    // second, and this level
+    fn analyze(&mut self, conflict: usize) -> (Vec<usize>, usize) {
+        let mut learnt = vec![0];
+        // the number of literals of the current level left to walk back
+        let mut pending = 0;
+        let mut clause = conflict;
+        // the first literal of the reason of an implied literal is itself
+        let mut skip = 0;
+        let mut index = self.trail.len();
+        loop {
+            for k in skip..self.clauses[clause].len() {
+                let lit = self.clauses[clause][k];
+                let var = lit >> 1;
+                if !self.seen[var] && self.level[var] > 0 {
+                    self.seen[var] = true;
+                    self.bump(var);
+                    if self.level[var] == self.decision_level() {
+                        pending += 1;
+                    } else {
+                        learnt.push(lit);
+                    }
+                }
+            }
+            index -= 1;
+            while !self.seen[self.trail[index] >> 1] {
+                index -= 1;
+            }
+            let lit = self.trail[index];
+            self.seen[lit >> 1] = false;
+            pending -= 1;
+            if pending == 0 {
+                learnt[0] = lit ^ 1;
+                break;
+            }
+            clause = self.reason[lit >> 1].unwrap();
+            skip = 1;
+        }
+        for &lit in &learnt[1..] {
+            self.seen[lit >> 1] = false;
+        }
+
+        let mut backtrack_level = 0;
+        for i in 1..learnt.len() {
+            let level = self.level[learnt[i] >> 1];
+            if level > backtrack_level {
+                backtrack_level = level;
+                learnt.swap(1, i);
+            }
+        }
+        (learnt, backtrack_level)
+    }
+
+    fn cancel_until(&mut self, level: usize) {
+        if self.decision_level() <= level {
+            return;
+        }
+        let limit = self.trail_limits[level];
+        for lit in self.trail.drain(limit..) {
+            let var = lit >> 1;
+            self.value[lit] = UNASSIGNED;
+            self.value[lit ^ 1] = UNASSIGNED;
+            self.reason[var] = None;
+            self.phase[var] = lit & 1 == 0;
+            self.heap.push((self.activity[var].to_bits(), var));
+        }
+        self.trail_limits.truncate(level);
+        self.propagated = limit;
+    }
+
+    // The unassigned variable with the highest activity, None if all of them
+    // are assigned
+    fn pick_variable(&mut self) -> Option<usize> {
+        while let Some((activity, var)) = self.heap.pop() {
+            if self.value[var << 1] == UNASSIGNED && activity == self.activity[var].to_bits() {
+                return Some(var);
+            }
+        }
+        None
+    }
+
+    fn solve(&mut self) -> SatResult {
+        let mut restarts = 1;
+        let mut conflicts = 0;
+        loop {
+            if let Some(conflict) = self.propagate() {
+                if self.decision_level() == 0 {
+                    return SatResult::Unsatisfiable;
+                }
+                conflicts += 1;
+                let (learnt, level) = self.analyze(conflict);
+                self.cancel_until(level);
+                let implied = learnt[0];
+                let reason = (learnt.len() > 1).then(|| self.attach(learnt));
+                self.assign(implied, reason);
+                self.increment /= ACTIVITY_DECAY;
+            } else if conflicts >= RESTART_BASE * luby(restarts) {
+                self.cancel_until(0);
+                restarts += 1;
+                conflicts = 0;
+            } else {
+                match self.pick_variable() {
+                    None => {
+                        let model = (0..=self.num_variables)
+                            .map(|v| v > 0 && self.value[v << 1] == TRUE)
+                            .collect();
+                        return SatResult::Satisfiable(model);
+                    }
+                    Some(var) => {
+                        let lit = if self.phase[var] {
+                            var << 1
+                        } else {
+                            (var << 1) + 1
+                        };
+                        self.trail_limits.push(self.trail.len());
+                        self.assign(lit, None);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// The Luby sequence 1, 1, 2, 1, 1, 2, 4, 1, 1, 2, 1, 1, 2, 4, 8, ... (from
+// i = 1)
+fn luby(mut i: u64) -> u64 {
+    loop {
+        // the sequence up to 2^k - 1 is twice the one up to 2^(k - 1) - 1,
+        // then 2^(k - 1)
+        let mut k = 1;
+        while (1 << k) - 1 < i {
+            k += 1;
+        }
+        if (1 << k) - 1 == i {
+            return 1 << (k - 1);
+        }
+        i -= (1 << (k - 1)) - 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{parse_dimacs_cnf, solve_two_satisfiability, write_dimacs_solution};
+    use crate::math::PCG32;
+
+    fn satisfies(clauses: &[Vec<i64>], model: &[bool]) -> bool {
+        clauses.iter().all(|clause| {
+            clause
+                .iter()
+                .any(|&x| model[x.unsigned_abs() as usize] == (x > 0))
+        })
+    }
+
+    fn check(clauses: &[Vec<i64>], num_variables: usize) -> bool {
+        match solve_sat(clauses, num_variables) {
+            SatResult::Satisfiable(model) => {
+                assert_eq!(model.len(), num_variables + 1);
+                assert!(satisfies(clauses, &model));
+                true
+            }
+            SatResult::Unsatisfiable => false,
+        }
+    }
+
+    fn brute_force(clauses: &[Vec<i64>], num_variables: usize) -> bool {
+        (0..1u32 << num_variables).any(|mask| {
+            let model: Vec<bool> = (0..=num_variables)
+                .map(|v| v > 0 && mask >> (v - 1) & 1 == 1)
+                .collect();
+            satisfies(clauses, &model)
+        })
+    }
+
+    fn random_clauses(
+        num_variables: usize,
+        num_clauses: usize,
+        size: usize,
+        rng: &mut PCG32,
+    ) -> Vec<Vec<i64>> {
+        (0..num_clauses)
+            .map(|_| {
+                (0..size)
+                    .map(|_| {
+                        let var = 1 + (rng.get_u32() as usize % num_variables) as i64;
+                        if rng.get_u32().is_multiple_of(2) {
+                            var
+                        } else {
+                            -var
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    // p pigeons in h holes, at most one per hole: the variable (i - 1) * h + j
+    // puts the pigeon i in the hole j
+    fn pigeonhole(pigeons: i64, holes: i64) -> Vec<Vec<i64>> {
+        let var = |i: i64, j: i64| (i - 1) * holes + j;
+        let mut clauses: Vec<Vec<i64>> = (1..=pigeons)
+            .map(|i| (1..=holes).map(|j| var(i, j)).collect())
+            .collect();
+        for j in 1..=holes {
+            for i in 1..=pigeons {
+                for k in i + 1..=pigeons {
+                    clauses.push(vec![-var(i, j), -var(k, j)]);
+                }
+            }
+        }
+        clauses
+    }
+
+    #[test]
+    fn small_formulas() {
+        assert_eq!(solve_sat(&[], 2), SatResult::Satisfiable(vec![false; 3]));
+        assert!(check(&[vec![1, -1]], 1));
+        assert!(check(&[vec![-2], vec![1, 2], vec![-1, 2, 3]], 3));
+        assert!(!check(&[vec![]], 1));
+        assert!(!check(&[vec![1], vec![-1]], 1));
+        assert!(!check(
+            &[vec![1, 2], vec![1, -2], vec![-1, 2], vec![-1, -2]],
+            2
+        ));
+        assert_eq!(
+            solve_sat(&[vec![1, 2], vec![-1], vec![-2, 3, 3]], 3),
+            SatResult::Satisfiable(vec![false, false, true, true])
+        );
+    }
+
+    #[test]
+    fn pigeons() {
+        assert!(check(&pigeonhole(4, 4), 16));
+        assert!(!check(&pigeonhole(5, 4), 20));
+        assert!(!check(&pigeonhole(7, 6), 42));
+    }
+
+    #[test]
+    fn luby_sequence() {
+        let sequence: Vec<u64> = (1..=15).map(luby).collect();
+        assert_eq!(sequence, vec![1, 1, 2, 1, 1, 2, 4, 1, 1, 2, 1, 1, 2, 4, 8]);
+    }
+
+    #[test]
+    fn same_as_brute_force() {
+        let mut rng = PCG32::new_default(4242);
+        let mut satisfiable = 0;
+        for _ in 0..200 {
+            let n = 1 + rng.get_u32() as usize % 12;
+            // on both sides of the threshold of 4.26 clauses per variable for 3-SAT
+            let m = 1 + rng.get_u32() as usize % (10 * n);
+            let clauses = random_clauses(n, m, 3, &mut rng);
+            let expected = brute_force(&clauses, n);
+            assert_eq!(check(&clauses, n), expected);
+            satisfiable += expected as usize;
+        }
+        assert!(satisfiable > 50 && satisfiable < 150);
+    }
+
+    #[test]
+    fn planted_solution() {
+        // clauses satisfied by a hidden model, hard enough to need restarts
+        let mut rng = PCG32::new_default(77);
+        let n = 200;
+        let hidden: Vec<bool> = (0..=n).map(|_| rng.get_u32().is_multiple_of(2)).collect();
+        let mut clauses = vec![];
+        while clauses.len() < 850 {
+            let clause = random_clauses(n, 1, 3, &mut rng).pop().unwrap();
+            if satisfies(std::slice::from_ref(&clause), &hidden) {
+                clauses.push(clause);
+            }
+        }
+        assert!(check(&clauses, n));
+    }
+
+    #[test]
+    fn same_as_two_satisfiability() {
+        let mut rng = PCG32::new_default(2718);
+        for _ in 0..100 {
+            let n = 1 + rng.get_u32() as usize % 30;
+            let m = rng.get_u32() as usize % (2 * n);
+            let clauses = random_clauses(n, m, 2, &mut rng);
+            let conditions: Vec<(i64, i64)> = clauses.iter().map(|c| (c[0], c[1])).collect();
+            match solve_two_satisfiability(&conditions, n) {
+                Ok(model) => {
+                    assert!(satisfies(&clauses, &model));
+                    assert!(check(&clauses, n));
+                }
+                Err(conflict) => {
+                    assert!(conflict.component.contains(&conflict.variable));
+                    assert!(conflict.component.contains(&-conflict.variable));
+                    assert!(!check(&clauses, n));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn dimacs() {
+        let text = "c a small example\n\
+                    p cnf 3 4\n\
+                    1 2 0\n\
+                    -2 0\n\
+                    -1 -2 0\n\
+                    2 3 0\n";
+        let formula = parse_dimacs_cnf(text).unwrap();
+        let result = solve_sat(&formula.clauses, formula.num_variables);
+        assert_eq!(
+            write_dimacs_solution(&result),
+            "s SATISFIABLE\nv 1 -2 3 0\n"
+        );
+
+        let text = "p cnf 1 2\n1 0 -1 0\n";
+        let formula = parse_dimacs_cnf(text).unwrap();
+        let result = solve_sat(&formula.clauses, formula.num_variables);
+        assert_eq!(write_dimacs_solution(&result), "s UNSATISFIABLE\n");
+    }
+}
@@ -0,0 +1,187 @@
+/*
+The Shortest Path Faster Algorithm (SPFA), a queue-based Bellman-Ford.
+
+Instead of relaxing every edge in each round, only the out-going edges of the
+vertices whose distance has just changed are looked at: those vertices are
+kept in a FIFO queue (at most once at a time). The worst case is still
+O(n * m), but on most graphs it is much faster than the plain rounds of
+`bellman_ford`.
+
+A shortest path has at most n - 1 edges, so if the path found to a vertex
+ever has n edges, it goes around a negative cycle.
+*/
+
+use std::collections::{BTreeMap, VecDeque};
+use std::ops::Add;
+
+use super::{all_vertices, negative_cycle_from, Graph, PredecessorMap};
+
+// the shortest paths from the start, or a negative cycle
+type ShortestPaths<V, E> = Result<PredecessorMap<V, E>, Vec<V>>;
+
+// the graph is a directed graph, possibly with negative weights
+//
+// returns the same map as `bellman_ford` (for each reachable vertex, the
+// predecessor and the distance, map[start] being None), or the vertices of a
+// negative cycle reachable from start (the edge from the last one back to the
+// first closes it). E::default() should be zero.
+pub fn spfa<G: Graph>(graph: &G, start: &G::Vertex) -> ShortestPaths<G::Vertex, G::Weight>
+where
+    G::Weight: Ord + Add<Output = G::Weight> + Default,
+{
+    // the vertices that are only the destination of edges count too
+    let mut vertices = all_vertices(graph);
+    vertices.insert(*start);
+    let n = vertices.len();
+    // for each reached vertex: its distance, its predecessor and the number of
+    // edges of the path
+    let mut best = BTreeMap::new();
+    let mut in_queue = BTreeMap::new();
+    let mut queue = VecDeque::new();
+
+    best.insert(*start, (G::Weight::default(), None::<G::Vertex>, 0));
+    queue.push_back(*start);
+    in_queue.insert(*start, true);
+
+    while let Some(u) = queue.pop_front() {
+        in_queue.insert(u, false);
+        let (dist_u, _, edges_u) = best[&u];
+        for (v, w) in graph.neighbors(u) {
+            match best.get(&v) {
+                Some(&(dist_v, _, _)) if dist_u + w >= dist_v => {}
+                _ => {
+                    if edges_u + 1 >= n {
+                        // the path goes around a negative cycle
+                        if let Some(cycle) = negative_cycle_from(graph, start) {
+                            return Err(cycle);
+                        }
+                    }
+                    best.insert(v, (dist_u + w, Some(u), edges_u + 1));
+                    if !in_queue.get(&v).copied().unwrap_or(false) {
+                        queue.push_back(v);
+                        in_queue.insert(v, true);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(best
+        .into_iter()
+        .map(|(v, (dist, pred, _))| (v, pred.map(|p| (p, dist))))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{bellman_ford, AdjacencyMap};
+    use crate::math::PCG32;
+
+    fn add_edge(graph: &mut AdjacencyMap<u32, i64>, u: u32, v: u32, w: i64) {
+        graph.entry(u).or_default().insert(v, w);
+        graph.entry(v).or_default();
+    }
+
+    #[test]
+    fn small_graph() {
+        let mut graph = BTreeMap::new();
+        add_edge(&mut graph, 0, 1, 6);
+        add_edge(&mut graph, 0, 3, 7);
+        add_edge(&mut graph, 1, 2, 5);
+        add_edge(&mut graph, 1, 3, 8);
+        add_edge(&mut graph, 1, 4, -4);
+        add_edge(&mut graph, 2, 1, -2);
+        add_edge(&mut graph, 3, 2, -3);
+        add_edge(&mut graph, 3, 4, 9);
+        add_edge(&mut graph, 4, 0, 3);
+        add_edge(&mut graph, 4, 2, 7);
+
+        let mut dists = BTreeMap::new();
+        dists.insert(0, None);
+        dists.insert(1, Some((2, 2)));
+        dists.insert(2, Some((3, 4)));
+        dists.insert(3, Some((0, 7)));
+        dists.insert(4, Some((1, -2)));
+        assert_eq!(spfa(&graph, &0), Ok(dists));
+
+        // 1 -> 2 -> 1 now costs -1
+        add_edge(&mut graph, 2, 1, -6);
+        let cycle = spfa(&graph, &0).unwrap_err();
+        let total: i64 = (0..cycle.len())
+            .map(|i| graph[&cycle[i]][&cycle[(i + 1) % cycle.len()]])
+            .sum();
+        assert!(total < 0);
+    }
+
+    #[test]
+    fn single_vertex() {
+        let mut graph = BTreeMap::new();
+        graph.insert(0, BTreeMap::new());
+        assert_eq!(spfa(&graph, &0), Ok(BTreeMap::from([(0, None)])));
+
+        add_edge(&mut graph, 0, 0, -1);
+        assert_eq!(spfa(&graph, &0), Err(vec![0]));
+    }
+
+    #[test]
+    fn vertex_without_entry() {
+        // 2 is only the destination of an edge
+        let graph = BTreeMap::from([(1, vec![(2, 3)])]);
+        assert_eq!(
+            spfa(&graph, &1),
+            Ok(BTreeMap::from([(1, None), (2, Some((1, 3)))]))
+        );
+        assert_eq!(spfa(&graph, &2), Ok(BTreeMap::from([(2, None)])));
+    }
+
+    #[test]
+    fn same_as_bellman_ford() {
+        // a path against the order of the vertices, whose end has no entry
+        let graph: AdjacencyMap<u32, i64> =
+            (2..=5).map(|v| (v, BTreeMap::from([(v - 1, 1)]))).collect();
+        assert_eq!(spfa(&graph, &5), Ok(bellman_ford(&graph, &5).unwrap()));
+
+        let mut rng = PCG32::new_default(2718);
+        for _ in 0..50 {
+            let n = 1 + rng.get_u32() % 30;
+            let mut graph = BTreeMap::new();
+            for v in 0..n {
+                graph.insert(v, BTreeMap::new());
+            }
+            for _ in 0..rng.get_u32() % (4 * n) {
+                let u = rng.get_u32() % n;
+                let v = rng.get_u32() % n;
+                if u != v {
+                    add_edge(&mut graph, u, v, (rng.get_u32() % 30) as i64 - 4);
+                }
+            }
+
+            let start = rng.get_u32() % n;
+            // half of the time, vertices without out-going edges have no
+            // entry: some of them are only destinations
+            if rng.get_u32().is_multiple_of(2) {
+                graph.retain(|&v, neighbors| v == start || !neighbors.is_empty());
+            }
+            match spfa(&graph, &start) {
+                Ok(found) => {
+                    // the predecessors may differ between two shortest paths
+                    let expected = bellman_ford(&graph, &start).unwrap();
+                    let distances = |map: &PredecessorMap<u32, i64>| -> Vec<(u32, i64)> {
+                        map.iter()
+                            .map(|(&v, entry)| (v, entry.map_or(0, |(_, d)| d)))
+                            .collect()
+                    };
+                    assert_eq!(distances(&found), distances(&expected));
+                }
+                Err(cycle) => {
+                    assert_eq!(bellman_ford(&graph, &start), None);
+                    let total: i64 = (0..cycle.len())
+                        .map(|i| graph[&cycle[i]][&cycle[(i + 1) % cycle.len()]])
+                        .sum();
+                    assert!(total < 0);
+                }
+            }
+        }
+    }
+}
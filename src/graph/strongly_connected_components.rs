@@ -6,8 +6,36 @@ components in some (reverse) topologically sorted order.
 We assume that graph is represented using (compressed) adjacency matrix
 and its vertices are numbered from 1 to n. If this is not the case, one
 can use `src/graph/graph_enumeration.rs` to convert their graph.
+A `CompressedSparseRowGraph` with n + 1 vertices (vertex 0 being unused) can
+be used instead of the adjacency matrix too.
+
+Once the components are found, `condensation` builds the DAG of the
+components, and `reachability` answers "can u reach v" queries in O(1) with a
+bitset of the components reachable from each one (O(c^2 / 64) words for c
+components).
 */
 
+use super::{CompressedSparseRowGraph, Graph};
+
+// The out-going edges of a vertex, for both graph representations
+trait Successors {
+    fn successors(&self, v: usize) -> &[usize];
+}
+
+impl Successors for [Vec<usize>] {
+    #[inline]
+    fn successors(&self, v: usize) -> &[usize] {
+        &self[v]
+    }
+}
+
+impl<E: Copy> Successors for CompressedSparseRowGraph<E> {
+    #[inline]
+    fn successors(&self, v: usize) -> &[usize] {
+        self.targets(v)
+    }
+}
+
 pub struct StronglyConnectedComponents {
     // The number of the SCC the vertex is in, starting from 1
     pub component: Vec<usize>,
@@ -61,14 +89,14 @@ impl StronglyConnectedComponents {
             current_time: 1,
         }
     }
-    fn dfs(&mut self, v: usize, adj: &[Vec<usize>]) -> u64 {
+    fn dfs<A: Successors + ?Sized>(&mut self, v: usize, adj: &A) -> u64 {
         let mut min_disc = self.current_time as u64;
         // self.state[v] = NOT_DONE + min_disc
         self.state[v] ^= min_disc;
         self.current_time += 1;
         self.stack.push(v);
 
-        for &u in adj[v].iter() {
+        for &u in adj.successors(v) {
             if is_unvisited(self.state[u]) {
                 min_disc = std::cmp::min(self.dfs(u, adj), min_disc);
             } else if is_in_stack(self.state[u]) {
@@ -93,18 +121,100 @@ impl StronglyConnectedComponents {
         min_disc
     }
     pub fn find_components(&mut self, adj: &[Vec<usize>]) {
+        self.find_components_in(adj, adj.len());
+    }
+    pub fn find_components_compressed<E: Copy>(&mut self, graph: &CompressedSparseRowGraph<E>) {
+        self.find_components_in(graph, graph.num_vertices());
+    }
+    fn find_components_in<A: Successors + ?Sized>(&mut self, adj: &A, len: usize) {
         self.state[0] = 0;
-        for v in 1..adj.len() {
+        for v in 1..len {
             if is_unvisited(self.state[v]) {
                 self.dfs(v, adj);
             }
         }
     }
+
+    // The condensation of the graph (the same one given to
+    // `find_components`): a DAG whose vertices are the components, numbered
+    // from 1, with an edge between two components if there is one between
+    // their vertices. There are no duplicate edges and no loops.
+    //
+    // Tarjan's algorithm finds a component after all the ones it can reach,
+    // so every edge goes from a component to one with a smaller number.
+    pub fn condensation(&self, adj: &[Vec<usize>]) -> Vec<Vec<usize>> {
+        let mut dag = vec![vec![]; self.num_components + 1];
+        for (v, edges) in adj.iter().enumerate().skip(1) {
+            for &u in edges {
+                if self.component[v] != self.component[u] {
+                    dag[self.component[v]].push(self.component[u]);
+                }
+            }
+        }
+        for edges in dag.iter_mut() {
+            edges.sort_unstable();
+            edges.dedup();
+        }
+        dag
+    }
+
+    // The components in a topological order of the condensation
+    pub fn topological_order(&self) -> Vec<usize> {
+        (1..=self.num_components).rev().collect()
+    }
+
+    // Precomputes which components can be reached from each component, in
+    // O(c.m / 64), to answer reachability queries between vertices in O(1)
+    pub fn reachability(&self, adj: &[Vec<usize>]) -> Reachability {
+        let words = self.num_components / 64 + 1;
+        let mut reach = vec![vec![0u64; words]; self.num_components + 1];
+        let dag = self.condensation(adj);
+        // The components a component points to have smaller numbers, so they
+        // are done before it
+        for c in 1..=self.num_components {
+            let (done, rest) = reach.split_at_mut(c);
+            let current = &mut rest[0];
+            current[c / 64] |= 1 << (c % 64);
+            for &d in dag[c].iter() {
+                for (word, other) in current.iter_mut().zip(done[d].iter()) {
+                    *word |= other;
+                }
+            }
+        }
+        Reachability {
+            component: self.component.clone(),
+            reach,
+        }
+    }
+}
+
+// Answers "is there a path from u to v" for the graph given to
+// `StronglyConnectedComponents::reachability`
+pub struct Reachability {
+    component: Vec<usize>,
+    // A bitset of the components reachable from each component
+    reach: Vec<Vec<u64>>,
+}
+
+impl Reachability {
+    // A vertex can always reach itself
+    pub fn can_reach(&self, u: usize, v: usize) -> bool {
+        let (cu, cv) = (self.component[u], self.component[v]);
+        self.reach[cu][cv / 64] & (1 << (cv % 64)) != 0
+    }
+
+    // The number of vertices reachable from u (u included)
+    pub fn count_reachable(&self, u: usize) -> usize {
+        (1..self.component.len())
+            .filter(|&v| self.can_reach(u, v))
+            .count()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::math::PCG32;
 
     #[test]
     fn acyclic() {
@@ -161,4 +271,83 @@ mod tests {
         assert_eq!(sccs.state, vec![0, 1, 2, 3, 4, 5, 6]);
         assert_eq!(sccs.num_components, 1);
     }
+
+    #[test]
+    fn condensation_of_dumbbell() {
+        let mut sccs = StronglyConnectedComponents::new(7);
+        let adj = vec![
+            vec![],
+            vec![2],
+            vec![3, 4],
+            vec![1],
+            vec![5],
+            vec![6],
+            vec![4, 7],
+            vec![],
+        ];
+        sccs.find_components(&adj);
+        // {1, 2, 3} -> {4, 5, 6} -> {7}
+        assert_eq!(sccs.num_components, 3);
+        assert_eq!(
+            sccs.condensation(&adj),
+            vec![vec![], vec![], vec![1], vec![2]]
+        );
+        assert_eq!(sccs.topological_order(), vec![3, 2, 1]);
+
+        let reachability = sccs.reachability(&adj);
+        assert!(reachability.can_reach(1, 7));
+        assert!(reachability.can_reach(5, 4));
+        assert!(reachability.can_reach(7, 7));
+        assert!(!reachability.can_reach(4, 1));
+        assert!(!reachability.can_reach(7, 6));
+        assert_eq!(reachability.count_reachable(2), 7);
+        assert_eq!(reachability.count_reachable(6), 4);
+    }
+
+    #[test]
+    fn reachability_matches_search() {
+        let mut rng = PCG32::new_default(6174);
+        for round in 0..50 {
+            // enough components to need several words per bitset
+            let n = 10 + round * 4;
+            let mut adj = vec![vec![]; n + 1];
+            for _ in 0..n + round {
+                let u = rng.get_u32() as usize % n + 1;
+                let v = rng.get_u32() as usize % n + 1;
+                adj[u].push(v);
+            }
+            let mut sccs = StronglyConnectedComponents::new(n);
+            sccs.find_components(&adj);
+
+            let dag = sccs.condensation(&adj);
+            let order = sccs.topological_order();
+            let mut position = vec![0; sccs.num_components + 1];
+            for (i, &c) in order.iter().enumerate() {
+                position[c] = i;
+            }
+            for (c, edges) in dag.iter().enumerate() {
+                for &d in edges {
+                    assert!(position[c] < position[d]);
+                }
+            }
+
+            let reachability = sccs.reachability(&adj);
+            for u in 1..=n {
+                let mut seen = vec![false; n + 1];
+                let mut stack = vec![u];
+                seen[u] = true;
+                while let Some(v) = stack.pop() {
+                    for &w in adj[v].iter() {
+                        if !seen[w] {
+                            seen[w] = true;
+                            stack.push(w);
+                        }
+                    }
+                }
+                for (v, &reached) in seen.iter().enumerate().skip(1) {
+                    assert_eq!(reachability.can_reach(u, v), reached);
+                }
+            }
+        }
+    }
 }
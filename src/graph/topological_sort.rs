@@ -1,18 +1,52 @@
-use std::collections::{BTreeMap, VecDeque};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, VecDeque};
+use std::fmt;
+use std::ops::Add;
 
-type Graph<V, E> = BTreeMap<V, Vec<(V, E)>>;
+use super::{find_cycle, Graph};
 
-/// returns topological sort of the graph using Kahn's algorithm
-pub fn topological_sort<V: Ord + Copy, E: Ord>(graph: &Graph<V, E>) -> Vec<V> {
-    let mut visited = BTreeMap::new();
+/// The graph is not acyclic: `cycle` is one of its cycles, as its vertices
+/// in order (the edge from the last one back to the first closes it)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CycleError<V> {
+    pub cycle: Vec<V>,
+}
+
+impl<V: fmt::Debug> fmt::Display for CycleError<V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "the graph has a cycle: {:?}", self.cycle)
+    }
+}
+
+impl<V: fmt::Debug> std::error::Error for CycleError<V> {}
+
+fn cycle_error<G: Graph>(graph: &G) -> CycleError<G::Vertex> {
+    CycleError {
+        cycle: find_cycle(graph).expect("a graph without topological order has a cycle"),
+    }
+}
+
+/// A path and its length, or a cycle
+type LongestPath<V, E> = Result<(Vec<V>, E), CycleError<V>>;
+
+/// The in-degree of every vertex, including the ones that only appear as
+/// neighbours
+fn in_degrees<G: Graph>(graph: &G) -> BTreeMap<G::Vertex, usize> {
     let mut degree = BTreeMap::new();
-    for u in graph.keys() {
-        degree.insert(*u, 0);
-        for (v, _) in graph.get(u).unwrap() {
-            let entry = degree.entry(*v).or_insert(0);
-            *entry += 1;
+    for u in graph.vertices() {
+        degree.entry(u).or_insert(0);
+        for (v, _) in graph.neighbors(u) {
+            *degree.entry(v).or_insert(0) += 1;
         }
     }
+    degree
+}
+
+/// returns topological sort of the graph using Kahn's algorithm, or one of
+/// its cycles if it has any
+pub fn topological_sort<G: Graph>(graph: &G) -> Result<Vec<G::Vertex>, CycleError<G::Vertex>> {
+    let mut visited = BTreeMap::new();
+    let mut degree = in_degrees(graph);
     let mut queue = VecDeque::new();
     for (u, d) in degree.iter() {
         if *d == 0 {
@@ -23,25 +57,151 @@ pub fn topological_sort<V: Ord + Copy, E: Ord>(graph: &Graph<V, E>) -> Vec<V> {
     let mut ret = Vec::new();
     while let Some(u) = queue.pop_front() {
         ret.push(u);
-        if let Some(from_u) = graph.get(&u) {
-            for (v, _) in from_u {
-                *degree.get_mut(v).unwrap() -= 1;
-                if *degree.get(v).unwrap() == 0 {
-                    queue.push_back(*v);
-                    visited.insert(*v, true);
-                }
+        for (v, _) in graph.neighbors(u) {
+            *degree.get_mut(&v).unwrap() -= 1;
+            if *degree.get(&v).unwrap() == 0 {
+                queue.push_back(v);
+                visited.insert(v, true);
             }
         }
     }
-    ret
+    if ret.len() < degree.len() {
+        return Err(cycle_error(graph));
+    }
+    Ok(ret)
+}
+
+/// returns the lexicographically smallest topological order: Kahn's
+/// algorithm, always taking the smallest vertex with no remaining incoming
+/// edge, in O((n + m).log(n))
+pub fn lexicographic_topological_sort<G: Graph>(
+    graph: &G,
+) -> Result<Vec<G::Vertex>, CycleError<G::Vertex>> {
+    let mut degree = in_degrees(graph);
+    let mut heap: BinaryHeap<Reverse<G::Vertex>> = degree
+        .iter()
+        .filter(|(_, &d)| d == 0)
+        .map(|(&v, _)| Reverse(v))
+        .collect();
+    let mut order = Vec::new();
+    while let Some(Reverse(u)) = heap.pop() {
+        order.push(u);
+        for (v, _) in graph.neighbors(u) {
+            let d = degree.get_mut(&v).unwrap();
+            *d -= 1;
+            if *d == 0 {
+                heap.push(Reverse(v));
+            }
+        }
+    }
+    if order.len() < degree.len() {
+        return Err(cycle_error(graph));
+    }
+    Ok(order)
+}
+
+/// returns the topological orders of the graph in lexicographic order, at
+/// most `limit` of them (there can be up to n! orders)
+pub fn all_topological_sorts<G: Graph>(
+    graph: &G,
+    limit: usize,
+) -> Result<Vec<Vec<G::Vertex>>, CycleError<G::Vertex>> {
+    topological_sort(graph)?;
+    let mut degree = in_degrees(graph);
+    let vertices: Vec<G::Vertex> = degree.keys().copied().collect();
+    let mut orders = Vec::new();
+    let mut order = Vec::with_capacity(vertices.len());
+    extend_orders(
+        graph,
+        &vertices,
+        &mut degree,
+        &mut order,
+        &mut orders,
+        limit,
+    );
+    Ok(orders)
+}
+
+/// Backtracking: tries every vertex that can come next in `order`, smallest
+/// first
+fn extend_orders<G: Graph>(
+    graph: &G,
+    vertices: &[G::Vertex],
+    degree: &mut BTreeMap<G::Vertex, usize>,
+    order: &mut Vec<G::Vertex>,
+    orders: &mut Vec<Vec<G::Vertex>>,
+    limit: usize,
+) {
+    if order.len() == vertices.len() {
+        orders.push(order.clone());
+        return;
+    }
+    for &u in vertices {
+        if orders.len() >= limit {
+            return;
+        }
+        if degree[&u] != 0 {
+            continue;
+        }
+        // taken vertices are marked with a degree that can't reach zero
+        *degree.get_mut(&u).unwrap() = usize::MAX;
+        for (v, _) in graph.neighbors(u) {
+            *degree.get_mut(&v).unwrap() -= 1;
+        }
+        order.push(u);
+        extend_orders(graph, vertices, degree, order, orders, limit);
+        order.pop();
+        for (v, _) in graph.neighbors(u) {
+            *degree.get_mut(&v).unwrap() += 1;
+        }
+        *degree.get_mut(&u).unwrap() = 0;
+    }
+}
+
+/// returns a longest path of a weighted DAG and its length: the critical
+/// path when the vertices are tasks and the weight of an edge u -> v is how
+/// long u takes before v can start. It can start and end at any vertex, and
+/// is empty for an empty graph. W::default() should be zero.
+pub fn dag_longest_path<G: Graph>(graph: &G) -> LongestPath<G::Vertex, G::Weight>
+where
+    G::Weight: Ord + Add<Output = G::Weight> + Default,
+{
+    let order = topological_sort(graph)?;
+    // the length of the longest path ending at each vertex, and the
+    // previous vertex on it
+    let mut longest: BTreeMap<_, (G::Weight, Option<G::Vertex>)> = order
+        .iter()
+        .map(|&v| (v, (G::Weight::default(), None)))
+        .collect();
+    for &u in order.iter() {
+        let length = longest[&u].0;
+        for (v, w) in graph.neighbors(u) {
+            let entry = longest.get_mut(&v).unwrap();
+            if length + w > entry.0 {
+                *entry = (length + w, Some(u));
+            }
+        }
+    }
+    let end = match longest.iter().max_by_key(|(_, (length, _))| *length) {
+        Some((&end, _)) => end,
+        None => return Ok((vec![], G::Weight::default())),
+    };
+    let mut path = vec![end];
+    while let Some(prev) = longest[path.last().unwrap()].1 {
+        path.push(prev);
+    }
+    path.reverse();
+    Ok((path, longest[&end].0))
 }
 
 #[cfg(test)]
 mod tests {
     use std::collections::BTreeMap;
 
-    use super::{topological_sort, Graph};
-    fn add_edge<V: Ord + Copy, E: Ord>(graph: &mut Graph<V, E>, from: V, to: V, weight: E) {
+    use super::*;
+    use crate::graph::{random_dag, AdjacencyList};
+    use crate::math::PCG32;
+    fn add_edge<V: Ord + Copy, E: Ord>(graph: &mut AdjacencyList<V, E>, from: V, to: V, weight: E) {
         let edges = graph.entry(from).or_insert(Vec::new());
         edges.push((to, weight));
     }
@@ -57,6 +217,87 @@ mod tests {
         add_edge(&mut graph, 5, 6, 1);
         add_edge(&mut graph, 6, 7, 1);
 
-        assert_eq!(topological_sort(&graph), vec![1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(topological_sort(&graph), Ok(vec![1, 2, 3, 4, 5, 6, 7]));
+    }
+
+    #[test]
+    fn reports_a_cycle() {
+        let mut graph = BTreeMap::new();
+        add_edge(&mut graph, 'a', 'b', 1);
+        add_edge(&mut graph, 'b', 'c', 1);
+        add_edge(&mut graph, 'c', 'd', 1);
+        add_edge(&mut graph, 'd', 'b', 1);
+        add_edge(&mut graph, 'd', 'e', 1);
+
+        let cycle = CycleError {
+            cycle: vec!['b', 'c', 'd'],
+        };
+        assert_eq!(topological_sort(&graph), Err(cycle.clone()));
+        assert_eq!(lexicographic_topological_sort(&graph), Err(cycle.clone()));
+        assert_eq!(all_topological_sorts(&graph, 10), Err(cycle.clone()));
+        assert_eq!(dag_longest_path(&graph), Err(cycle.clone()));
+        assert_eq!(cycle.to_string(), "the graph has a cycle: ['b', 'c', 'd']");
+    }
+
+    #[test]
+    fn lexicographic_and_all_orders() {
+        let mut graph = BTreeMap::new();
+        add_edge(&mut graph, 5, 1, ());
+        add_edge(&mut graph, 4, 1, ());
+        add_edge(&mut graph, 3, 2, ());
+        add_edge(&mut graph, 2, 1, ());
+
+        assert_eq!(topological_sort(&graph), Ok(vec![3, 4, 5, 2, 1]));
+        assert_eq!(
+            lexicographic_topological_sort(&graph),
+            Ok(vec![3, 2, 4, 5, 1])
+        );
+
+        let orders = all_topological_sorts(&graph, 100).unwrap();
+        // 3 before 2, and 1 last: 4 and 5 go anywhere before 1
+        assert_eq!(orders.len(), 12);
+        assert_eq!(orders[0], vec![3, 2, 4, 5, 1]);
+        assert_eq!(orders[11], vec![5, 4, 3, 2, 1]);
+        assert!(orders.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(all_topological_sorts(&graph, 5).unwrap(), orders[..5]);
+    }
+
+    #[test]
+    fn orders_of_random_dags_are_valid() {
+        let mut rng = PCG32::new_default(18);
+        for _ in 0..20 {
+            let dag = random_dag(8, 0.3, &mut rng);
+            let orders = all_topological_sorts(&dag, 50).unwrap();
+            assert!(!orders.is_empty() && orders.len() <= 50);
+            assert_eq!(orders[0], lexicographic_topological_sort(&dag).unwrap());
+            for order in orders.iter().chain([&topological_sort(&dag).unwrap()]) {
+                let position: BTreeMap<usize, usize> =
+                    order.iter().enumerate().map(|(i, &v)| (v, i)).collect();
+                for (u, neighbors) in dag.iter() {
+                    assert!(neighbors.iter().all(|v| position[u] < position[v]));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn critical_path() {
+        // tasks and how long each takes before the next one can start
+        let mut graph = BTreeMap::new();
+        add_edge(&mut graph, "fetch", "compile", 3);
+        add_edge(&mut graph, "fetch", "docs", 3);
+        add_edge(&mut graph, "configure", "compile", 1);
+        add_edge(&mut graph, "compile", "link", 10);
+        add_edge(&mut graph, "docs", "package", 4);
+        add_edge(&mut graph, "link", "package", 2);
+        graph.insert("package", vec![]);
+
+        assert_eq!(
+            dag_longest_path(&graph),
+            Ok((vec!["fetch", "compile", "link", "package"], 15))
+        );
+
+        let empty: AdjacencyList<u8, i32> = BTreeMap::new();
+        assert_eq!(dag_longest_path(&empty), Ok((vec![], 0)));
     }
 }
@@ -12,15 +12,27 @@ fn variable(var: i64) -> usize {
     }
 }
 
-/// Returns an assignment that satisfies all the constraints, or a variable
-/// that makes such an assignment impossible. Variables should be numbered
-/// from 1 to n, and a negative number -m corresponds to the negated variable
-/// m. For more information about this problem, please visit:
+/// Why a 2-SAT expression can't be satisfied: a variable and its negation
+/// are in the same strongly connected component of the implication graph, so
+/// each one implies the other.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TwoSatConflict {
+    pub variable: i64,
+    /// The literals of the component (with `variable` and `-variable`), in
+    /// increasing order of variable
+    pub component: Vec<i64>,
+}
+
+/// Returns an assignment that satisfies all the constraints, or the variable
+/// that makes such an assignment impossible, with the component of the
+/// implication graph that contains it and its negation. Variables should be
+/// numbered from 1 to n, and a negative number -m corresponds to the negated
+/// variable m. For more information about this problem, please visit:
 /// https://en.wikipedia.org/wiki/2-satisfiability
 pub fn solve_two_satisfiability(
     expression: &[Condition],
     num_variables: usize,
-) -> Result<Vec<bool>, i64> {
+) -> Result<Vec<bool>, TwoSatConflict> {
     let num_verts = (num_variables + 1) << 1;
     let mut result = Vec::new();
     let mut sccs = SCCs::new(num_verts);
@@ -36,7 +48,21 @@ pub fn solve_two_satisfiability(
     result.resize(num_variables + 1, false);
     for var in (2..num_verts).step_by(2) {
         if sccs.component[var] == sccs.component[var ^ 1] {
-            return Err((var >> 1) as i64);
+            let component = (2..num_verts)
+                .filter(|&w| sccs.component[w] == sccs.component[var])
+                .map(|w| {
+                    let literal = (w >> 1) as i64;
+                    if w & 1 == 1 {
+                        -literal
+                    } else {
+                        literal
+                    }
+                })
+                .collect();
+            return Err(TwoSatConflict {
+                variable: (var >> 1) as i64,
+                component,
+            });
         }
         // if a variable isn't
         if sccs.component[var] < sccs.component[var ^ 1] {
@@ -90,6 +116,12 @@ mod tests {
         let conds = vec![(-1, -1), (-2, -2), (1, 2)];
         let res = solve_two_satisfiability(&conds, 2);
         assert!(res.is_err());
+
+        // 1 -> 2 -> 3 -> -1 -> 1, and 4 is free
+        let conds = vec![(-1, 2), (-2, 3), (-3, -1), (1, 1), (4, -4)];
+        let conflict = solve_two_satisfiability(&conds, 4).unwrap_err();
+        assert_eq!(conflict.variable, 1);
+        assert_eq!(conflict.component, vec![1, -1, 2, -2, 3, -3]);
     }
 
     #[test]